@@ -0,0 +1,322 @@
+use crate::{
+    expr::{self, Expr},
+    object::Object,
+    stmt::{self, Stmt},
+    token::Token,
+};
+use std::rc::Rc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AstStyle {
+    /// `(+ 1 (group (* 2 3)))`, one top-level statement per line.
+    SExpr,
+    /// One node per line, children indented under their parent.
+    Tree,
+}
+
+/// Renders parsed statements as they are after desugaring, e.g. a `for` loop shows
+/// up as the `block`/`while` it becomes.
+pub struct AstPrinter;
+
+/// A printable node. `head` is its S-expression form and `label` its tree form;
+/// `atom` nodes print without parentheses.
+struct Node {
+    head: String,
+    label: String,
+    atom: bool,
+    children: Vec<Node>,
+}
+
+impl Node {
+    fn new(head: impl Into<String>, label: impl Into<String>, children: Vec<Node>) -> Self {
+        Self {
+            head: head.into(),
+            label: label.into(),
+            atom: false,
+            children,
+        }
+    }
+
+    fn atom(head: String, label: String) -> Self {
+        Self {
+            atom: true,
+            ..Self::new(head, label, Vec::new())
+        }
+    }
+
+    fn sexpr(&self, out: &mut String) {
+        if self.atom {
+            out.push_str(&self.head);
+            return;
+        }
+        out.push('(');
+        out.push_str(&self.head);
+        for child in &self.children {
+            out.push(' ');
+            child.sexpr(out);
+        }
+        out.push(')');
+    }
+
+    fn tree(&self, depth: usize, out: &mut String) {
+        out.push_str(&"  ".repeat(depth));
+        out.push_str(&self.label);
+        out.push('\n');
+        for child in &self.children {
+            child.tree(depth + 1, out);
+        }
+    }
+
+    fn render(&self, style: AstStyle) -> String {
+        let mut out = String::new();
+        match style {
+            AstStyle::SExpr => {
+                self.sexpr(&mut out);
+                out.push('\n');
+            }
+            AstStyle::Tree => self.tree(0, &mut out),
+        }
+        out
+    }
+}
+
+impl AstPrinter {
+    pub fn print(statements: &[Stmt], style: AstStyle) -> String {
+        statements
+            .iter()
+            .map(|statement| statement.accept(&mut AstPrinter).render(style))
+            .collect()
+    }
+
+    #[cfg(test)]
+    pub(crate) fn print_expr(expr: &Expr, style: AstStyle) -> String {
+        let rendered = expr.accept(&mut AstPrinter).render(style);
+        rendered.trim_end().to_string()
+    }
+
+    fn expr(&mut self, expr: &Expr) -> Node {
+        expr.accept(self)
+    }
+
+    fn block(&mut self, head: &str, label: &str, statements: &[Stmt]) -> Node {
+        let children = statements.iter().map(|s| s.accept(self)).collect();
+        Node::new(head, label, children)
+    }
+
+    fn literal(value: &Object) -> String {
+        match value {
+            Object::String(s) => format!("{:?}", s),
+            Object::Number(n) => format!("{:?}", n),
+            value => value.to_string(),
+        }
+    }
+
+    fn params(params: &[Token]) -> String {
+        let names: Vec<&str> = params.iter().map(|p| p.lexeme.as_str()).collect();
+        names.join(" ")
+    }
+}
+
+impl expr::Visitor<Node> for AstPrinter {
+    fn visit_assign_expr(&mut self, expr: &expr::Assign) -> Node {
+        let value = self.expr(&expr.value);
+        Node::new(
+            format!("= {}", expr.name.lexeme),
+            format!("Assign {}", expr.name.lexeme),
+            vec![value],
+        )
+    }
+
+    fn visit_binary_expr(&mut self, expr: &expr::Binary) -> Node {
+        let children = vec![self.expr(&expr.left), self.expr(&expr.right)];
+        Node::new(
+            expr.operator.lexeme.clone(),
+            format!("Binary {}", expr.operator.lexeme),
+            children,
+        )
+    }
+
+    fn visit_call_expr(&mut self, expr: &expr::Call) -> Node {
+        let mut children = vec![self.expr(&expr.callee)];
+        children.extend(expr.arguments.iter().map(|arg| self.expr(arg)));
+        Node::new("call", "Call", children)
+    }
+
+    fn visit_conditional_expr(&mut self, expr: &expr::Conditional) -> Node {
+        let children = vec![
+            self.expr(&expr.condition),
+            self.expr(&expr.then_branch),
+            self.expr(&expr.else_branch),
+        ];
+        Node::new("?:", "Conditional", children)
+    }
+
+    fn visit_get_expr(&mut self, expr: &expr::Get) -> Node {
+        let object = self.expr(&expr.object);
+        Node::new(
+            format!(". {}", expr.name.lexeme),
+            format!("Get {}", expr.name.lexeme),
+            vec![object],
+        )
+    }
+
+    fn visit_group_expr(&mut self, expr: &expr::Grouping) -> Node {
+        let expression = self.expr(&expr.expression);
+        Node::new("group", "Grouping", vec![expression])
+    }
+
+    fn visit_literal_expr(&mut self, expr: &expr::Literal) -> Node {
+        let value = Self::literal(&expr.value);
+        Node::atom(value.clone(), format!("Literal {}", value))
+    }
+
+    fn visit_logical_expr(&mut self, expr: &expr::Logical) -> Node {
+        let children = vec![self.expr(&expr.left), self.expr(&expr.right)];
+        Node::new(
+            expr.operator.lexeme.clone(),
+            format!("Logical {}", expr.operator.lexeme),
+            children,
+        )
+    }
+
+    fn visit_set_expr(&mut self, expr: &expr::Set) -> Node {
+        let operator = match &expr.operator {
+            Some(operator) => format!("{}=", operator.lexeme),
+            None => "=".to_string(),
+        };
+        let children = vec![self.expr(&expr.object), self.expr(&expr.value)];
+        Node::new(
+            format!("{} .{}", operator, expr.name.lexeme),
+            format!("Set {} {}", expr.name.lexeme, operator),
+            children,
+        )
+    }
+
+    fn visit_super_expr(&mut self, _expr: &expr::Super) -> Node {
+        Node::atom("super".to_string(), "Super".to_string())
+    }
+
+    fn visit_this_expr(&mut self, _expr: &expr::This) -> Node {
+        Node::atom("this".to_string(), "This".to_string())
+    }
+
+    fn visit_unary_expr(&mut self, expr: &expr::Unary) -> Node {
+        let right = self.expr(&expr.right);
+        Node::new(
+            expr.operator.lexeme.clone(),
+            format!("Unary {}", expr.operator.lexeme),
+            vec![right],
+        )
+    }
+
+    fn visit_variable_expr(&mut self, expr: &expr::Variable) -> Node {
+        Node::atom(
+            expr.name.lexeme.clone(),
+            format!("Variable {}", expr.name.lexeme),
+        )
+    }
+}
+
+impl stmt::Visitor<Node> for AstPrinter {
+    fn visit_block_stmt(&mut self, stmt: &stmt::Block) -> Node {
+        self.block("block", "Block", &stmt.statements)
+    }
+
+    fn visit_break_stmt(&mut self, _stmt: &stmt::Break) -> Node {
+        Node::new("break", "Break", Vec::new())
+    }
+
+    fn visit_class_stmt(&mut self, _stmt: &stmt::Class) -> Node {
+        Node::new("class", "Class", Vec::new())
+    }
+
+    fn visit_continue_stmt(&mut self, _stmt: &stmt::Continue) -> Node {
+        Node::new("continue", "Continue", Vec::new())
+    }
+
+    fn visit_expr_stmt(&mut self, stmt: &stmt::Expression) -> Node {
+        let expression = self.expr(&stmt.expression);
+        Node::new(";", "Expression", vec![expression])
+    }
+
+    fn visit_func_stmt(&mut self, stmt: &Rc<stmt::Function>) -> Node {
+        let params = Self::params(&stmt.params);
+        self.block(
+            &format!("fun {} ({})", stmt.name.lexeme, params),
+            &format!(
+                "Function {}({})",
+                stmt.name.lexeme,
+                params.replace(' ', ", ")
+            ),
+            &stmt.body,
+        )
+    }
+
+    fn visit_if_stmt(&mut self, stmt: &stmt::If) -> Node {
+        let mut children = vec![self.expr(&stmt.condition), stmt.then_branch.accept(self)];
+        if let Some(else_branch) = &stmt.else_branch {
+            children.push(else_branch.accept(self));
+        }
+        Node::new("if", "If", children)
+    }
+
+    fn visit_import_stmt(&mut self, stmt: &stmt::Import) -> Node {
+        Node::new(
+            format!("import {} as {}", stmt.path.lexeme, stmt.name.lexeme),
+            format!("Import {} as {}", stmt.path.lexeme, stmt.name.lexeme),
+            Vec::new(),
+        )
+    }
+
+    fn visit_print_stmt(&mut self, stmt: &stmt::Print) -> Node {
+        let expression = self.expr(&stmt.expression);
+        Node::new("print", "Print", vec![expression])
+    }
+
+    fn visit_return_stmt(&mut self, stmt: &stmt::Return) -> Node {
+        let children = stmt.value.iter().map(|value| self.expr(value)).collect();
+        Node::new("return", "Return", children)
+    }
+
+    fn visit_throw_stmt(&mut self, stmt: &stmt::Throw) -> Node {
+        let value = self.expr(&stmt.value);
+        Node::new("throw", "Throw", vec![value])
+    }
+
+    fn visit_try_stmt(&mut self, stmt: &stmt::Try) -> Node {
+        let mut children = vec![self.block("block", "Block", &stmt.body)];
+        if let Some((name, body)) = &stmt.catch {
+            children.push(self.block(
+                &format!("catch {}", name.lexeme),
+                &format!("Catch {}", name.lexeme),
+                body,
+            ));
+        }
+        if let Some(body) = &stmt.finally {
+            children.push(self.block("finally", "Finally", body));
+        }
+        Node::new("try", "Try", children)
+    }
+
+    fn visit_var_stmt(&mut self, stmt: &stmt::Var) -> Node {
+        let children = stmt
+            .initializer
+            .iter()
+            .map(|init| self.expr(init))
+            .collect();
+        Node::new(
+            format!("var {}", stmt.name.lexeme),
+            format!("Var {}", stmt.name.lexeme),
+            children,
+        )
+    }
+
+    fn visit_while_stmt(&mut self, stmt: &stmt::While) -> Node {
+        let mut children = vec![self.expr(&stmt.condition), stmt.body.accept(self)];
+        if let Some(increment) = &stmt.increment {
+            let increment = self.expr(increment);
+            children.push(Node::new("increment", "Increment", vec![increment]));
+        }
+        Node::new("while", "While", children)
+    }
+}
@@ -0,0 +1,10 @@
+use std::{error::Error, io, process::exit};
+
+/// `lox-lsp`: a language server for Lox that speaks LSP over stdin and stdout.
+fn main() -> Result<(), Box<dyn Error>> {
+    let shut_down = lox::lsp::serve(io::stdin().lock(), io::stdout().lock())?;
+    if !shut_down {
+        exit(1);
+    }
+    Ok(())
+}
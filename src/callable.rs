@@ -1,9 +1,15 @@
 use std::error::Error;
 
-use crate::object::Object;
+use crate::error::{error_types::RuntimeError, LoxError};
+use crate::object::{Native, Object};
 
+use crate::interpreter::Interpreter;
 use crate::token::Token;
-use crate::Interpreter;
+
+mod io;
+mod math;
+mod string;
+mod testing;
 
 pub trait Callable {
     fn call(
@@ -18,41 +24,167 @@ pub trait Callable {
     }
 
     fn to_string(&self) -> String {
-        format!("<native fn>")
+        "<native fn>".to_string()
+    }
+}
+
+/// Host resources a script may reach through natives. Natives for a disabled
+/// capability are not defined at all.
+#[derive(Debug, Clone, Copy)]
+pub struct Capabilities {
+    pub clock: bool,
+    pub filesystem: bool,
+    pub stdin: bool,
+}
+
+impl Default for Capabilities {
+    fn default() -> Self {
+        Self {
+            clock: true,
+            filesystem: true,
+            stdin: true,
+        }
+    }
+}
+
+pub fn get_native_functions(capabilities: Capabilities) -> Vec<(&'static str, Object)> {
+    let mut natives = vec![
+        ("assert", Object::Function(None, Some(Native::Fn(assert)))),
+        ("int", Object::Function(None, Some(Native::Fn(int)))),
+        ("float", Object::Function(None, Some(Native::Fn(float)))),
+    ];
+    natives.extend(string::get_native_functions());
+    natives.extend(math::get_native_functions());
+    natives.extend(testing::get_native_functions());
+    if capabilities.clock {
+        natives.push(("clock", Object::Function(None, Some(Native::Fn(clock)))));
+    }
+    if capabilities.filesystem {
+        natives.extend(io::get_filesystem_functions());
+    }
+    if capabilities.stdin {
+        natives.extend(io::get_stdin_functions());
+    }
+    natives
+}
+
+/// Builds the runtime error a native reports at the call site's closing paren.
+pub fn error(paren: &Token, message: &str) -> Box<dyn Error> {
+    Box::new(
+        LoxError::new()
+            .type_(Box::new(RuntimeError))
+            .at_token(paren.to_owned())
+            .message(message.to_string()),
+    )
+}
+
+pub fn check_arity(argv: &[Object], arity: usize, paren: &Token) -> Result<(), Box<dyn Error>> {
+    if argv.len() != arity {
+        return Err(error(
+            paren,
+            &format!("Expected {} arguments but got {}.", arity, argv.len()),
+        ));
     }
+    Ok(())
 }
 
-pub fn get_native_functions() -> Vec<(&'static str, Object)> {
-    vec![
-        ("clock", Object::Function(None, Some(clock))),
-        ("assert", Object::Function(None, Some(assert))),
-    ]
+fn string_arg<'a>(
+    argv: &'a [Object],
+    index: usize,
+    paren: &Token,
+) -> Result<&'a str, Box<dyn Error>> {
+    match &argv[index] {
+        Object::String(s) => Ok(s),
+        other => Err(error(
+            paren,
+            &format!(
+                "Argument {} must be a string, not {}.",
+                index + 1,
+                other.type_name()
+            ),
+        )),
+    }
 }
 
-fn clock(argv: Vec<Object>) -> Result<Object, Box<dyn Error>> {
-    let argc = argv.len();
-    if argc != 0 {
-        return Err(format!("Expected 0 arguments found {} arguments", argc).into());
+fn int_arg(argv: &[Object], index: usize, paren: &Token) -> Result<i64, Box<dyn Error>> {
+    match &argv[index] {
+        Object::Int(i) => Ok(*i),
+        other => Err(error(
+            paren,
+            &format!(
+                "Argument {} must be an integer, not {}.",
+                index + 1,
+                other.type_name()
+            ),
+        )),
     }
+}
+
+fn number_arg(argv: &[Object], index: usize, paren: &Token) -> Result<f64, Box<dyn Error>> {
+    argv[index].as_float().ok_or_else(|| {
+        error(
+            paren,
+            &format!(
+                "Argument {} must be a number, not {}.",
+                index + 1,
+                argv[index].type_name()
+            ),
+        )
+    })
+}
+
+fn clock(argv: Vec<Object>, paren: &Token) -> Result<Object, Box<dyn Error>> {
+    check_arity(&argv, 0, paren)?;
 
     let current_time = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .unwrap()
-        .as_millis() as f64;
+        .as_millis() as i64;
 
-    Ok(Object::Number(current_time))
+    Ok(Object::Int(current_time))
 }
 
-fn assert(argv: Vec<Object>) -> Result<Object, Box<dyn Error>> {
-    let argc = argv.len();
-    if argc != 1 {
-        return Err(format!("Expected 1 argument found {} arguments", argc).into());
-    }
+fn assert(argv: Vec<Object>, paren: &Token) -> Result<Object, Box<dyn Error>> {
+    check_arity(&argv, 1, paren)?;
 
-    let arg = argv.get(0).unwrap();
+    let arg = argv.first().unwrap();
     if *arg == Object::Boolean(true) {
         return Ok(Object::Nil);
     }
 
-    Err(format!("Assertion failed").into())
+    Err(error(paren, "Assertion failed."))
+}
+
+fn int(argv: Vec<Object>, paren: &Token) -> Result<Object, Box<dyn Error>> {
+    check_arity(&argv, 1, paren)?;
+
+    match argv.into_iter().next().unwrap() {
+        Object::Int(i) => Ok(Object::Int(i)),
+        // `as` saturates, so check the range first to report overflow instead.
+        Object::Number(n) if n.is_finite() && n.trunc().abs() < i64::MAX as f64 => {
+            Ok(Object::Int(n.trunc() as i64))
+        }
+        Object::Number(n) => Err(error(paren, &format!("Can't convert {} to an integer.", n))),
+        Object::String(s) => s
+            .trim()
+            .parse::<i64>()
+            .map(Object::Int)
+            .map_err(|_| error(paren, &format!("Can't convert '{}' to an integer.", s))),
+        _ => Err(error(paren, "Argument must be a number or a string.")),
+    }
+}
+
+fn float(argv: Vec<Object>, paren: &Token) -> Result<Object, Box<dyn Error>> {
+    check_arity(&argv, 1, paren)?;
+
+    match argv.into_iter().next().unwrap() {
+        Object::Int(i) => Ok(Object::Number(i as f64)),
+        Object::Number(n) => Ok(Object::Number(n)),
+        Object::String(s) => s
+            .trim()
+            .parse::<f64>()
+            .map(Object::Number)
+            .map_err(|_| error(paren, &format!("Can't convert '{}' to a float.", s))),
+        _ => Err(error(paren, "Argument must be a number or a string.")),
+    }
 }
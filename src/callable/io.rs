@@ -0,0 +1,106 @@
+use std::{cell::RefCell, error::Error, fs, io::Write, rc::Rc};
+
+use super::{check_arity, error, string_arg};
+use crate::{
+    object::{Native, Object},
+    token::Token,
+};
+
+/// Natives that touch the filesystem; registered only with `Capabilities::filesystem`.
+pub fn get_filesystem_functions() -> Vec<(&'static str, Object)> {
+    vec![
+        (
+            "read_file",
+            Object::Function(None, Some(Native::Fn(read_file))),
+        ),
+        (
+            "write_file",
+            Object::Function(None, Some(Native::Fn(write_file))),
+        ),
+        (
+            "append_file",
+            Object::Function(None, Some(Native::Fn(append_file))),
+        ),
+        ("lines", Object::Function(None, Some(Native::Fn(lines)))),
+        ("exists", Object::Function(None, Some(Native::Fn(exists)))),
+    ]
+}
+
+/// Natives that read standard input; registered only with `Capabilities::stdin`.
+pub fn get_stdin_functions() -> Vec<(&'static str, Object)> {
+    vec![(
+        "read_line",
+        Object::Function(None, Some(Native::Fn(read_line))),
+    )]
+}
+
+fn io_error(paren: &Token, action: &str, path: &str, err: std::io::Error) -> Box<dyn Error> {
+    error(paren, &format!("Can't {} '{}': {}.", action, path, err))
+}
+
+fn read_file(argv: Vec<Object>, paren: &Token) -> Result<Object, Box<dyn Error>> {
+    check_arity(&argv, 1, paren)?;
+    let path = string_arg(&argv, 0, paren)?;
+
+    fs::read_to_string(path)
+        .map(Object::String)
+        .map_err(|err| io_error(paren, "read", path, err))
+}
+
+fn write_file(argv: Vec<Object>, paren: &Token) -> Result<Object, Box<dyn Error>> {
+    check_arity(&argv, 2, paren)?;
+    let path = string_arg(&argv, 0, paren)?;
+    let contents = string_arg(&argv, 1, paren)?;
+
+    fs::write(path, contents).map_err(|err| io_error(paren, "write", path, err))?;
+    Ok(Object::Nil)
+}
+
+fn append_file(argv: Vec<Object>, paren: &Token) -> Result<Object, Box<dyn Error>> {
+    check_arity(&argv, 2, paren)?;
+    let path = string_arg(&argv, 0, paren)?;
+    let contents = string_arg(&argv, 1, paren)?;
+
+    fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(contents.as_bytes()))
+        .map_err(|err| io_error(paren, "append to", path, err))?;
+    Ok(Object::Nil)
+}
+
+fn lines(argv: Vec<Object>, paren: &Token) -> Result<Object, Box<dyn Error>> {
+    check_arity(&argv, 1, paren)?;
+    let path = string_arg(&argv, 0, paren)?;
+
+    let contents = fs::read_to_string(path).map_err(|err| io_error(paren, "read", path, err))?;
+    let lines = contents
+        .lines()
+        .map(|line| Object::String(line.to_string()))
+        .collect();
+    Ok(Object::List(Rc::new(RefCell::new(lines))))
+}
+
+fn exists(argv: Vec<Object>, paren: &Token) -> Result<Object, Box<dyn Error>> {
+    check_arity(&argv, 1, paren)?;
+    let path = string_arg(&argv, 0, paren)?;
+    Ok(Object::Boolean(std::path::Path::new(path).exists()))
+}
+
+/// Returns the next line without its line ending, or `nil` at end of input.
+fn read_line(argv: Vec<Object>, paren: &Token) -> Result<Object, Box<dyn Error>> {
+    check_arity(&argv, 0, paren)?;
+
+    let mut line = String::new();
+    let read = std::io::stdin()
+        .read_line(&mut line)
+        .map_err(|err| error(paren, &format!("Can't read from stdin: {}.", err)))?;
+    if read == 0 {
+        return Ok(Object::Nil);
+    }
+
+    let trimmed = line.trim_end_matches(['\n', '\r']).len();
+    line.truncate(trimmed);
+    Ok(Object::String(line))
+}
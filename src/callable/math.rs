@@ -0,0 +1,200 @@
+use std::error::Error;
+
+use super::{check_arity, error, int_arg, number_arg};
+use crate::{
+    interpreter::Interpreter,
+    object::{Native, Object},
+    token::Token,
+};
+
+pub fn get_native_functions() -> Vec<(&'static str, Object)> {
+    vec![
+        ("PI", Object::Number(std::f64::consts::PI)),
+        ("E", Object::Number(std::f64::consts::E)),
+        ("sqrt", Object::Function(None, Some(Native::Fn(sqrt)))),
+        ("pow", Object::Function(None, Some(Native::Fn(pow)))),
+        ("abs", Object::Function(None, Some(Native::Fn(abs)))),
+        ("floor", Object::Function(None, Some(Native::Fn(floor)))),
+        ("ceil", Object::Function(None, Some(Native::Fn(ceil)))),
+        ("round", Object::Function(None, Some(Native::Fn(round)))),
+        ("min", Object::Function(None, Some(Native::Fn(min)))),
+        ("max", Object::Function(None, Some(Native::Fn(max)))),
+        ("sin", Object::Function(None, Some(Native::Fn(sin)))),
+        ("cos", Object::Function(None, Some(Native::Fn(cos)))),
+        ("tan", Object::Function(None, Some(Native::Fn(tan)))),
+        ("log", Object::Function(None, Some(Native::Fn(log)))),
+        ("exp", Object::Function(None, Some(Native::Fn(exp)))),
+        ("seed", Object::Function(None, Some(Native::Callback(seed)))),
+        (
+            "random",
+            Object::Function(None, Some(Native::Callback(random))),
+        ),
+        (
+            "random_int",
+            Object::Function(None, Some(Native::Callback(random_int))),
+        ),
+    ]
+}
+
+/// The next number from the interpreter's SplitMix64 generator.
+fn next_u64(interpreter: &Interpreter) -> u64 {
+    let seed = interpreter.rng.get().wrapping_add(0x9e37_79b9_7f4a_7c15);
+    interpreter.rng.set(seed);
+
+    let mut z = seed;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+fn float_fn(argv: Vec<Object>, paren: &Token, f: fn(f64) -> f64) -> Result<Object, Box<dyn Error>> {
+    check_arity(&argv, 1, paren)?;
+    Ok(Object::Number(f(number_arg(&argv, 0, paren)?)))
+}
+
+/// Rounding an `Int` leaves it unchanged; a float stays a float.
+fn rounding_fn(
+    argv: Vec<Object>,
+    paren: &Token,
+    f: fn(f64) -> f64,
+) -> Result<Object, Box<dyn Error>> {
+    check_arity(&argv, 1, paren)?;
+    match argv[0] {
+        Object::Int(i) => Ok(Object::Int(i)),
+        _ => Ok(Object::Number(f(number_arg(&argv, 0, paren)?))),
+    }
+}
+
+fn sqrt(argv: Vec<Object>, paren: &Token) -> Result<Object, Box<dyn Error>> {
+    float_fn(argv, paren, f64::sqrt)
+}
+
+fn pow(argv: Vec<Object>, paren: &Token) -> Result<Object, Box<dyn Error>> {
+    check_arity(&argv, 2, paren)?;
+    number_arg(&argv, 0, paren)?;
+    number_arg(&argv, 1, paren)?;
+
+    let mut argv = argv.into_iter();
+    match argv.next().unwrap().pow(argv.next().unwrap()) {
+        Object::Nil => Err(error(paren, "Integer overflow.")),
+        result => Ok(result),
+    }
+}
+
+fn abs(argv: Vec<Object>, paren: &Token) -> Result<Object, Box<dyn Error>> {
+    check_arity(&argv, 1, paren)?;
+    match argv[0] {
+        Object::Int(i) => i
+            .checked_abs()
+            .map(Object::Int)
+            .ok_or_else(|| error(paren, "Integer overflow.")),
+        _ => Ok(Object::Number(number_arg(&argv, 0, paren)?.abs())),
+    }
+}
+
+fn floor(argv: Vec<Object>, paren: &Token) -> Result<Object, Box<dyn Error>> {
+    rounding_fn(argv, paren, f64::floor)
+}
+
+fn ceil(argv: Vec<Object>, paren: &Token) -> Result<Object, Box<dyn Error>> {
+    rounding_fn(argv, paren, f64::ceil)
+}
+
+fn round(argv: Vec<Object>, paren: &Token) -> Result<Object, Box<dyn Error>> {
+    rounding_fn(argv, paren, f64::round)
+}
+
+/// Returns whichever argument wins, so `min(1, 2.5)` is the `Int` 1.
+fn extremum(
+    argv: Vec<Object>,
+    paren: &Token,
+    wins: fn(&Object, &Object) -> bool,
+) -> Result<Object, Box<dyn Error>> {
+    if argv.is_empty() {
+        return Err(error(paren, "Expected at least 1 argument but got 0."));
+    }
+    for index in 0..argv.len() {
+        number_arg(&argv, index, paren)?;
+    }
+
+    Ok(argv
+        .into_iter()
+        .reduce(|best, next| if wins(&next, &best) { next } else { best })
+        .unwrap())
+}
+
+fn min(argv: Vec<Object>, paren: &Token) -> Result<Object, Box<dyn Error>> {
+    extremum(argv, paren, |next, best| next < best)
+}
+
+fn max(argv: Vec<Object>, paren: &Token) -> Result<Object, Box<dyn Error>> {
+    extremum(argv, paren, |next, best| next > best)
+}
+
+fn sin(argv: Vec<Object>, paren: &Token) -> Result<Object, Box<dyn Error>> {
+    float_fn(argv, paren, f64::sin)
+}
+
+fn cos(argv: Vec<Object>, paren: &Token) -> Result<Object, Box<dyn Error>> {
+    float_fn(argv, paren, f64::cos)
+}
+
+fn tan(argv: Vec<Object>, paren: &Token) -> Result<Object, Box<dyn Error>> {
+    float_fn(argv, paren, f64::tan)
+}
+
+fn log(argv: Vec<Object>, paren: &Token) -> Result<Object, Box<dyn Error>> {
+    float_fn(argv, paren, f64::ln)
+}
+
+fn exp(argv: Vec<Object>, paren: &Token) -> Result<Object, Box<dyn Error>> {
+    float_fn(argv, paren, f64::exp)
+}
+
+fn seed(
+    interpreter: Interpreter,
+    argv: Vec<Object>,
+    paren: &Token,
+) -> Result<Object, Box<dyn Error>> {
+    check_arity(&argv, 1, paren)?;
+    let seed = int_arg(&argv, 0, paren)?;
+    interpreter.rng.set(seed as u64);
+    Ok(Object::Nil)
+}
+
+/// A float in `[0, 1)`.
+fn random(
+    interpreter: Interpreter,
+    argv: Vec<Object>,
+    paren: &Token,
+) -> Result<Object, Box<dyn Error>> {
+    check_arity(&argv, 0, paren)?;
+    Ok(Object::Number(
+        (next_u64(&interpreter) >> 11) as f64 / (1u64 << 53) as f64,
+    ))
+}
+
+/// An `Int` in `[low, high]`, both ends included.
+fn random_int(
+    interpreter: Interpreter,
+    argv: Vec<Object>,
+    paren: &Token,
+) -> Result<Object, Box<dyn Error>> {
+    check_arity(&argv, 2, paren)?;
+    let low = int_arg(&argv, 0, paren)?;
+    let high = int_arg(&argv, 1, paren)?;
+    if low > high {
+        return Err(error(
+            paren,
+            &format!("Empty range: {} is greater than {}.", low, high),
+        ));
+    }
+
+    let span = high.abs_diff(low).wrapping_add(1);
+    let offset = if span == 0 {
+        next_u64(&interpreter)
+    } else {
+        next_u64(&interpreter) % span
+    };
+    Ok(Object::Int(low.wrapping_add(offset as i64)))
+}
@@ -0,0 +1,224 @@
+use std::{cell::RefCell, error::Error, rc::Rc};
+
+use super::{check_arity, error, int_arg, string_arg};
+use crate::{
+    object::{Native, Object},
+    token::Token,
+};
+
+pub fn get_native_functions() -> Vec<(&'static str, Object)> {
+    vec![
+        ("len", Object::Function(None, Some(Native::Fn(len)))),
+        ("substr", Object::Function(None, Some(Native::Fn(substr)))),
+        (
+            "index_of",
+            Object::Function(None, Some(Native::Fn(index_of))),
+        ),
+        ("split", Object::Function(None, Some(Native::Fn(split)))),
+        ("join", Object::Function(None, Some(Native::Fn(join)))),
+        ("trim", Object::Function(None, Some(Native::Fn(trim)))),
+        ("upper", Object::Function(None, Some(Native::Fn(upper)))),
+        ("lower", Object::Function(None, Some(Native::Fn(lower)))),
+        ("replace", Object::Function(None, Some(Native::Fn(replace)))),
+        (
+            "starts_with",
+            Object::Function(None, Some(Native::Fn(starts_with))),
+        ),
+        (
+            "ends_with",
+            Object::Function(None, Some(Native::Fn(ends_with))),
+        ),
+        ("chars", Object::Function(None, Some(Native::Fn(chars)))),
+        ("ord", Object::Function(None, Some(Native::Fn(ord)))),
+        ("chr", Object::Function(None, Some(Native::Fn(chr)))),
+        ("str", Object::Function(None, Some(Native::Fn(str)))),
+        ("num", Object::Function(None, Some(Native::Fn(num)))),
+    ]
+}
+
+fn list(items: Vec<Object>) -> Object {
+    Object::List(Rc::new(RefCell::new(items)))
+}
+
+/// Lengths and indices count characters, not bytes.
+fn len(argv: Vec<Object>, paren: &Token) -> Result<Object, Box<dyn Error>> {
+    check_arity(&argv, 1, paren)?;
+
+    match &argv[0] {
+        Object::String(s) => Ok(Object::Int(s.chars().count() as i64)),
+        Object::List(items) => Ok(Object::Int(items.borrow().len() as i64)),
+        other => Err(error(
+            paren,
+            &format!(
+                "Argument 1 must be a string or a list, not {}.",
+                other.type_name()
+            ),
+        )),
+    }
+}
+
+/// `substr(s, start)` or `substr(s, start, length)`.
+fn substr(argv: Vec<Object>, paren: &Token) -> Result<Object, Box<dyn Error>> {
+    if argv.len() != 3 {
+        check_arity(&argv, 2, paren)?;
+    }
+
+    let s = string_arg(&argv, 0, paren)?;
+    let count = s.chars().count() as i64;
+    let start = int_arg(&argv, 1, paren)?;
+    if start < 0 || start > count {
+        return Err(error(paren, "Substring out of range."));
+    }
+    let length = if argv.len() == 3 {
+        int_arg(&argv, 2, paren)?
+    } else {
+        count - start
+    };
+    if length < 0 || length > count - start {
+        return Err(error(paren, "Substring out of range."));
+    }
+
+    Ok(Object::String(
+        s.chars()
+            .skip(start as usize)
+            .take(length as usize)
+            .collect(),
+    ))
+}
+
+fn index_of(argv: Vec<Object>, paren: &Token) -> Result<Object, Box<dyn Error>> {
+    check_arity(&argv, 2, paren)?;
+
+    let s = string_arg(&argv, 0, paren)?;
+    let needle = string_arg(&argv, 1, paren)?;
+    let index = s
+        .find(needle)
+        .map_or(-1, |byte| s[..byte].chars().count() as i64);
+    Ok(Object::Int(index))
+}
+
+fn split(argv: Vec<Object>, paren: &Token) -> Result<Object, Box<dyn Error>> {
+    check_arity(&argv, 2, paren)?;
+
+    let s = string_arg(&argv, 0, paren)?;
+    let separator = string_arg(&argv, 1, paren)?;
+    if separator.is_empty() {
+        return Err(error(paren, "Separator can't be empty."));
+    }
+
+    Ok(list(
+        s.split(separator)
+            .map(|part| Object::String(part.to_string()))
+            .collect(),
+    ))
+}
+
+fn join(argv: Vec<Object>, paren: &Token) -> Result<Object, Box<dyn Error>> {
+    check_arity(&argv, 2, paren)?;
+
+    let Object::List(items) = &argv[0] else {
+        return Err(error(
+            paren,
+            &format!("Argument 1 must be a list, not {}.", argv[0].type_name()),
+        ));
+    };
+    let separator = string_arg(&argv, 1, paren)?;
+
+    let parts: Vec<String> = items.borrow().iter().map(|item| item.to_string()).collect();
+    Ok(Object::String(parts.join(separator)))
+}
+
+fn trim(argv: Vec<Object>, paren: &Token) -> Result<Object, Box<dyn Error>> {
+    check_arity(&argv, 1, paren)?;
+    Ok(Object::String(
+        string_arg(&argv, 0, paren)?.trim().to_string(),
+    ))
+}
+
+fn upper(argv: Vec<Object>, paren: &Token) -> Result<Object, Box<dyn Error>> {
+    check_arity(&argv, 1, paren)?;
+    Ok(Object::String(string_arg(&argv, 0, paren)?.to_uppercase()))
+}
+
+fn lower(argv: Vec<Object>, paren: &Token) -> Result<Object, Box<dyn Error>> {
+    check_arity(&argv, 1, paren)?;
+    Ok(Object::String(string_arg(&argv, 0, paren)?.to_lowercase()))
+}
+
+fn replace(argv: Vec<Object>, paren: &Token) -> Result<Object, Box<dyn Error>> {
+    check_arity(&argv, 3, paren)?;
+
+    let s = string_arg(&argv, 0, paren)?;
+    let from = string_arg(&argv, 1, paren)?;
+    let to = string_arg(&argv, 2, paren)?;
+    if from.is_empty() {
+        return Err(error(paren, "Pattern can't be empty."));
+    }
+    Ok(Object::String(s.replace(from, to)))
+}
+
+fn starts_with(argv: Vec<Object>, paren: &Token) -> Result<Object, Box<dyn Error>> {
+    check_arity(&argv, 2, paren)?;
+    let s = string_arg(&argv, 0, paren)?;
+    Ok(Object::Boolean(s.starts_with(string_arg(&argv, 1, paren)?)))
+}
+
+fn ends_with(argv: Vec<Object>, paren: &Token) -> Result<Object, Box<dyn Error>> {
+    check_arity(&argv, 2, paren)?;
+    let s = string_arg(&argv, 0, paren)?;
+    Ok(Object::Boolean(s.ends_with(string_arg(&argv, 1, paren)?)))
+}
+
+fn chars(argv: Vec<Object>, paren: &Token) -> Result<Object, Box<dyn Error>> {
+    check_arity(&argv, 1, paren)?;
+
+    Ok(list(
+        string_arg(&argv, 0, paren)?
+            .chars()
+            .map(|ch| Object::String(ch.to_string()))
+            .collect(),
+    ))
+}
+
+fn ord(argv: Vec<Object>, paren: &Token) -> Result<Object, Box<dyn Error>> {
+    check_arity(&argv, 1, paren)?;
+
+    let mut chars = string_arg(&argv, 0, paren)?.chars();
+    match (chars.next(), chars.next()) {
+        (Some(ch), None) => Ok(Object::Int(ch as i64)),
+        _ => Err(error(paren, "Argument 1 must be a single character.")),
+    }
+}
+
+fn chr(argv: Vec<Object>, paren: &Token) -> Result<Object, Box<dyn Error>> {
+    check_arity(&argv, 1, paren)?;
+
+    let code = int_arg(&argv, 0, paren)?;
+    u32::try_from(code)
+        .ok()
+        .and_then(char::from_u32)
+        .map(|ch| Object::String(ch.to_string()))
+        .ok_or_else(|| error(paren, &format!("{} is not a valid code point.", code)))
+}
+
+fn str(argv: Vec<Object>, paren: &Token) -> Result<Object, Box<dyn Error>> {
+    check_arity(&argv, 1, paren)?;
+    Ok(Object::String(argv[0].to_string()))
+}
+
+/// Parses integer text as an `Int` and anything else numeric as a float.
+fn num(argv: Vec<Object>, paren: &Token) -> Result<Object, Box<dyn Error>> {
+    check_arity(&argv, 1, paren)?;
+
+    if argv[0].is_number() {
+        return Ok(argv[0].clone());
+    }
+
+    let s = string_arg(&argv, 0, paren)?.trim();
+    if let Ok(i) = s.parse::<i64>() {
+        return Ok(Object::Int(i));
+    }
+    s.parse::<f64>()
+        .map(Object::Number)
+        .map_err(|_| error(paren, &format!("Can't convert '{}' to a number.", s)))
+}
@@ -0,0 +1,126 @@
+use std::error::Error;
+
+use super::{error, string_arg, Callable};
+use crate::{
+    interpreter::Interpreter,
+    object::{Native, Object},
+    token::Token,
+};
+
+pub fn get_native_functions() -> Vec<(&'static str, Object)> {
+    vec![
+        (
+            "assert_eq",
+            Object::Function(None, Some(Native::Fn(assert_eq))),
+        ),
+        (
+            "assert_ne",
+            Object::Function(None, Some(Native::Fn(assert_ne))),
+        ),
+        (
+            "assert_throws",
+            Object::Function(None, Some(Native::Callback(assert_throws))),
+        ),
+    ]
+}
+
+/// How a failed assertion shows a value: like `print`, except that strings are
+/// quoted so that `"1"` and `1` can be told apart.
+fn show(value: &Object) -> String {
+    match value {
+        Object::String(s) => format!("{:?}", s),
+        value => format!("{}", value),
+    }
+}
+
+/// Checks for `required` arguments plus an optional message string at the end, and
+/// returns how a failure should be introduced.
+fn message_arg(
+    name: &str,
+    argv: &[Object],
+    required: usize,
+    paren: &Token,
+) -> Result<String, Box<dyn Error>> {
+    match argv.len() {
+        n if n == required => Ok(format!("{} failed", name)),
+        n if n == required + 1 => Ok(format!(
+            "{} failed ({})",
+            name,
+            string_arg(argv, required, paren)?
+        )),
+        n => Err(error(
+            paren,
+            &format!(
+                "Expected {} or {} arguments but got {}.",
+                required,
+                required + 1,
+                n
+            ),
+        )),
+    }
+}
+
+/// `assert_eq(actual, expected)` or `assert_eq(actual, expected, message)`.
+fn assert_eq(argv: Vec<Object>, paren: &Token) -> Result<Object, Box<dyn Error>> {
+    let failed = message_arg("assert_eq", &argv, 2, paren)?;
+    if argv[0] == argv[1] {
+        return Ok(Object::Nil);
+    }
+
+    Err(error(
+        paren,
+        &format!("{}: {} != {}.", failed, show(&argv[0]), show(&argv[1])),
+    ))
+}
+
+/// `assert_ne(actual, unexpected)` or `assert_ne(actual, unexpected, message)`.
+fn assert_ne(argv: Vec<Object>, paren: &Token) -> Result<Object, Box<dyn Error>> {
+    let failed = message_arg("assert_ne", &argv, 2, paren)?;
+    if argv[0] != argv[1] {
+        return Ok(Object::Nil);
+    }
+
+    Err(error(
+        paren,
+        &format!("{}: {} == {}.", failed, show(&argv[0]), show(&argv[1])),
+    ))
+}
+
+/// `assert_throws(function)` or `assert_throws(function, message)`. Calls `function`
+/// with no arguments and returns what it threw, as `catch` would bind it.
+fn assert_throws(
+    mut interpreter: Interpreter,
+    argv: Vec<Object>,
+    paren: &Token,
+) -> Result<Object, Box<dyn Error>> {
+    let failed = message_arg("assert_throws", &argv, 1, paren)?;
+    let function = &argv[0];
+    match function {
+        Object::Function(Some(f), _) if f.borrow().arity() != 0 => {
+            return Err(error(
+                paren,
+                "Argument 1 must be a function that takes no arguments.",
+            ));
+        }
+        Object::Function(..) => {}
+        other => {
+            return Err(error(
+                paren,
+                &format!("Argument 1 must be a function, not {}.", other.type_name()),
+            ))
+        }
+    }
+
+    match interpreter.call(function.clone(), None, Vec::new(), paren) {
+        Ok(value) => Err(error(
+            paren,
+            &format!(
+                "{}: {} returned {} without throwing.",
+                failed,
+                show(function),
+                show(&value)
+            ),
+        )),
+        Err(err) => Interpreter::caught(err.as_ref()).ok_or(err),
+    }
+}
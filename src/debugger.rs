@@ -0,0 +1,354 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    error::Error,
+    fmt,
+    io::{BufRead, Write},
+    path::PathBuf,
+};
+
+use crate::{
+    interpreter::debug::{DebugHook, Frame},
+    object::Object,
+    token::{token_type::TokenType, Token},
+};
+
+pub mod dap;
+
+const HELP: &str = "Commands:
+  break <line>, b      Pause before running the line; without a line, list breakpoints
+  delete <line>, d     Remove a breakpoint
+  continue, c          Run to the next breakpoint
+  step, s              Run to the next line, entering calls
+  next, n              Run to the next line in this function or its caller
+  finish, f            Run until the current function returns
+  backtrace, bt        Show the call stack
+  vars, v              Show the variables in every scope
+  print <name>, p      Show one variable
+  list, l              Show the source around the current line
+  quit, q              Stop the script
+An empty line repeats the last command.";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    Entry,
+    Breakpoint,
+    Step,
+}
+
+impl fmt::Display for StopReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            StopReason::Entry => "entry",
+            StopReason::Breakpoint => "breakpoint",
+            StopReason::Step => "step",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resume {
+    Continue,
+    StepIn,
+    StepOver,
+    StepOut,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Entry,
+    Run,
+    StepIn,
+    // The call depth stepping started at.
+    StepOver(usize),
+    StepOut(usize),
+}
+
+/// Decides where a script pauses: on entry, at breakpoints and after a step. Shared by
+/// the terminal and DAP front-ends.
+///
+/// Stepping is by line, so other statements on the line it paused at run without
+/// pausing again, though a loop coming back to the paused statement does. Breakpoints
+/// are lines of the script being debugged, not of files it imports.
+pub struct Stepper {
+    pub breakpoints: BTreeSet<i64>,
+    mode: Mode,
+    // Call depth, file, line and statement id of the last pause.
+    stopped: Option<(usize, Option<PathBuf>, i64, Option<usize>)>,
+}
+
+impl Stepper {
+    pub fn new(stop_on_entry: bool) -> Self {
+        Self {
+            breakpoints: BTreeSet::new(),
+            mode: if stop_on_entry {
+                Mode::Entry
+            } else {
+                Mode::Run
+            },
+            stopped: None,
+        }
+    }
+
+    /// Whether to pause before the statement the innermost frame is at, and why.
+    pub fn check(&mut self, frames: &[Frame]) -> Option<StopReason> {
+        let frame = frames.last()?;
+        let depth = frames.len();
+        let here = (depth, frame.path.clone(), frame.line, frame.statement);
+        // Other statements on the line just paused at run on; the paused statement
+        // itself, reached again by a loop, is a new visit to the line.
+        if let Some((stopped_depth, path, line, statement)) = &self.stopped {
+            if (*stopped_depth, path, *line) == (depth, &frame.path, frame.line)
+                && *statement != frame.statement
+            {
+                return None;
+            }
+        }
+        self.stopped = None;
+
+        let in_script = frame.path == frames[0].path;
+        let reason = match self.mode {
+            Mode::Entry => Some(StopReason::Entry),
+            Mode::StepIn => Some(StopReason::Step),
+            Mode::StepOver(start) if depth <= start => Some(StopReason::Step),
+            Mode::StepOut(start) if depth < start => Some(StopReason::Step),
+            _ if in_script && self.breakpoints.contains(&frame.line) => {
+                Some(StopReason::Breakpoint)
+            }
+            _ => None,
+        };
+
+        if reason.is_some() {
+            self.mode = Mode::Run;
+            self.stopped = Some(here);
+        }
+        reason
+    }
+
+    pub fn resume(&mut self, how: Resume, frames: &[Frame]) {
+        let depth = frames.len();
+        self.mode = match how {
+            Resume::Continue => Mode::Run,
+            Resume::StepIn => Mode::StepIn,
+            Resume::StepOver => Mode::StepOver(depth),
+            Resume::StepOut => Mode::StepOut(depth),
+        };
+    }
+
+    /// Lets the script run to the end without pausing again.
+    pub fn detach(&mut self) {
+        self.breakpoints.clear();
+        self.mode = Mode::Run;
+    }
+}
+
+/// Raised through the interpreter when the user stops the script from the debugger.
+pub struct Quit;
+
+impl fmt::Display for Quit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Stopped by the debugger.")
+    }
+}
+
+impl fmt::Debug for Quit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+impl Error for Quit {}
+
+/// How the debugger shows a value: like `print`, except that strings are quoted.
+pub fn display_value(value: &Object) -> String {
+    match value {
+        Object::String(s) => format!("{:?}", s),
+        value => format!("{}", value),
+    }
+}
+
+/// The variables in each scope visible from `frame`, innermost scope first and
+/// sorted by name within a scope.
+pub fn variables(frame: &Frame) -> Vec<(String, Vec<(String, String)>)> {
+    frame
+        .scopes()
+        .into_iter()
+        .map(|(kind, env)| {
+            let mut values: Vec<(String, String)> = env
+                .borrow()
+                .values
+                .iter()
+                .map(|(name, value)| (name.clone(), display_value(value)))
+                .collect();
+            values.sort();
+            (kind.to_string(), values)
+        })
+        .collect()
+}
+
+/// `lox debug`: a line-oriented debugger that reads commands from `input`, like gdb.
+/// It pauses before the first statement.
+pub struct Terminal<R, W> {
+    stepper: Stepper,
+    input: R,
+    output: W,
+    script: Vec<String>,
+    // Lines of imported files, read when the debugger first shows one.
+    sources: HashMap<PathBuf, Vec<String>>,
+    last_command: String,
+}
+
+impl<R: BufRead, W: Write> Terminal<R, W> {
+    pub fn new(source: &str, input: R, output: W) -> Self {
+        Self {
+            stepper: Stepper::new(true),
+            input,
+            output,
+            script: source.lines().map(str::to_string).collect(),
+            sources: HashMap::new(),
+            last_command: String::new(),
+        }
+    }
+
+    fn source_line(&mut self, frames: &[Frame], frame: &Frame, line: i64) -> Option<String> {
+        let lines = match &frame.path {
+            path if *path == frames[0].path => &self.script,
+            Some(path) => self.sources.entry(path.clone()).or_insert_with(|| {
+                let source = std::fs::read_to_string(path).unwrap_or_default();
+                source.lines().map(str::to_string).collect()
+            }),
+            None => return None,
+        };
+        let index = usize::try_from(line - 1).ok()?;
+        lines.get(index).cloned()
+    }
+
+    fn show_line(&mut self, frames: &[Frame], marker: &str, line: i64) -> std::io::Result<()> {
+        let frame = frames.last().unwrap();
+        if let Some(text) = self.source_line(frames, frame, line) {
+            writeln!(self.output, "{:>2} {:>4} | {}", marker, line, text)?;
+        }
+        Ok(())
+    }
+
+    /// Runs one command; returns how to resume, or `None` to stay paused.
+    fn command(&mut self, line: &str, frames: &[Frame]) -> Result<Option<Resume>, Box<dyn Error>> {
+        let frame = frames.last().unwrap();
+        let mut words = line.split_whitespace();
+        let command = words.next().unwrap_or_default();
+        let argument = words.next();
+
+        match command {
+            "continue" | "c" => return Ok(Some(Resume::Continue)),
+            "step" | "s" => return Ok(Some(Resume::StepIn)),
+            "next" | "n" => return Ok(Some(Resume::StepOver)),
+            "finish" | "f" => return Ok(Some(Resume::StepOut)),
+            "quit" | "q" => return Err(Box::new(Quit)),
+            "break" | "b" => match argument.map(str::parse::<i64>) {
+                Some(Ok(line)) => {
+                    self.stepper.breakpoints.insert(line);
+                    writeln!(self.output, "Breakpoint set at line {}.", line)?;
+                }
+                Some(Err(_)) => writeln!(self.output, "Usage: break <line>")?,
+                None if self.stepper.breakpoints.is_empty() => {
+                    writeln!(self.output, "No breakpoints.")?
+                }
+                None => {
+                    let lines: Vec<String> = self
+                        .stepper
+                        .breakpoints
+                        .iter()
+                        .map(i64::to_string)
+                        .collect();
+                    writeln!(self.output, "Breakpoints at lines {}.", lines.join(", "))?;
+                }
+            },
+            "delete" | "d" => match argument.and_then(|arg| arg.parse::<i64>().ok()) {
+                Some(line) if self.stepper.breakpoints.remove(&line) => {
+                    writeln!(self.output, "Breakpoint at line {} removed.", line)?
+                }
+                Some(line) => writeln!(self.output, "No breakpoint at line {}.", line)?,
+                None => writeln!(self.output, "Usage: delete <line>")?,
+            },
+            "backtrace" | "bt" => {
+                for (i, frame) in frames.iter().rev().enumerate() {
+                    writeln!(self.output, "#{} {} at line {}", i, frame.name, frame.line)?;
+                }
+            }
+            "vars" | "v" => {
+                for (scope, values) in variables(frame) {
+                    writeln!(self.output, "{}:", scope)?;
+                    for (name, value) in values {
+                        writeln!(self.output, "  {} = {}", name, value)?;
+                    }
+                }
+            }
+            "print" | "p" => match argument {
+                Some(name) => {
+                    let token = Token::new(TokenType::IDENTIFIER, name.to_string(), None, 0);
+                    match frame.env.borrow().get(&token) {
+                        Ok(value) => writeln!(self.output, "{} = {}", name, display_value(&value))?,
+                        Err(_) => writeln!(self.output, "Undefined variable '{}'.", name)?,
+                    }
+                }
+                None => writeln!(self.output, "Usage: print <name>")?,
+            },
+            "list" | "l" => {
+                for line in (frame.line - 3).max(1)..=frame.line + 3 {
+                    let marker = if line == frame.line { "->" } else { "" };
+                    self.show_line(frames, marker, line)?;
+                }
+            }
+            "help" | "h" => writeln!(self.output, "{}", HELP)?,
+            _ => writeln!(
+                self.output,
+                "Unknown command '{}'. Type 'help' for a list.",
+                command
+            )?,
+        }
+        Ok(None)
+    }
+}
+
+impl<R: BufRead, W: Write> DebugHook for Terminal<R, W> {
+    fn on_statement(&mut self, frames: &[Frame]) -> Result<(), Box<dyn Error>> {
+        let Some(reason) = self.stepper.check(frames) else {
+            return Ok(());
+        };
+        let frame = frames.last().unwrap();
+        writeln!(
+            self.output,
+            "Paused at line {} in {} ({}).",
+            frame.line, frame.name, reason
+        )?;
+        self.show_line(frames, "->", frame.line)?;
+
+        loop {
+            write!(self.output, "(lox) ")?;
+            self.output.flush()?;
+
+            let mut line = String::new();
+            if self.input.read_line(&mut line)? == 0 {
+                // Out of commands: let the script finish.
+                writeln!(self.output)?;
+                self.stepper.detach();
+                return Ok(());
+            }
+            let line = match line.trim() {
+                "" => self.last_command.clone(),
+                line => line.to_string(),
+            };
+            self.last_command = line.clone();
+
+            if let Some(how) = self.command(&line, frames)? {
+                self.stepper.resume(how, frames);
+                return Ok(());
+            }
+        }
+    }
+
+    fn output(&mut self, text: &str) {
+        // Shares the writer so the script's output interleaves with the debugger's.
+        let _ = writeln!(self.output, "{}", text);
+    }
+}
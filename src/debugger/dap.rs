@@ -0,0 +1,410 @@
+use std::{
+    cell::RefCell,
+    collections::BTreeSet,
+    error::Error,
+    io::{self, BufRead, Write},
+    path::Path,
+    rc::Rc,
+};
+
+use crate::{
+    debugger::{display_value, variables, Quit, Resume, Stepper},
+    interpreter::{
+        debug::{DebugHook, Frame},
+        Interpreter,
+    },
+    json::Json,
+    lsp::{read_message, write_message},
+    parser::Parser,
+    resolver::Resolver,
+    scanner::Scanner,
+    token::{token_type::TokenType, Token},
+};
+
+// Lox runs on one thread, which is all DAP clients get told about.
+const THREAD_ID: i64 = 1;
+// Each frame's scopes get a block of variable references: frame * SCOPES + scope + 1.
+const SCOPES: usize = 16;
+
+/// The framed message stream to the client, shared by the adapter's startup loop and
+/// the hook that takes over while the script runs.
+struct Connection {
+    input: Box<dyn BufRead>,
+    output: Box<dyn Write>,
+    seq: i64,
+}
+
+impl Connection {
+    /// The next request, or `None` once the client hangs up.
+    fn read(&mut self) -> io::Result<Option<Json>> {
+        while let Some(body) = read_message(&mut self.input)? {
+            if let Ok(message) = Json::parse(&body) {
+                return Ok(Some(message));
+            }
+        }
+        Ok(None)
+    }
+
+    fn send(&mut self, mut fields: Vec<(&str, Json)>) -> io::Result<()> {
+        self.seq += 1;
+        fields.insert(0, ("seq", Json::from(self.seq)));
+        write_message(&mut self.output, &Json::object(fields))
+    }
+
+    fn respond(&mut self, request: &Json, body: Json) -> io::Result<()> {
+        self.send(vec![
+            ("type", Json::from("response")),
+            (
+                "request_seq",
+                request.get("seq").cloned().unwrap_or(Json::Null),
+            ),
+            ("success", Json::from(true)),
+            ("command", command(request).into()),
+            ("body", body),
+        ])
+    }
+
+    fn fail(&mut self, request: &Json, message: String) -> io::Result<()> {
+        self.send(vec![
+            ("type", Json::from("response")),
+            (
+                "request_seq",
+                request.get("seq").cloned().unwrap_or(Json::Null),
+            ),
+            ("success", Json::from(false)),
+            ("command", command(request).into()),
+            ("message", Json::from(message)),
+        ])
+    }
+
+    fn event(&mut self, event: &str, body: Json) -> io::Result<()> {
+        self.send(vec![
+            ("type", Json::from("event")),
+            ("event", Json::from(event)),
+            ("body", body),
+        ])
+    }
+
+    /// Answers the requests that mean the same whether or not the script is running.
+    /// Returns `false` for any other request.
+    fn common(&mut self, request: &Json, breakpoints: &mut BTreeSet<i64>) -> io::Result<bool> {
+        match command(request) {
+            "threads" => {
+                let thread =
+                    Json::object([("id", Json::from(THREAD_ID)), ("name", Json::from("main"))]);
+                self.respond(
+                    request,
+                    Json::object([("threads", Json::Array(vec![thread]))]),
+                )?;
+            }
+            "setBreakpoints" => {
+                *breakpoints = breakpoint_lines(request);
+                let verified = breakpoints
+                    .iter()
+                    .map(|line| {
+                        Json::object([("verified", Json::from(true)), ("line", Json::from(*line))])
+                    })
+                    .collect();
+                self.respond(
+                    request,
+                    Json::object([("breakpoints", Json::Array(verified))]),
+                )?;
+            }
+            "setExceptionBreakpoints" => self.respond(request, Json::object::<&str>([]))?,
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+fn command(request: &Json) -> &str {
+    request
+        .get("command")
+        .and_then(Json::as_str)
+        .unwrap_or_default()
+}
+
+fn arguments(request: &Json) -> &Json {
+    request.get("arguments").unwrap_or(&Json::Null)
+}
+
+fn breakpoint_lines(request: &Json) -> BTreeSet<i64> {
+    let breakpoints = arguments(request)
+        .get("breakpoints")
+        .and_then(Json::as_array)
+        .unwrap_or_default();
+    breakpoints
+        .iter()
+        .filter_map(|breakpoint| breakpoint.get("line")?.as_f64())
+        .map(|line| line as i64)
+        .collect()
+}
+
+/// Serves the Debug Adapter Protocol, launching the script named by the client's
+/// `launch` request once it sends `configurationDone`. Requests are only read while
+/// the script is paused, so `pause` is not supported.
+pub fn serve(
+    input: impl BufRead + 'static,
+    output: impl Write + 'static,
+) -> Result<(), Box<dyn Error>> {
+    let connection = Rc::new(RefCell::new(Connection {
+        input: Box::new(input),
+        output: Box::new(output),
+        seq: 0,
+    }));
+    let mut breakpoints = BTreeSet::new();
+    let mut program: Option<(String, bool)> = None;
+    let mut configured = false;
+
+    while program.is_none() || !configured {
+        let Some(request) = connection.borrow_mut().read()? else {
+            return Ok(());
+        };
+        let mut connection = connection.borrow_mut();
+        if connection.common(&request, &mut breakpoints)? {
+            continue;
+        }
+        match command(&request) {
+            "initialize" => {
+                let capabilities =
+                    Json::object([("supportsConfigurationDoneRequest", Json::from(true))]);
+                connection.respond(&request, capabilities)?;
+                connection.event("initialized", Json::object::<&str>([]))?;
+            }
+            "launch" => {
+                let arguments = arguments(&request);
+                match arguments.get("program").and_then(Json::as_str) {
+                    Some(path) => {
+                        let stop_on_entry = arguments.get("stopOnEntry") == Some(&Json::Bool(true));
+                        program = Some((path.to_string(), stop_on_entry));
+                        connection.respond(&request, Json::object::<&str>([]))?;
+                    }
+                    None => connection.fail(&request, "Missing 'program'.".to_string())?,
+                }
+            }
+            "configurationDone" => {
+                configured = true;
+                connection.respond(&request, Json::object::<&str>([]))?;
+            }
+            "disconnect" => {
+                connection.respond(&request, Json::object::<&str>([]))?;
+                return Ok(());
+            }
+            other => connection.fail(&request, format!("Unsupported request '{}'.", other))?,
+        }
+    }
+
+    let (path, stop_on_entry) = program.unwrap();
+    let mut stepper = Stepper::new(stop_on_entry);
+    stepper.breakpoints = breakpoints;
+    let adapter = Adapter {
+        connection: connection.clone(),
+        stepper,
+    };
+    let exit_code = match run(&path, adapter) {
+        Ok(()) => 0,
+        Err(err) if err.is::<Quit>() => 0,
+        Err(err) => {
+            let output = Json::object([
+                ("category", Json::from("stderr")),
+                ("output", Json::from(format!("{}\n", err))),
+            ]);
+            connection.borrow_mut().event("output", output)?;
+            70
+        }
+    };
+
+    let mut connection = connection.borrow_mut();
+    connection.event(
+        "exited",
+        Json::object([("exitCode", Json::from(exit_code as i64))]),
+    )?;
+    connection.event("terminated", Json::object::<&str>([]))?;
+    while let Some(request) = connection.read()? {
+        if connection.common(&request, &mut BTreeSet::new())? {
+            continue;
+        }
+        connection.respond(&request, Json::object::<&str>([]))?;
+        if command(&request) == "disconnect" {
+            break;
+        }
+    }
+    Ok(())
+}
+
+fn run(path: &str, adapter: Adapter) -> Result<(), Box<dyn Error>> {
+    let source = std::fs::read_to_string(path)?;
+    let tokens = Scanner::new(source).scan_tokens()?;
+    let statements = Parser::new(tokens).parse()?;
+
+    let mut interpreter = Interpreter::new().with_debugger(Box::new(adapter));
+    interpreter.set_script_path(Path::new(path))?;
+    Resolver::new(&mut interpreter).resolve(&statements)?;
+    interpreter.interpret(&statements)?;
+    Ok(())
+}
+
+/// The hook that reports pauses to the client and serves its requests until it
+/// resumes.
+struct Adapter {
+    connection: Rc<RefCell<Connection>>,
+    stepper: Stepper,
+}
+
+impl Adapter {
+    fn stack_trace(frames: &[Frame]) -> Json {
+        let frames: Vec<Json> = frames
+            .iter()
+            .rev()
+            .enumerate()
+            .map(|(id, frame)| {
+                let mut fields = vec![
+                    ("id", Json::from(id)),
+                    ("name", Json::from(frame.name.as_str())),
+                    ("line", Json::from(frame.line)),
+                    ("column", Json::from(1i64)),
+                ];
+                if let Some(path) = &frame.path {
+                    let source = Json::object([
+                        (
+                            "name",
+                            Json::from(path.file_name().unwrap().to_string_lossy().into_owned()),
+                        ),
+                        ("path", Json::from(path.display().to_string())),
+                    ]);
+                    fields.push(("source", source));
+                }
+                Json::object(fields)
+            })
+            .collect();
+        Json::object([
+            ("totalFrames", Json::from(frames.len())),
+            ("stackFrames", Json::Array(frames)),
+        ])
+    }
+
+    /// The frame a request's `frameId` names; ids count from the innermost frame.
+    fn frame<'a>(frames: &'a [Frame], id: Option<&Json>) -> Option<&'a Frame> {
+        let id = id.and_then(Json::as_f64).unwrap_or_default() as usize;
+        frames.iter().rev().nth(id)
+    }
+
+    fn scopes(frames: &[Frame], request: &Json) -> Option<Json> {
+        let id = arguments(request).get("frameId");
+        let frame = Self::frame(frames, id)?;
+        let id = id.and_then(Json::as_f64).unwrap_or_default() as usize;
+        let scopes = frame
+            .scopes()
+            .iter()
+            .enumerate()
+            .take(SCOPES)
+            .map(|(i, (kind, _))| {
+                Json::object([
+                    ("name", Json::from(kind.to_string())),
+                    ("variablesReference", Json::from(id * SCOPES + i + 1)),
+                    ("expensive", Json::from(false)),
+                ])
+            })
+            .collect();
+        Some(Json::object([("scopes", Json::Array(scopes))]))
+    }
+
+    fn variables(frames: &[Frame], request: &Json) -> Option<Json> {
+        let reference = arguments(request).get("variablesReference")?.as_f64()? as usize;
+        let reference = reference.checked_sub(1)?;
+        let frame = frames.iter().rev().nth(reference / SCOPES)?;
+        let (_, values) = variables(frame).into_iter().nth(reference % SCOPES)?;
+        let values = values
+            .into_iter()
+            .map(|(name, value)| {
+                Json::object([
+                    ("name", Json::from(name)),
+                    ("value", Json::from(value)),
+                    ("variablesReference", Json::from(0i64)),
+                ])
+            })
+            .collect();
+        Some(Json::object([("variables", Json::Array(values))]))
+    }
+
+    /// Evaluates a variable name, which is all the debugger can look up.
+    fn evaluate(frames: &[Frame], request: &Json) -> Option<Json> {
+        let arguments = arguments(request);
+        let frame = Self::frame(frames, arguments.get("frameId"))?;
+        let name = arguments.get("expression")?.as_str()?.trim();
+        let token = Token::new(TokenType::IDENTIFIER, name.to_string(), None, 0);
+        let value = frame.env.borrow().get(&token).ok()?;
+        Some(Json::object([
+            ("result", Json::from(display_value(&value))),
+            ("variablesReference", Json::from(0i64)),
+        ]))
+    }
+}
+
+impl DebugHook for Adapter {
+    fn on_statement(&mut self, frames: &[Frame]) -> Result<(), Box<dyn Error>> {
+        let Some(reason) = self.stepper.check(frames) else {
+            return Ok(());
+        };
+        let mut connection = self.connection.borrow_mut();
+        connection.event(
+            "stopped",
+            Json::object([
+                ("reason", Json::from(reason.to_string())),
+                ("threadId", Json::from(THREAD_ID)),
+                ("allThreadsStopped", Json::from(true)),
+            ]),
+        )?;
+
+        loop {
+            let Some(request) = connection.read()? else {
+                return Err(Box::new(Quit));
+            };
+            if connection.common(&request, &mut self.stepper.breakpoints)? {
+                continue;
+            }
+            let resume = match command(&request) {
+                "continue" => Some(Resume::Continue),
+                "next" => Some(Resume::StepOver),
+                "stepIn" => Some(Resume::StepIn),
+                "stepOut" => Some(Resume::StepOut),
+                _ => None,
+            };
+            if let Some(how) = resume {
+                let body = Json::object([("allThreadsContinued", Json::from(true))]);
+                connection.respond(&request, body)?;
+                self.stepper.resume(how, frames);
+                return Ok(());
+            }
+
+            let body = match command(&request) {
+                "stackTrace" => Some(Self::stack_trace(frames)),
+                "scopes" => Self::scopes(frames, &request),
+                "variables" => Self::variables(frames, &request),
+                "evaluate" => Self::evaluate(frames, &request),
+                "pause" => Some(Json::object::<&str>([])),
+                "disconnect" => {
+                    connection.respond(&request, Json::object::<&str>([]))?;
+                    return Err(Box::new(Quit));
+                }
+                other => {
+                    connection.fail(&request, format!("Unsupported request '{}'.", other))?;
+                    continue;
+                }
+            };
+            match body {
+                Some(body) => connection.respond(&request, body)?,
+                None => connection.fail(&request, "Invalid arguments.".to_string())?,
+            }
+        }
+    }
+
+    fn output(&mut self, text: &str) {
+        let output = Json::object([
+            ("category", Json::from("stdout")),
+            ("output", Json::from(format!("{}\n", text))),
+        ]);
+        // Output that can't be delivered has nowhere else to go.
+        let _ = self.connection.borrow_mut().event("output", output);
+    }
+}
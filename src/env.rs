@@ -12,6 +12,12 @@ pub struct Environment {
     pub enclosing: Option<Rc<RefCell<Environment>>>,
 }
 
+impl Default for Environment {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Environment {
     pub fn new() -> Self {
         Self {
@@ -32,13 +38,21 @@ impl Environment {
             return Ok(value.clone());
         }
 
+        if let Some(enclosing) = &self.enclosing {
+            return enclosing.borrow().get(token);
+        }
+
         Err(Self::error(
             format!("Undefined variable '{}'.", token.lexeme),
             token.clone(),
         ))
     }
 
-    pub fn get_at(&self, distance: i32, name: String) -> Result<Object, Box<dyn Error>> {
+    pub fn get_at(&self, distance: usize, name: String) -> Result<Object, Box<dyn Error>> {
+        if distance == 0 {
+            return Ok(self.values.get(&name).cloned().unwrap_or(Object::Nil));
+        }
+
         let env = self.ancestor(distance)?;
         if let Some(val) = env.borrow().values.get(&name) {
             return Ok(val.to_owned());
@@ -47,10 +61,12 @@ impl Environment {
         Ok(Object::Nil)
     }
 
-    pub fn ancestor(&self, distance: i32) -> Result<Rc<RefCell<Environment>>, Box<dyn Error>> {
-        let mut environ = Rc::new(RefCell::new(self.clone()));
+    /// Returns the environment `distance` hops up the chain; `distance` must be at least 1
+    /// since an environment holds no `Rc` to itself.
+    pub fn ancestor(&self, distance: usize) -> Result<Rc<RefCell<Environment>>, Box<dyn Error>> {
+        let mut environ = self.enclosing.clone().unwrap();
 
-        for _ in 0..distance {
+        for _ in 1..distance {
             let x = environ.borrow().enclosing.clone();
             environ = x.unwrap();
         }
@@ -87,11 +103,16 @@ impl Environment {
     }
 
     pub fn assign_at(
-        &self,
-        distance: i32,
+        &mut self,
+        distance: usize,
         name: &Token,
         value: &Object,
     ) -> Result<(), Box<dyn Error>> {
+        if distance == 0 {
+            self.values.insert(name.lexeme.clone(), value.to_owned());
+            return Ok(());
+        }
+
         self.ancestor(distance)?
             .borrow_mut()
             .values
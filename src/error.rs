@@ -22,6 +22,12 @@ impl fmt::Debug for LoxError {
 
 impl Error for LoxError {}
 
+impl Default for LoxError {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl LoxError {
     pub fn new() -> Self {
         Self {
@@ -55,6 +61,25 @@ impl LoxError {
         }
     }
 
+    pub fn get_message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+
+    pub fn line(&self) -> Option<i64> {
+        self.at_token.as_ref().map(|token| token.line)
+    }
+
+    pub fn token(&self) -> Option<&Token> {
+        self.at_token.as_ref()
+    }
+
+    /// Runtime errors are the ones a script can `catch`.
+    pub fn is_runtime_error(&self) -> bool {
+        self.error_type
+            .as_ref()
+            .is_some_and(|error_type| error_type.is_runtime())
+    }
+
     pub fn report(&self) -> String {
         if let Some(error_type) = &self.error_type {
             if let Some(token) = &self.at_token {
@@ -71,11 +96,28 @@ pub mod error_types {
 
     pub trait ErrorType {
         fn report(&self, token: Token, message: String) -> String;
+        fn is_runtime(&self) -> bool {
+            false
+        }
         fn write(&self, error_type: &str, line: i64, where_: &str, message: String) -> String {
             format!("{} [line {}] {}: {}", error_type, line, where_, message)
         }
     }
 
+    #[derive(Debug)]
+    pub struct ScanError;
+
+    impl ErrorType for ScanError {
+        fn report(&self, token: Token, message: String) -> String {
+            self.write(
+                "ScanError",
+                token.line,
+                format!(" at '{}'", token.lexeme).as_str(),
+                message,
+            )
+        }
+    }
+
     #[derive(Debug)]
     pub struct ParseError;
 
@@ -101,5 +143,9 @@ pub mod error_types {
         fn report(&self, token: Token, message: String) -> String {
             self.write("RuntimeError", token.line, "", message)
         }
+
+        fn is_runtime(&self) -> bool {
+            true
+        }
     }
 }
@@ -1,174 +1,184 @@
 use crate::{object::Object, token::Token};
-use std::any::Any;
-use std::cell::RefCell;
-use std::error::Error;
-use std::fmt::Debug;
-use std::rc::Rc;
-
-pub trait Expr: Debug + ExprClone {
-    fn accept(&mut self, visitor: &mut dyn Visitor) -> Result<Object, Box<dyn Error>>;
-    fn as_any(&self) -> &dyn Any;
-}
-pub trait ExprClone {
-    fn clone_box(&self) -> Box<dyn Expr>;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Identifies an AST node: a variable reference, so the resolver and interpreter agree
+/// on which occurrence a scope distance belongs to even when two share a name and a
+/// line, or a statement, so the debugger can tell statements on one line apart.
+pub(crate) fn next_id() -> usize {
+    NEXT_ID.fetch_add(1, Ordering::Relaxed)
 }
-impl<T> ExprClone for T
-where
-    T: 'static + Clone + Expr,
-{
-    fn clone_box(&self) -> Box<dyn Expr> {
-        Box::new(self.clone())
+
+#[derive(Debug, Clone)]
+pub enum Expr {
+    Assign(Assign), // a = 30
+    Binary(Binary),
+    Call(Call),
+    Conditional(Conditional), // c ? a : b
+    Get(Get),
+    Grouping(Grouping),
+    Literal(Literal),
+    Logical(Logical),
+    Set(Set),
+    Super(Super),
+    This(This),
+    Unary(Unary),
+    Variable(Variable),
+}
+
+impl Expr {
+    pub fn accept<R>(&self, visitor: &mut dyn Visitor<R>) -> R {
+        match self {
+            Expr::Assign(expr) => visitor.visit_assign_expr(expr),
+            Expr::Binary(expr) => visitor.visit_binary_expr(expr),
+            Expr::Call(expr) => visitor.visit_call_expr(expr),
+            Expr::Conditional(expr) => visitor.visit_conditional_expr(expr),
+            Expr::Get(expr) => visitor.visit_get_expr(expr),
+            Expr::Grouping(expr) => visitor.visit_group_expr(expr),
+            Expr::Literal(expr) => visitor.visit_literal_expr(expr),
+            Expr::Logical(expr) => visitor.visit_logical_expr(expr),
+            Expr::Set(expr) => visitor.visit_set_expr(expr),
+            Expr::Super(expr) => visitor.visit_super_expr(expr),
+            Expr::This(expr) => visitor.visit_this_expr(expr),
+            Expr::Unary(expr) => visitor.visit_unary_expr(expr),
+            Expr::Variable(expr) => visitor.visit_variable_expr(expr),
+        }
     }
-}
 
-impl Clone for Box<dyn Expr> {
-    fn clone(&self) -> Box<dyn Expr> {
-        self.clone_box()
+    /// The line of the expression's first token, or `None` if it's made only of
+    /// literals, which keep no token.
+    pub fn line(&self) -> Option<i64> {
+        match self {
+            Expr::Assign(expr) => Some(expr.name.line),
+            Expr::Binary(expr) => expr.left.line().or(Some(expr.operator.line)),
+            Expr::Call(expr) => expr.callee.line().or(Some(expr.paren.line)),
+            Expr::Conditional(expr) => expr.condition.line(),
+            Expr::Get(expr) => expr.object.line().or(Some(expr.name.line)),
+            Expr::Grouping(expr) => expr.expression.line(),
+            Expr::Literal(_) => None,
+            Expr::Logical(expr) => expr.left.line().or(Some(expr.operator.line)),
+            Expr::Set(expr) => expr.object.line().or(Some(expr.name.line)),
+            Expr::Super(expr) => Some(expr.keyword.line),
+            Expr::This(expr) => Some(expr.keyword.line),
+            Expr::Unary(expr) => Some(expr.operator.line),
+            Expr::Variable(expr) => Some(expr.name.line),
+        }
     }
 }
 
-pub trait Visitor {
-    fn visit_assign_expr(&mut self, expr: &Assign) -> Result<Object, Box<dyn Error>>; // a = 30;
-    fn visit_binary_expr(&mut self, expr: &mut Binary) -> Result<Object, Box<dyn Error>>;
-    fn visit_call_expr(&mut self, expr: &Call) -> Result<Object, Box<dyn Error>>;
-    fn visit_get_expr(&self, expr: &Get) -> Result<Object, Box<dyn Error>>;
-    fn visit_group_expr(&mut self, expr: &mut Grouping) -> Result<Object, Box<dyn Error>>;
-    fn visit_literal_expr(&self, expr: &Literal) -> Result<Object, Box<dyn Error>>;
-    fn visit_logical_expr(&mut self, expr: &Logical) -> Result<Object, Box<dyn Error>>;
-    fn visit_set_expr(&self, expr: &Set) -> Result<Object, Box<dyn Error>>;
-    fn visit_super_expr(&self, expr: &Super) -> Result<Object, Box<dyn Error>>;
-    fn visit_this_expr(&self, expr: &This) -> Result<Object, Box<dyn Error>>;
-    fn visit_unary_expr(&mut self, expr: &mut Unary) -> Result<Object, Box<dyn Error>>;
-    fn visit_variable_expr(&mut self, expr: &Variable) -> Result<Object, Box<dyn Error>>; // var a = 20;
+pub trait Visitor<R> {
+    fn visit_assign_expr(&mut self, expr: &Assign) -> R;
+    fn visit_binary_expr(&mut self, expr: &Binary) -> R;
+    fn visit_call_expr(&mut self, expr: &Call) -> R;
+    fn visit_conditional_expr(&mut self, expr: &Conditional) -> R;
+    fn visit_get_expr(&mut self, expr: &Get) -> R;
+    fn visit_group_expr(&mut self, expr: &Grouping) -> R;
+    fn visit_literal_expr(&mut self, expr: &Literal) -> R;
+    fn visit_logical_expr(&mut self, expr: &Logical) -> R;
+    fn visit_set_expr(&mut self, expr: &Set) -> R;
+    fn visit_super_expr(&mut self, expr: &Super) -> R;
+    fn visit_this_expr(&mut self, expr: &This) -> R;
+    fn visit_unary_expr(&mut self, expr: &Unary) -> R;
+    fn visit_variable_expr(&mut self, expr: &Variable) -> R;
 }
 
 #[derive(Debug, Clone)]
 pub struct Assign {
+    pub id: usize,
     pub name: Token,
-    pub value: Rc<RefCell<Box<dyn Expr>>>,
+    pub value: Box<Expr>,
 }
 
 impl Assign {
-    pub fn new(name: Token, value: Box<dyn Expr>) -> Self {
+    pub fn new(name: Token, value: Expr) -> Self {
         Self {
+            id: next_id(),
             name,
-            value: Rc::new(RefCell::new(value)),
+            value: Box::new(value),
         }
     }
 }
 
-impl Expr for Assign {
-    fn accept(&mut self, visitor: &mut dyn Visitor) -> Result<Object, Box<dyn Error>> {
-        visitor.visit_assign_expr(self)
-    }
-
-    fn as_any(&self) -> &dyn Any {
-        self
-    }
-}
-
 #[derive(Debug, Clone)]
 pub struct Binary {
-    pub left: Rc<RefCell<Box<dyn Expr>>>,
+    pub left: Box<Expr>,
     pub operator: Token,
-    pub right: Rc<RefCell<Box<dyn Expr>>>,
+    pub right: Box<Expr>,
 }
 
 impl Binary {
-    pub fn new(left: Box<dyn Expr>, operator: Token, right: Box<dyn Expr>) -> Self {
+    pub fn new(left: Expr, operator: Token, right: Expr) -> Self {
         Self {
-            left: Rc::new(RefCell::new(left)),
+            left: Box::new(left),
             operator,
-            right: Rc::new(RefCell::new(right)),
+            right: Box::new(right),
         }
     }
 }
 
-impl Expr for Binary {
-    fn accept(&mut self, visitor: &mut dyn Visitor) -> Result<Object, Box<dyn Error>> {
-        visitor.visit_binary_expr(self)
-    }
-
-    fn as_any(&self) -> &dyn Any {
-        self
-    }
-}
-
 #[derive(Debug, Clone)]
 pub struct Call {
-    pub callee: Rc<RefCell<Box<dyn Expr>>>,
+    pub callee: Box<Expr>,
     pub paren: Token,
-    pub arguments: Vec<Rc<RefCell<Box<dyn Expr>>>>,
+    pub arguments: Vec<Expr>,
 }
 
 impl Call {
-    pub fn new(callee: Box<dyn Expr>, paren: Token, arguments: Vec<Box<dyn Expr>>) -> Self {
+    pub fn new(callee: Expr, paren: Token, arguments: Vec<Expr>) -> Self {
         Self {
-            callee: Rc::new(RefCell::new(callee)),
+            callee: Box::new(callee),
             paren,
-            arguments: arguments
-                .into_iter()
-                .map(|arg| Rc::new(RefCell::new(arg)))
-                .collect(),
+            arguments,
         }
     }
 }
 
-impl Expr for Call {
-    fn accept(&mut self, visitor: &mut dyn Visitor) -> Result<Object, Box<dyn Error>> {
-        visitor.visit_call_expr(self)
-    }
+#[derive(Debug, Clone)]
+pub struct Conditional {
+    pub condition: Box<Expr>,
+    pub then_branch: Box<Expr>,
+    pub else_branch: Box<Expr>,
+}
 
-    fn as_any(&self) -> &dyn Any {
-        self
+impl Conditional {
+    pub fn new(condition: Expr, then_branch: Expr, else_branch: Expr) -> Self {
+        Self {
+            condition: Box::new(condition),
+            then_branch: Box::new(then_branch),
+            else_branch: Box::new(else_branch),
+        }
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct Get {
-    object: Box<dyn Expr>,
-    name: Token,
+    pub object: Box<Expr>,
+    pub name: Token,
 }
 
 impl Get {
-    fn new(object: Box<dyn Expr>, name: Token) -> Self {
-        Self { object, name }
-    }
-}
-
-impl Expr for Get {
-    fn accept(&mut self, visitor: &mut dyn Visitor) -> Result<Object, Box<dyn Error>> {
-        visitor.visit_get_expr(self)
-    }
-
-    fn as_any(&self) -> &dyn Any {
-        self
+    pub fn new(object: Expr, name: Token) -> Self {
+        Self {
+            object: Box::new(object),
+            name,
+        }
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct Grouping {
-    pub expression: Rc<RefCell<Box<dyn Expr>>>,
+    pub expression: Box<Expr>,
 }
 
 impl Grouping {
-    pub fn new(expression: Box<dyn Expr>) -> Self {
+    pub fn new(expression: Expr) -> Self {
         Self {
-            expression: Rc::new(RefCell::new(expression)),
+            expression: Box::new(expression),
         }
     }
 }
 
-impl Expr for Grouping {
-    fn accept(&mut self, visitor: &mut dyn Visitor) -> Result<Object, Box<dyn Error>> {
-        visitor.visit_group_expr(self)
-    }
-
-    fn as_any(&self) -> &dyn Any {
-        self
-    }
-}
-
 #[derive(Debug, Clone)]
 pub struct Literal {
     pub value: Object,
@@ -180,155 +190,106 @@ impl Literal {
     }
 }
 
-impl Expr for Literal {
-    fn accept(&mut self, visitor: &mut dyn Visitor) -> Result<Object, Box<dyn Error>> {
-        visitor.visit_literal_expr(self)
-    }
-
-    fn as_any(&self) -> &dyn Any {
-        self
-    }
-}
-
 #[derive(Debug, Clone)]
 pub struct Logical {
-    pub left: Rc<RefCell<Box<dyn Expr>>>,
+    pub left: Box<Expr>,
     pub operator: Token,
-    pub right: Rc<RefCell<Box<dyn Expr>>>,
+    pub right: Box<Expr>,
 }
 
 impl Logical {
-    pub fn new(left: Box<dyn Expr>, operator: Token, right: Box<dyn Expr>) -> Self {
+    pub fn new(left: Expr, operator: Token, right: Expr) -> Self {
         Self {
-            left: Rc::new(RefCell::new(left)),
+            left: Box::new(left),
             operator,
-            right: Rc::new(RefCell::new(right)),
+            right: Box::new(right),
         }
     }
 }
 
-impl Expr for Logical {
-    fn accept(&mut self, visitor: &mut dyn Visitor) -> Result<Object, Box<dyn Error>> {
-        visitor.visit_logical_expr(self)
-    }
-
-    fn as_any(&self) -> &dyn Any {
-        self
-    }
-}
-
+/// `object.name = value`, or with an `operator` the compound `object.name += value`,
+/// which must evaluate `object` only once.
 #[derive(Debug, Clone)]
 pub struct Set {
-    object: Box<dyn Expr>,
-    name: Token,
-    value: Box<dyn Expr>,
+    pub object: Box<Expr>,
+    pub name: Token,
+    pub operator: Option<Token>,
+    pub value: Box<Expr>,
 }
 
 impl Set {
-    fn new(object: Box<dyn Expr>, name: Token, value: Box<dyn Expr>) -> Self {
+    pub fn new(object: Box<Expr>, name: Token, operator: Option<Token>, value: Expr) -> Self {
         Self {
             object,
             name,
-            value,
+            operator,
+            value: Box::new(value),
         }
     }
 }
 
-impl Expr for Set {
-    fn accept(&mut self, visitor: &mut dyn Visitor) -> Result<Object, Box<dyn Error>> {
-        visitor.visit_set_expr(self)
-    }
-
-    fn as_any(&self) -> &dyn Any {
-        self
-    }
-}
-
+#[allow(dead_code)]
 #[derive(Debug, Clone)]
 pub struct Super {
+    id: usize,
     keyword: Token,
     method: Token,
 }
 
+#[allow(dead_code)]
 impl Super {
     fn new(keyword: Token, method: Token) -> Self {
-        Self { keyword, method }
-    }
-}
-
-impl Expr for Super {
-    fn accept(&mut self, visitor: &mut dyn Visitor) -> Result<Object, Box<dyn Error>> {
-        visitor.visit_super_expr(self)
-    }
-
-    fn as_any(&self) -> &dyn Any {
-        self
+        Self {
+            id: next_id(),
+            keyword,
+            method,
+        }
     }
 }
 
+#[allow(dead_code)]
 #[derive(Debug, Clone)]
 pub struct This {
+    id: usize,
     keyword: Token,
 }
 
+#[allow(dead_code)]
 impl This {
     fn new(keyword: Token) -> Self {
-        Self { keyword }
-    }
-}
-
-impl Expr for This {
-    fn accept(&mut self, visitor: &mut dyn Visitor) -> Result<Object, Box<dyn Error>> {
-        visitor.visit_this_expr(self)
-    }
-
-    fn as_any(&self) -> &dyn Any {
-        self
+        Self {
+            id: next_id(),
+            keyword,
+        }
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct Unary {
     pub operator: Token,
-    pub right: Rc<RefCell<Box<dyn Expr>>>,
+    pub right: Box<Expr>,
 }
 
 impl Unary {
-    pub fn new(operator: Token, right: Box<dyn Expr>) -> Self {
+    pub fn new(operator: Token, right: Expr) -> Self {
         Self {
             operator,
-            right: Rc::new(RefCell::new(right)),
+            right: Box::new(right),
         }
     }
 }
 
-impl Expr for Unary {
-    fn accept(&mut self, visitor: &mut dyn Visitor) -> Result<Object, Box<dyn Error>> {
-        visitor.visit_unary_expr(self)
-    }
-
-    fn as_any(&self) -> &dyn Any {
-        self
-    }
-}
-
 #[derive(Debug, Clone)]
 pub struct Variable {
+    pub id: usize,
     pub name: Token,
 }
 
 impl Variable {
     pub fn new(name: Token) -> Self {
-        Self { name }
-    }
-}
-
-impl Expr for Variable {
-    fn accept(&mut self, visitor: &mut dyn Visitor) -> Result<Object, Box<dyn Error>> {
-        visitor.visit_variable_expr(self)
-    }
-
-    fn as_any(&self) -> &dyn Any {
-        self
+        Self {
+            id: next_id(),
+            name,
+        }
     }
 }
@@ -0,0 +1,299 @@
+use std::error::Error;
+
+use crate::{
+    parser::Parser,
+    scanner::Scanner,
+    token::{token_type::TokenType, Token},
+};
+
+const INDENT: &str = "    ";
+
+/// Formats `source` in the canonical style: one statement per line, four-space
+/// indentation, `{` at the end of the line that opens a block, `} else {` on one line,
+/// single spaces around binary operators and at most one blank line in a row.
+/// An `if`, `else`, `while` or `for` body without braces goes on its own line, one
+/// level deeper. Comments are kept where they were.
+///
+/// Works on the token stream rather than the AST, which can't reproduce the source
+/// because the parser desugars `for` loops, `++` and string interpolation. The source
+/// is still parsed first, so only valid programs are formatted.
+pub fn format(source: &str) -> Result<String, Box<dyn Error>> {
+    let tokens = Scanner::new(source.to_string()).scan_tokens()?;
+    Parser::new(tokens).parse()?;
+
+    let tokens = Scanner::new(source.to_string())
+        .with_comments()
+        .scan_tokens()?;
+    Ok(Formatter::default().format(&tokens))
+}
+
+#[derive(Default)]
+struct Formatter {
+    out: String,
+    indent: usize,
+    paren_depth: usize,
+    // Whether the last token left a statement unfinished, so that a line a comment
+    // breaks it onto is indented one extra level.
+    continued: bool,
+    // Whether the last token ends an operand, which makes a following `-` binary,
+    // `++` postfix and `(` a call.
+    operand: bool,
+    // Whether the last token was a prefix operator, which binds to what follows.
+    prefix: bool,
+    // Set by `if`, `while` and `for` until their `(`, then holds the paren depth of
+    // the statement's header along with its keyword.
+    keyword: Option<TokenType>,
+    header: Option<(usize, TokenType)>,
+    // Set by the end of a header or by `else`, where a body starts.
+    body: Option<TokenType>,
+    // Whether each open block is the branch of an `if`, which an `else` can follow.
+    blocks: Vec<bool>,
+    if_branch: bool,
+    // The unbraced bodies being written, each indented one level, with the keyword
+    // that opened it and the number of blocks it's in.
+    bodies: Vec<(TokenType, usize)>,
+}
+
+impl Formatter {
+    fn format(mut self, tokens: &[Token]) -> String {
+        let mut prev: Option<&Token> = None;
+
+        for (i, token) in tokens.iter().enumerate() {
+            if token.type_ == TokenType::EOF {
+                break;
+            }
+
+            let next = tokens[i + 1..]
+                .iter()
+                .find(|t| !matches!(t.type_, TokenType::COMMENT | TokenType::DOC_COMMENT));
+            let mut ends_statement = false;
+
+            if let Some(prev) = prev {
+                if self.at_line_start() && Self::start_line(token) > prev.line + 1 {
+                    self.blank_line(token);
+                }
+            }
+
+            match token.type_ {
+                TokenType::COMMENT | TokenType::DOC_COMMENT => {
+                    let trailing = prev.is_some_and(|p| p.line == Self::start_line(token));
+                    let reopened = trailing && self.at_line_start() && !self.out.is_empty();
+                    if reopened {
+                        self.out.pop();
+                        self.out.push(' ');
+                    } else if !self.at_line_start() {
+                        self.out.push(' ');
+                    } else {
+                        self.write_indent();
+                    }
+                    self.out.push_str(token.lexeme.trim_end());
+
+                    // A block comment inside a line stays there, unless it trails a
+                    // line that had already ended.
+                    let line_break = tokens
+                        .get(i + 1)
+                        .is_some_and(|next| Self::start_line(next) > token.line);
+                    if token.lexeme.starts_with("//") || reopened || line_break {
+                        self.out.push('\n');
+                    }
+                    prev = Some(token);
+                    continue;
+                }
+                TokenType::LEFT_BRACE => {
+                    if self.at_line_start() {
+                        self.continued = false;
+                        self.write_indent();
+                    } else {
+                        self.out.push(' ');
+                    }
+                    self.out.push_str("{\n");
+                    self.indent += 1;
+                    let if_branch = std::mem::take(&mut self.if_branch);
+                    self.blocks.push(if_branch);
+                }
+                TokenType::RIGHT_BRACE => {
+                    self.indent = self.indent.saturating_sub(1);
+                    if self.out.ends_with("{\n") {
+                        self.out.pop();
+                    } else {
+                        if !self.at_line_start() {
+                            self.out.push('\n');
+                        }
+                        self.write_indent();
+                    }
+                    self.out.push('}');
+
+                    let if_branch = self.blocks.pop().unwrap_or(false);
+                    let continues = next.is_some_and(|t| match t.type_ {
+                        TokenType::ELSE => if_branch,
+                        TokenType::CATCH | TokenType::FINALLY => true,
+                        _ => false,
+                    });
+                    if !continues {
+                        self.out.push('\n');
+                        ends_statement = true;
+                    }
+                }
+                TokenType::SEMICOLON => {
+                    if self.at_line_start() {
+                        self.write_indent();
+                    }
+                    self.out.push(';');
+                    if !matches!(self.header, Some((_, TokenType::FOR))) {
+                        self.out.push('\n');
+                        ends_statement = true;
+                    }
+                }
+                _ => {
+                    if self.at_line_start() {
+                        self.write_indent();
+                    } else if let Some(prev) = prev {
+                        if self.space_between(prev, token) {
+                            self.out.push(' ');
+                        }
+                    }
+                    self.out.push_str(&token.lexeme);
+                }
+            }
+
+            self.track(token);
+            prev = Some(token);
+
+            if let Some(keyword) = self.body.take() {
+                self.open_body(keyword, next);
+            } else if ends_statement {
+                self.close_bodies(next);
+            }
+        }
+
+        let trimmed = self.out.trim_end();
+        if trimmed.is_empty() {
+            String::new()
+        } else {
+            format!("{}\n", trimmed)
+        }
+    }
+
+    /// Breaks the line before a body that isn't a block. `else if` stays on one line.
+    fn open_body(&mut self, keyword: TokenType, next: Option<&Token>) {
+        let inline = next.is_some_and(|t| {
+            t.type_ == TokenType::LEFT_BRACE
+                || (keyword == TokenType::ELSE && t.type_ == TokenType::IF)
+        });
+        if inline {
+            self.if_branch = keyword == TokenType::IF;
+        } else {
+            self.out.push('\n');
+            self.continued = false;
+            self.indent += 1;
+            self.bodies.push((keyword, self.blocks.len()));
+        }
+    }
+
+    /// A statement ends every unbraced body it completes, except that an `else` after
+    /// it belongs to the innermost `if`, which it then lines up with.
+    fn close_bodies(&mut self, next: Option<&Token>) {
+        while let Some((keyword, blocks)) = self.bodies.last() {
+            if *blocks != self.blocks.len() {
+                break;
+            }
+            let if_body = *keyword == TokenType::IF;
+            self.bodies.pop();
+            self.indent -= 1;
+            if if_body && next.is_some_and(|t| t.type_ == TokenType::ELSE) {
+                break;
+            }
+        }
+    }
+
+    fn at_line_start(&self) -> bool {
+        self.out.is_empty() || self.out.ends_with('\n')
+    }
+
+    /// Keeps a single blank line where the source had one or more, except at the
+    /// start or end of a block.
+    fn blank_line(&mut self, next: &Token) {
+        if self.out.is_empty()
+            || self.out.ends_with("\n\n")
+            || self.out.ends_with("{\n")
+            || next.type_ == TokenType::RIGHT_BRACE
+        {
+            return;
+        }
+        self.out.push('\n');
+    }
+
+    fn write_indent(&mut self) {
+        // Lines a comment breaks a statement or parentheses onto are indented one
+        // extra level.
+        let continuation = usize::from(self.continued || self.paren_depth > 0);
+        self.out
+            .push_str(&INDENT.repeat(self.indent + continuation));
+    }
+
+    /// A token's line is where it ends; strings and block comments can span lines.
+    fn start_line(token: &Token) -> i64 {
+        token.line - token.lexeme.matches('\n').count() as i64
+    }
+
+    fn space_between(&self, prev: &Token, token: &Token) -> bool {
+        use TokenType::*;
+
+        if self.prefix {
+            // `- -x` must not become `--x`, which scans as a decrement.
+            let glued = |a: char| prev.lexeme.ends_with(a) && token.lexeme.starts_with(a);
+            return glued('-') || glued('+');
+        }
+
+        let closes_interpolation =
+            matches!(token.type_, STRING | INTERPOLATION) && token.lexeme.starts_with('}');
+
+        match (&prev.type_, &token.type_) {
+            (_, RIGHT_PAREN | COMMA | DOT | SEMICOLON) => false,
+            (LEFT_PAREN | DOT | INTERPOLATION, _) => false,
+            _ if closes_interpolation => false,
+            (_, LEFT_PAREN) => !self.operand,
+            (_, PLUS_PLUS | MINUS_MINUS) => !self.operand,
+            _ => true,
+        }
+    }
+
+    fn track(&mut self, token: &Token) {
+        use TokenType::*;
+
+        let (operand, prefix) = match token.type_ {
+            IDENTIFIER | NUMBER | TRUE | FALSE | NIL | THIS | SUPER | RIGHT_PAREN | STRING => {
+                (true, false)
+            }
+            PLUS_PLUS | MINUS_MINUS => (self.operand, !self.operand),
+            MINUS => (false, !self.operand),
+            BANG => (false, true),
+            _ => (false, false),
+        };
+        self.operand = operand;
+        self.prefix = prefix;
+        self.continued = !matches!(token.type_, SEMICOLON | LEFT_BRACE | RIGHT_BRACE);
+
+        match token.type_ {
+            IF | WHILE | FOR => self.keyword = Some(token.type_.clone()),
+            ELSE => self.body = Some(ELSE),
+            LEFT_PAREN => {
+                if let Some(keyword) = self.keyword.take() {
+                    self.header = Some((self.paren_depth, keyword));
+                }
+                self.paren_depth += 1;
+            }
+            RIGHT_PAREN => {
+                self.paren_depth = self.paren_depth.saturating_sub(1);
+                if self
+                    .header
+                    .as_ref()
+                    .is_some_and(|h| h.0 == self.paren_depth)
+                {
+                    self.body = self.header.take().map(|h| h.1);
+                }
+            }
+            _ => {}
+        }
+    }
+}
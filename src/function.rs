@@ -1,25 +1,38 @@
 use crate::callable::Callable;
 use crate::env::Environment;
-use crate::interpreter::Interpreter;
+use crate::interpreter::{completion::Completion, Interpreter};
 use crate::object::Object;
 use crate::stmt;
 use crate::token::Token;
 
 use std::cell::RefCell;
 use std::error::Error;
+use std::path::PathBuf;
 use std::rc::Rc;
 
 #[derive(Debug)]
 pub struct Function {
-    pub declaration: stmt::Function,
+    pub declaration: Rc<stmt::Function>,
     pub closeure: Rc<RefCell<Environment>>,
+    // The top-level environment of the file the function was declared in, where
+    // names the resolver left unresolved are looked up.
+    pub globals: Rc<RefCell<Environment>>,
+    // The file the function was declared in; its body runs as part of that file.
+    pub path: Option<PathBuf>,
 }
 
 impl Function {
-    pub fn new(declaration: stmt::Function, closeure: Rc<RefCell<Environment>>) -> Self {
+    pub fn new(
+        declaration: Rc<stmt::Function>,
+        closeure: Rc<RefCell<Environment>>,
+        globals: Rc<RefCell<Environment>>,
+        path: Option<PathBuf>,
+    ) -> Self {
         Self {
             declaration,
             closeure,
+            globals,
+            path,
         }
     }
 }
@@ -33,27 +46,25 @@ impl Callable for Function {
     ) -> Result<Object, Box<dyn Error>> {
         let environment = Rc::new(RefCell::new(Environment::from(self.closeure.clone())));
 
-        for i in 0..self.declaration.params.len() {
-            environment
-                .borrow_mut()
-                .define(&self.declaration.params[i], arguments[i].clone())?;
+        for (param, argument) in self.declaration.params.iter().zip(arguments) {
+            environment.borrow_mut().define(param, argument)?;
         }
 
-        if let Err(err) =
-            interpreter.execute_block(self.declaration.body.clone(), environment.clone())
-        {
-            let v = err
-                .as_ref()
-                .downcast_ref::<crate::interpreter::return_v::Return>();
-
-            if let Some(val) = v {
-                return Ok(val.value.clone());
-            }
-
-            return Err(err);
+        interpreter.globals = self.globals.clone();
+        interpreter.script_path = self.path.clone();
+        let debug = interpreter.debug.clone();
+        if let Some(debug) = &debug {
+            debug.enter(self.declaration.name.lexeme.clone(), environment.clone());
+        }
+        let completion = interpreter.execute_block(&self.declaration.body, environment);
+        if let Some(debug) = &debug {
+            debug.exit();
         }
 
-        Ok(Object::Nil)
+        match completion? {
+            Completion::Return(value) => Ok(value),
+            _ => Ok(Object::Nil),
+        }
     }
 
     fn arity(&self) -> usize {
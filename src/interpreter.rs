@@ -1,58 +1,181 @@
-use return_v::Return;
-
 use crate::{
-    callable,
+    callable::{self, Capabilities},
     env::Environment,
     error::{error_types::RuntimeError, LoxError},
     expr::{self, Expr},
     function,
-    object::Object,
+    module::{Module, Modules},
+    object::{ErrorObject, Object},
+    parser::Parser,
+    resolver::Resolver,
+    scanner::Scanner,
     stmt::{self, Stmt},
     token::{token_type::TokenType, Token},
 };
 
-mod expr_key;
-pub mod return_v;
+pub mod completion;
+pub mod coverage;
+pub mod debug;
+pub mod interrupt;
+pub mod profile;
+pub mod sandbox;
+pub mod throw;
 
 use crate::callable::Callable;
-use expr_key::ExprKey;
-use std::{cell::RefCell, collections::HashMap, error::Error, ops::Not, rc::Rc};
+use completion::Completion;
+use coverage::{Branch, Coverage};
+use debug::{DebugHook, DebugSession};
+use interrupt::InterruptHandle;
+use profile::Profiler;
+use sandbox::{Limits, Sandbox};
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    error::Error,
+    ops::Not,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+use throw::Throw;
 
 #[derive(Debug, Clone)]
 pub struct Interpreter {
     pub env: Rc<RefCell<Environment>>,
     pub globals: Rc<RefCell<Environment>>,
-    pub locals: HashMap<ExprKey, i32>,
+    // Natives shared by the script and every module; encloses each of their top-level scopes.
+    pub builtins: Rc<RefCell<Environment>>,
+    // Scope distance of each resolved variable reference, keyed by its node id.
+    pub locals: HashMap<usize, usize>,
+    pub modules: Rc<RefCell<Modules>>,
+    // The file whose top-level code is running; imports resolve relative to it.
+    pub script_path: Option<PathBuf>,
+    // Host resources the script may reach, through natives and `import`.
+    pub capabilities: Capabilities,
+    // SplitMix64 state for `random()`; seeded from the clock until a script calls
+    // `seed(n)`. Shared by the interpreter's clones, but not with other interpreters.
+    pub rng: Rc<Cell<u64>>,
+    pub sandbox: Rc<Sandbox>,
+    pub interrupt: InterruptHandle,
+    pub debug: Option<Rc<DebugSession>>,
+    pub profiler: Option<Rc<Profiler>>,
+    pub coverage: Option<Rc<Coverage>>,
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Interpreter {
-    pub fn evaluate(
-        &mut self,
-        expr: Rc<RefCell<Box<dyn expr::Expr>>>,
-    ) -> Result<Object, Box<dyn std::error::Error>> {
-        expr.borrow_mut().accept(self)
+    pub fn evaluate(&mut self, expr: &Expr) -> Result<Object, Box<dyn std::error::Error>> {
+        self.sandbox.step()?;
+        expr.accept(self)
     }
 
     pub fn new() -> Self {
-        let environ = Rc::new(RefCell::new(Environment::new()));
-        let interpreter = Self {
+        let builtins = Rc::new(RefCell::new(Environment::new()));
+        let environ = Rc::new(RefCell::new(Environment::from(builtins.clone())));
+        Self {
             env: environ.clone(),
             globals: environ.clone(),
+            builtins,
             locals: HashMap::new(),
-        };
+            modules: Rc::new(RefCell::new(Modules::default())),
+            script_path: None,
+            capabilities: Capabilities::default(),
+            rng: Rc::new(Cell::new(
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map_or(0, |time| time.as_nanos() as u64),
+            )),
+            sandbox: Rc::new(Sandbox::default()),
+            interrupt: InterruptHandle::default(),
+            debug: None,
+            profiler: None,
+            coverage: None,
+        }
+        .with_capabilities(Capabilities::default())
+    }
 
-        for (name, function) in callable::get_native_functions() {
-            interpreter
-                .globals
-                .borrow_mut()
+    /// Defines only the natives that `capabilities` allows, and disallows `import`
+    /// without filesystem access.
+    pub fn with_capabilities(mut self, capabilities: Capabilities) -> Self {
+        self.capabilities = capabilities;
+        let mut builtins = self.builtins.borrow_mut();
+        builtins.values.clear();
+        for (name, function) in callable::get_native_functions(capabilities) {
+            builtins
                 .define(
                     &Token::new(TokenType::IDENTIFIER, name.to_string(), None, 0),
                     function,
                 )
                 .unwrap();
         }
+        drop(builtins);
+
+        self
+    }
+
+    /// The value `catch` binds for `err`, or `None` if scripts can't catch it.
+    /// Sandbox limits and interrupts always propagate.
+    pub fn caught(err: &(dyn Error + 'static)) -> Option<Object> {
+        if let Some(throw) = err.downcast_ref::<Throw>() {
+            return Some(throw.value.clone());
+        }
+
+        let err = err.downcast_ref::<LoxError>()?;
+        if !err.is_runtime_error() {
+            return None;
+        }
+        Some(Object::Error(Rc::new(ErrorObject {
+            message: err.get_message().unwrap_or_default().to_string(),
+            line: err.line().unwrap_or_default(),
+        })))
+    }
+
+    /// A handle that stops this interpreter, and every clone of it, from any thread.
+    pub fn interrupt_handle(&self) -> InterruptHandle {
+        self.interrupt.clone()
+    }
+
+    /// Runs under `limits` instead of the default, unlimited sandbox.
+    pub fn with_limits(self, limits: Limits) -> Self {
+        Self {
+            sandbox: Rc::new(Sandbox::new(limits)),
+            ..self
+        }
+    }
+
+    /// Pauses at statements as `hook` decides, e.g. for `lox debug`.
+    pub fn with_debugger(self, hook: Box<dyn DebugHook>) -> Self {
+        Self {
+            debug: Some(Rc::new(DebugSession::new(hook))),
+            ..self
+        }
+    }
+
+    /// Records calls, timings and line hits into `profiler`, e.g. for `lox --profile`.
+    pub fn with_profiler(self, profiler: Rc<Profiler>) -> Self {
+        Self {
+            profiler: Some(profiler),
+            ..self
+        }
+    }
+
+    /// Records which statements and branches run into `coverage`, e.g. for
+    /// `lox --coverage`. Imported files are registered with it as they load.
+    pub fn with_coverage(self, coverage: Rc<Coverage>) -> Self {
+        Self {
+            coverage: Some(coverage),
+            ..self
+        }
+    }
 
-        interpreter
+    fn cover_branch(&self, token: &Token, branch: Branch) {
+        if let Some(coverage) = &self.coverage {
+            coverage.branch(self.script_path.as_deref(), token, branch);
+        }
     }
 
     fn is_truthy(object: &Object) -> bool {
@@ -63,116 +186,222 @@ impl Interpreter {
         }
     }
 
-    pub fn interpret(&mut self, statements: Vec<Box<dyn Stmt>>) -> Result<Object, Box<dyn Error>> {
-        let statements = statements;
-
+    pub fn interpret(&mut self, statements: &[Stmt]) -> Result<Object, Box<dyn Error>> {
         for statement in statements {
-            self.execute(Rc::new(RefCell::new(statement)))?;
+            self.execute(statement)?;
         }
 
         Ok(Object::Nil)
     }
 
-    pub fn execute(&mut self, stmt: Rc<RefCell<Box<dyn Stmt>>>) -> Result<(), Box<dyn Error>> {
-        stmt.borrow_mut().accept(self)?;
-        Ok(())
+    pub fn execute(&mut self, stmt: &Stmt) -> Result<Completion, Box<dyn Error>> {
+        self.sandbox.step()?;
+        if let (Some(profiler), Some(line)) = (&self.profiler, stmt.line()) {
+            profiler.hit(self.script_path.as_deref(), line);
+        }
+        if let (Some(coverage), Some(line)) = (&self.coverage, stmt.line()) {
+            coverage.hit(self.script_path.as_deref(), line);
+        }
+        if let (Some(debug), Some(line)) = (&self.debug, stmt.line()) {
+            debug.statement(stmt, line, &self.env, self.script_path.as_deref())?;
+        }
+        stmt.accept(self)
     }
 
-    pub fn resolve(&mut self, expr: Box<dyn Expr>, depth: i32) {
-        self.locals.insert(
-            ExprKey {
-                expr: Rc::new(expr),
-            },
-            depth,
-        );
+    pub fn resolve(&mut self, id: usize, depth: usize) {
+        self.locals.insert(id, depth);
     }
 
-    pub fn lookup_variable(
-        &self,
-        name: &Token,
-        expr: Rc<Box<dyn Expr>>,
-    ) -> Result<Object, Box<dyn Error>> {
-        if let Some(distance) = self.locals.get(&ExprKey { expr }) {
+    pub fn lookup_variable(&self, name: &Token, id: usize) -> Result<Object, Box<dyn Error>> {
+        if let Some(distance) = self.locals.get(&id) {
             self.env.borrow().get_at(*distance, name.lexeme.clone())
         } else {
             self.globals.borrow().get(name)
         }
     }
 
-    pub fn execute_block(
-        &mut self,
-        statements: Vec<Rc<RefCell<Box<dyn Stmt>>>>,
-        environment: Rc<RefCell<Environment>>,
-    ) -> Result<(), Box<dyn Error>> {
-        let previous = self.env.clone();
-        self.env = environment;
-
-        for statement in statements {
-            self.execute(statement)?;
-        }
-        previous.borrow_mut().enclosing = Some(self.env.clone());
-        self.env = previous;
+    /// Records the file being run, so that its imports resolve relative to it
+    /// and an import of the file itself is reported as a cycle.
+    pub fn set_script_path(&mut self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let path = path.canonicalize()?;
+        self.modules.borrow_mut().loading.push(path.clone());
+        self.script_path = Some(path);
         Ok(())
     }
 
-    pub fn error(&self, message: &str, token: &Token) -> Box<dyn Error> {
-        let mut err = LoxError::new();
-        err = err
-            .type_(Box::new(RuntimeError))
-            .at_token(token.to_owned())
-            .message(message.to_string());
-        Box::new(err)
+    fn import(&mut self, stmt: &stmt::Import) -> Result<Rc<Module>, Box<dyn Error>> {
+        let relative: String = stmt.path.literal.as_deref().unwrap().into();
+        if !self.capabilities.filesystem {
+            return Err(self.error(
+                &format!(
+                    "Can't import '{}': filesystem access is disabled.",
+                    relative
+                ),
+                &stmt.path,
+            ));
+        }
+        let base = self
+            .script_path
+            .as_deref()
+            .and_then(Path::parent)
+            .unwrap_or(Path::new("."));
+        let path = base.join(&relative).canonicalize().map_err(|err| {
+            self.error(
+                &format!("Can't import '{}': {}.", relative, err),
+                &stmt.path,
+            )
+        })?;
+
+        if let Some(module) = self.modules.borrow().loaded.get(&path) {
+            return Ok(module.clone());
+        }
+
+        let cycle_start = self
+            .modules
+            .borrow()
+            .loading
+            .iter()
+            .position(|p| *p == path);
+        if let Some(start) = cycle_start {
+            let modules = self.modules.borrow();
+            let cycle: Vec<String> = modules.loading[start..]
+                .iter()
+                .chain([&path])
+                .map(|p| p.file_name().unwrap().to_string_lossy().into_owned())
+                .collect();
+            return Err(self.error(
+                &format!("Import cycle detected: {}.", cycle.join(" -> ")),
+                &stmt.path,
+            ));
+        }
+
+        self.modules.borrow_mut().loading.push(path.clone());
+        let module = self.run_module(&path, &stmt.path);
+        self.modules.borrow_mut().loading.pop();
+
+        let module = Rc::new(module?);
+        self.modules
+            .borrow_mut()
+            .loaded
+            .insert(path, module.clone());
+        Ok(module)
     }
-}
 
-#[allow(unused_variables)]
-impl expr::Visitor for Interpreter {
-    fn visit_assign_expr(&mut self, expr: &expr::Assign) -> Result<Object, Box<dyn Error>> {
-        let value = self.evaluate(expr.value.clone())?;
+    fn run_module(&mut self, path: &Path, at: &Token) -> Result<Module, Box<dyn Error>> {
+        let source = std::fs::read_to_string(path).map_err(|err| {
+            self.error(&format!("Can't import '{}': {}.", path.display(), err), at)
+        })?;
 
-        if let Some(distance) = self.locals.get(&ExprKey {
-            expr: Rc::new(Box::new(expr.clone())),
-        }) {
-            self.env.borrow().assign_at(*distance, &expr.name, &value)?;
-        } else {
-            self.globals.borrow_mut().assign(&expr.name, &value)?;
+        let tokens = Scanner::new(source).scan_tokens()?;
+        let statements = Parser::new(tokens).parse()?;
+        Resolver::new(self).resolve(&statements)?;
+        if let Some(coverage) = &self.coverage {
+            coverage.register(Some(path), &statements);
         }
 
-        Ok(value)
+        let module_env = Rc::new(RefCell::new(Environment::from(self.builtins.clone())));
+        let previous_env = std::mem::replace(&mut self.env, module_env.clone());
+        let previous_globals = std::mem::replace(&mut self.globals, module_env.clone());
+        let previous_path = self.script_path.replace(path.to_path_buf());
+
+        let name = format!("<module {}>", path.file_stem().unwrap().to_string_lossy());
+        if let Some(debug) = &self.debug {
+            debug.enter(name, module_env.clone());
+        }
+        let result = self.interpret(&statements);
+        if let Some(debug) = &self.debug {
+            debug.exit();
+        }
+
+        self.env = previous_env;
+        self.globals = previous_globals;
+        self.script_path = previous_path;
+        result?;
+
+        Ok(Module {
+            name: path.file_stem().unwrap().to_string_lossy().into_owned(),
+            path: path.to_path_buf(),
+            env: module_env,
+        })
     }
 
-    fn visit_binary_expr(&mut self, expr: &mut expr::Binary) -> Result<Object, Box<dyn Error>> {
-        let left = self.evaluate(expr.left.clone())?;
-        let right = self.evaluate(expr.right.clone())?;
+    pub fn execute_block(
+        &mut self,
+        statements: &[Stmt],
+        environment: Rc<RefCell<Environment>>,
+    ) -> Result<Completion, Box<dyn Error>> {
+        let previous = self.env.clone();
+        self.env = environment;
 
-        let rhs = right.to_owned();
-        let check_number_operands = |v: Object| {
-            if v.is_nil() {
-                if rhs == Object::Number(0.0) {
-                    return Err(self.error("Can't divide by zero.", &expr.operator));
-                } else {
-                    return Err(self.error("Operands must be numbers.", &expr.operator));
-                }
+        let mut result = Ok(Completion::Normal);
+        for statement in statements {
+            result = self.execute(statement);
+            if !matches!(result, Ok(Completion::Normal)) {
+                break;
             }
+        }
 
-            Ok(v)
-        };
+        self.env = previous;
+        result
+    }
 
-        let check_addition_operands = |v: Object| {
-            if v.is_nil() {
+    /// Applies a binary operator to already evaluated operands. Shared by `Binary`
+    /// expressions and compound assignments, which evaluate their target only once.
+    pub fn binary_op(
+        &self,
+        operator: &Token,
+        left: Object,
+        right: Object,
+    ) -> Result<Object, Box<dyn Error>> {
+        let (lhs, rhs) = (left.to_owned(), right.to_owned());
+        let check_arithmetic = |v: Object| {
+            if !v.is_nil() {
+                return Ok(v);
+            }
+
+            if operator.type_ == TokenType::PLUS && !(lhs.is_number() && rhs.is_number()) {
                 return Err(self.error(
                     "Operands can be in pairs of:\n\t(String::String)\n\t(Number::Number)\n\t(String::Number)\n\t(Number::String)\n\t(Boolean::String) or\n\t(String::Boolean)",
-                    &expr.operator,
+                    operator,
                 ));
             }
-            Ok(v)
+            if !(lhs.is_number() && rhs.is_number()) {
+                return Err(self.error("Operands must be numbers.", operator));
+            }
+            if rhs == Object::Int(0) {
+                return Err(self.error("Can't divide by zero.", operator));
+            }
+            Err(self.error("Integer overflow.", operator))
         };
 
-        match expr.operator.type_ {
-            TokenType::MINUS => Ok(left - right),
-            TokenType::SLASH => check_number_operands(left / right),
-            TokenType::STAR => Ok(left * right),
-            TokenType::PLUS => check_addition_operands(left + right),
+        let check_bitwise = |v: Object| {
+            if !v.is_nil() {
+                return Ok(v);
+            }
+
+            if !(matches!(lhs, Object::Int(_)) && matches!(rhs, Object::Int(_))) {
+                return Err(self.error("Operands must be integers.", operator));
+            }
+            Err(self.error("Shift amount out of range.", operator))
+        };
+
+        match operator.type_ {
+            TokenType::MINUS => check_arithmetic(left - right),
+            TokenType::SLASH => check_arithmetic(left / right),
+            TokenType::STAR => check_arithmetic(left * right),
+            TokenType::PERCENT => check_arithmetic(left % right),
+            TokenType::DIV => check_arithmetic(left.int_div(right)),
+            TokenType::STAR_STAR => check_arithmetic(left.pow(right)),
+            TokenType::PLUS => {
+                let sum = check_arithmetic(left + right)?;
+                self.sandbox.check_size(&sum)?;
+                Ok(sum)
+            }
+            TokenType::AMPERSAND => check_bitwise(left & right),
+            TokenType::PIPE => check_bitwise(left | right),
+            TokenType::CARET => check_bitwise(left ^ right),
+            TokenType::LESS_LESS => check_bitwise(left << right),
+            TokenType::GREATER_GREATER => check_bitwise(left >> right),
             TokenType::GREATER => Ok(Object::Boolean(left > right)),
             TokenType::GREATER_EQUAL => Ok(Object::Boolean(left >= right)),
             TokenType::LESS => Ok(Object::Boolean(left < right)),
@@ -183,43 +412,156 @@ impl expr::Visitor for Interpreter {
                 let mut err = LoxError::new();
                 err = err
                     .type_(Box::new(RuntimeError))
-                    .message(format!(
-                        "Unsupported binary operator: {}",
-                        expr.operator.lexeme
-                    ))
-                    .at_token(expr.operator.to_owned());
+                    .message(format!("Unsupported binary operator: {}", operator.lexeme))
+                    .at_token(operator.to_owned());
                 Err(Box::new(err))
             }
         }
     }
 
-    fn visit_call_expr(&mut self, expr: &expr::Call) -> Result<Object, Box<dyn Error>> {
-        let function: Box<dyn Callable> = Box::new(self.evaluate(expr.callee.clone())?);
+    /// Calls `callee` the way a call expression does: after polling for an interrupt,
+    /// within the sandbox's call depth and timed by the profiler, which knows natives
+    /// by `name` if given.
+    pub fn call(
+        &mut self,
+        callee: Object,
+        name: Option<&str>,
+        arguments: Vec<Object>,
+        paren: &Token,
+    ) -> Result<Object, Box<dyn Error>> {
+        let profiled = self.profiler.as_ref().map(|profiler| {
+            let (name, line) = match (&callee, name) {
+                (Object::Function(Some(function), _), _) => {
+                    let name = &function.borrow().declaration.name;
+                    (name.lexeme.clone(), Some(name.line))
+                }
+                (_, Some(name)) => (name.to_string(), None),
+                _ => (format!("{}", callee), None),
+            };
+            (profiler.clone(), name, line)
+        });
+        let function: Box<dyn Callable> = Box::new(callee);
+
+        self.interrupt.check()?;
+        self.sandbox.enter_call()?;
+        if let Some((profiler, name, line)) = &profiled {
+            profiler.enter(name.clone(), *line);
+        }
+        let returned_v = function.call(self.clone(), arguments, paren.to_owned());
+        if let Some((profiler, ..)) = &profiled {
+            profiler.exit();
+        }
+        self.sandbox.exit_call();
+
+        let returned_v = returned_v?;
+        self.sandbox.check_size(&returned_v)?;
+        Ok(returned_v)
+    }
 
+    pub fn error(&self, message: &str, token: &Token) -> Box<dyn Error> {
+        let mut err = LoxError::new();
+        err = err
+            .type_(Box::new(RuntimeError))
+            .at_token(token.to_owned())
+            .message(message.to_string());
+        Box::new(err)
+    }
+}
+
+#[allow(unused_variables)]
+impl expr::Visitor<Result<Object, Box<dyn Error>>> for Interpreter {
+    fn visit_assign_expr(&mut self, expr: &expr::Assign) -> Result<Object, Box<dyn Error>> {
+        let value = self.evaluate(&expr.value)?;
+
+        if let Some(distance) = self.locals.get(&expr.id) {
+            self.env
+                .borrow_mut()
+                .assign_at(*distance, &expr.name, &value)?;
+        } else {
+            self.globals.borrow_mut().assign(&expr.name, &value)?;
+        }
+
+        Ok(value)
+    }
+
+    fn visit_binary_expr(&mut self, expr: &expr::Binary) -> Result<Object, Box<dyn Error>> {
+        let left = self.evaluate(&expr.left)?;
+        let right = self.evaluate(&expr.right)?;
+
+        self.binary_op(&expr.operator, left, right)
+    }
+
+    fn visit_call_expr(&mut self, expr: &expr::Call) -> Result<Object, Box<dyn Error>> {
+        let callee = self.evaluate(&expr.callee)?;
+        let name = match expr.callee.as_ref() {
+            Expr::Variable(variable) => Some(variable.name.lexeme.as_str()),
+            _ => None,
+        };
         let arguments = expr
             .arguments
             .iter()
-            .map(|arg| self.evaluate(arg.clone()))
+            .map(|arg| self.evaluate(arg))
             .collect::<Result<Vec<Object>, Box<dyn Error>>>()?;
-        let returned_v = function.call(self.clone(), arguments, expr.paren.to_owned())?;
+        self.call(callee, name, arguments, &expr.paren)
+    }
 
-        Ok(returned_v)
+    fn visit_conditional_expr(
+        &mut self,
+        expr: &expr::Conditional,
+    ) -> Result<Object, Box<dyn Error>> {
+        if Interpreter::is_truthy(&self.evaluate(&expr.condition)?) {
+            self.evaluate(&expr.then_branch)
+        } else {
+            self.evaluate(&expr.else_branch)
+        }
     }
 
-    fn visit_get_expr(&self, expr: &expr::Get) -> Result<Object, Box<dyn Error>> {
-        todo!()
+    fn visit_get_expr(&mut self, expr: &expr::Get) -> Result<Object, Box<dyn Error>> {
+        match self.evaluate(&expr.object)? {
+            Object::Module(module) => {
+                let value = module.env.borrow().values.get(&expr.name.lexeme).cloned();
+                value.ok_or_else(|| {
+                    self.error(
+                        &format!(
+                            "Undefined property '{}' in module '{}'.",
+                            expr.name.lexeme, module.name
+                        ),
+                        &expr.name,
+                    )
+                })
+            }
+            Object::Error(error) => match expr.name.lexeme.as_str() {
+                "message" => Ok(Object::String(error.message.clone())),
+                "line" => Ok(Object::Int(error.line)),
+                _ => Err(self.error(
+                    &format!("Undefined property '{}' on error.", expr.name.lexeme),
+                    &expr.name,
+                )),
+            },
+            _ => Err(self.error("Only instances have properties.", &expr.name)),
+        }
     }
 
-    fn visit_group_expr(&mut self, expr: &mut expr::Grouping) -> Result<Object, Box<dyn Error>> {
-        self.evaluate(expr.expression.clone())
+    fn visit_group_expr(&mut self, expr: &expr::Grouping) -> Result<Object, Box<dyn Error>> {
+        self.evaluate(&expr.expression)
     }
 
-    fn visit_literal_expr(&self, expr: &expr::Literal) -> Result<Object, Box<dyn Error>> {
+    fn visit_literal_expr(&mut self, expr: &expr::Literal) -> Result<Object, Box<dyn Error>> {
         Ok(expr.value.clone())
     }
 
     fn visit_logical_expr(&mut self, expr: &expr::Logical) -> Result<Object, Box<dyn Error>> {
-        let left = self.evaluate(expr.left.clone())?;
+        let left = self.evaluate(&expr.left)?;
+
+        if expr.operator.type_ == TokenType::QUESTION_QUESTION {
+            return if left.is_nil() {
+                self.cover_branch(&expr.operator, Branch::NotTaken);
+                self.evaluate(&expr.right)
+            } else {
+                self.cover_branch(&expr.operator, Branch::Taken);
+                Ok(left)
+            };
+        }
 
         let get_truth = |token_type: &TokenType| {
             let mut truth = Interpreter::is_truthy(&left);
@@ -230,9 +572,11 @@ impl expr::Visitor for Interpreter {
             };
 
             if truth {
+                self.cover_branch(&expr.operator, Branch::Taken);
                 Ok(left)
             } else {
-                Ok(self.evaluate(expr.right.clone())?)
+                self.cover_branch(&expr.operator, Branch::NotTaken);
+                Ok(self.evaluate(&expr.right)?)
             }
         };
 
@@ -243,24 +587,53 @@ impl expr::Visitor for Interpreter {
         }
     }
 
-    fn visit_set_expr(&self, expr: &expr::Set) -> Result<Object, Box<dyn Error>> {
-        todo!()
+    fn visit_set_expr(&mut self, expr: &expr::Set) -> Result<Object, Box<dyn Error>> {
+        let module = match self.evaluate(&expr.object)? {
+            Object::Module(module) => module,
+            _ => return Err(self.error("Only instances have fields.", &expr.name)),
+        };
+
+        let current = module.env.borrow().values.get(&expr.name.lexeme).cloned();
+        let Some(current) = current else {
+            return Err(self.error(
+                &format!(
+                    "Undefined property '{}' in module '{}'.",
+                    expr.name.lexeme, module.name
+                ),
+                &expr.name,
+            ));
+        };
+
+        let mut value = self.evaluate(&expr.value)?;
+        if let Some(operator) = &expr.operator {
+            value = self.binary_op(operator, current, value)?;
+        }
+        module
+            .env
+            .borrow_mut()
+            .values
+            .insert(expr.name.lexeme.clone(), value.clone());
+        Ok(value)
     }
 
-    fn visit_super_expr(&self, expr: &expr::Super) -> Result<Object, Box<dyn Error>> {
+    fn visit_super_expr(&mut self, expr: &expr::Super) -> Result<Object, Box<dyn Error>> {
         todo!()
     }
 
-    fn visit_this_expr(&self, expr: &expr::This) -> Result<Object, Box<dyn Error>> {
+    fn visit_this_expr(&mut self, expr: &expr::This) -> Result<Object, Box<dyn Error>> {
         todo!()
     }
 
-    fn visit_unary_expr(&mut self, expr: &mut expr::Unary) -> Result<Object, Box<dyn Error>> {
-        let right = self.evaluate(expr.right.clone())?;
+    fn visit_unary_expr(&mut self, expr: &expr::Unary) -> Result<Object, Box<dyn Error>> {
+        let right = self.evaluate(&expr.right)?;
 
         match expr.operator.type_ {
             TokenType::MINUS => match right {
                 Object::Number(n) => Ok(Object::Number(-n)),
+                Object::Int(i) => i
+                    .checked_neg()
+                    .map(Object::Int)
+                    .ok_or_else(|| self.error("Integer overflow.", &expr.operator)),
                 _ => {
                     let err = LoxError::new();
                     let err_ = err
@@ -270,7 +643,7 @@ impl expr::Visitor for Interpreter {
                     Err(Box::new(err_))
                 }
             },
-            TokenType::BANG => Ok(Object::Boolean(right.is_nil())),
+            TokenType::BANG => Ok(Object::Boolean(!Interpreter::is_truthy(&right))),
             _ => {
                 let mut err = LoxError::new();
                 err = err
@@ -283,80 +656,150 @@ impl expr::Visitor for Interpreter {
     }
 
     fn visit_variable_expr(&mut self, expr: &expr::Variable) -> Result<Object, Box<dyn Error>> {
-        let value = self.lookup_variable(&expr.name, Rc::new(Box::new(expr.clone())));
-        value
+        self.lookup_variable(&expr.name, expr.id)
     }
 }
 
 #[allow(unused_variables)]
-impl stmt::Visitor for Interpreter {
-    fn visit_block_stmt(&mut self, stmt: &mut stmt::Block) -> Result<(), Box<dyn Error>> {
+impl stmt::Visitor<Result<Completion, Box<dyn Error>>> for Interpreter {
+    fn visit_block_stmt(&mut self, stmt: &stmt::Block) -> Result<Completion, Box<dyn Error>> {
         self.execute_block(
-            stmt.statements.clone(),
+            &stmt.statements,
             Rc::new(RefCell::new(Environment::from(self.env.clone()))),
-        )?;
-        Ok(())
+        )
     }
 
-    fn visit_class_stmt(&self, stmt: &stmt::Class) -> Result<(), Box<dyn Error>> {
+    fn visit_break_stmt(&mut self, _stmt: &stmt::Break) -> Result<Completion, Box<dyn Error>> {
+        Ok(Completion::Break)
+    }
+
+    fn visit_class_stmt(&mut self, stmt: &stmt::Class) -> Result<Completion, Box<dyn Error>> {
         todo!()
     }
 
-    fn visit_expr_stmt(&mut self, stmt: &mut stmt::Expression) -> Result<(), Box<dyn Error>> {
-        self.evaluate(stmt.expression.clone())?;
-        Ok(())
+    fn visit_expr_stmt(&mut self, stmt: &stmt::Expression) -> Result<Completion, Box<dyn Error>> {
+        self.evaluate(&stmt.expression)?;
+        Ok(Completion::Normal)
+    }
+
+    fn visit_continue_stmt(
+        &mut self,
+        _stmt: &stmt::Continue,
+    ) -> Result<Completion, Box<dyn Error>> {
+        Ok(Completion::Continue)
     }
 
-    fn visit_func_stmt(&mut self, stmt: &stmt::Function) -> Result<(), Box<dyn Error>> {
-        let function: function::Function =
-            function::Function::new(stmt.to_owned(), self.env.clone());
+    fn visit_func_stmt(&mut self, stmt: &Rc<stmt::Function>) -> Result<Completion, Box<dyn Error>> {
+        let function = function::Function::new(
+            stmt.clone(),
+            self.env.clone(),
+            self.globals.clone(),
+            self.script_path.clone(),
+        );
         let fn_obj = Object::Function(Some(Rc::new(RefCell::new(function))), None);
         self.env.borrow_mut().define(&stmt.name, fn_obj)?;
-        Ok(())
+        Ok(Completion::Normal)
+    }
+
+    fn visit_import_stmt(&mut self, stmt: &stmt::Import) -> Result<Completion, Box<dyn Error>> {
+        let module = self.import(stmt)?;
+        self.globals
+            .borrow_mut()
+            .define(&stmt.name, Object::Module(module))?;
+        Ok(Completion::Normal)
     }
 
-    fn visit_if_stmt(&mut self, stmt: &mut stmt::If) -> Result<(), Box<dyn Error>> {
-        if Interpreter::is_truthy(&self.evaluate(stmt.condition.clone())?) {
-            self.execute(stmt.then_branch.clone())?;
+    fn visit_if_stmt(&mut self, stmt: &stmt::If) -> Result<Completion, Box<dyn Error>> {
+        if Interpreter::is_truthy(&self.evaluate(&stmt.condition)?) {
+            self.cover_branch(&stmt.keyword, Branch::Taken);
+            self.execute(&stmt.then_branch)
         } else {
-            if let Some(else_stmt) = stmt.else_branch.clone() {
-                self.execute(else_stmt.clone())?;
+            self.cover_branch(&stmt.keyword, Branch::NotTaken);
+            match &stmt.else_branch {
+                Some(else_stmt) => self.execute(else_stmt),
+                None => Ok(Completion::Normal),
             }
         }
+    }
 
-        Ok(())
+    fn visit_print_stmt(&mut self, stmt: &stmt::Print) -> Result<Completion, Box<dyn Error>> {
+        let value = self.evaluate(&stmt.expression)?;
+        match &self.debug {
+            Some(debug) => debug.output(&format!("{}", value)),
+            None => println!("{}", value),
+        }
+        Ok(Completion::Normal)
     }
 
-    fn visit_print_stmt(&mut self, stmt: &mut stmt::Print) -> Result<(), Box<dyn Error>> {
-        let value = self.evaluate(stmt.expression.clone())?;
-        println!("{}", value);
-        Ok(())
+    fn visit_return_stmt(&mut self, stmt: &stmt::Return) -> Result<Completion, Box<dyn Error>> {
+        let value = match &stmt.value {
+            Some(value) => self.evaluate(value)?,
+            None => Object::Nil,
+        };
+        Ok(Completion::Return(value))
     }
 
-    fn visit_return_stmt(&mut self, stmt: &stmt::Return) -> Result<(), Box<dyn Error>> {
-        if let Some(value) = stmt.value.clone() {
-            let value = self.evaluate(value.clone())?;
-            return Err(Box::new(Return { value }));
+    fn visit_throw_stmt(&mut self, stmt: &stmt::Throw) -> Result<Completion, Box<dyn Error>> {
+        let value = self.evaluate(&stmt.value)?;
+        Err(Box::new(Throw {
+            value,
+            line: stmt.keyword.line,
+        }))
+    }
+
+    fn visit_try_stmt(&mut self, stmt: &stmt::Try) -> Result<Completion, Box<dyn Error>> {
+        let mut result = self.execute_block(
+            &stmt.body,
+            Rc::new(RefCell::new(Environment::from(self.env.clone()))),
+        );
+
+        if let (Err(err), Some((name, body))) = (&result, &stmt.catch) {
+            if let Some(exception) = Self::caught(err.as_ref()) {
+                let environment = Rc::new(RefCell::new(Environment::from(self.env.clone())));
+                environment.borrow_mut().define(name, exception)?;
+                result = self.execute_block(body, environment);
+            }
         }
 
-        Ok(())
+        if let Some(body) = &stmt.finally {
+            // An error or abrupt completion of `finally` replaces the pending one.
+            let completion = self.execute_block(
+                body,
+                Rc::new(RefCell::new(Environment::from(self.env.clone()))),
+            )?;
+            if !matches!(completion, Completion::Normal) {
+                return Ok(completion);
+            }
+        }
+
+        result
     }
 
-    fn visit_var_stmt(&mut self, stmt: &mut stmt::Var) -> Result<(), Box<dyn Error>> {
+    fn visit_var_stmt(&mut self, stmt: &stmt::Var) -> Result<Completion, Box<dyn Error>> {
         let mut value = Object::Nil;
-        if stmt.initializer.is_some() {
-            value = self.evaluate(stmt.initializer.clone().unwrap())?;
+        if let Some(initializer) = &stmt.initializer {
+            value = self.evaluate(initializer)?;
         }
 
         self.env.borrow_mut().define(&stmt.name, value)?;
-        Ok(())
+        Ok(Completion::Normal)
     }
 
-    fn visit_while_stmt(&mut self, stmt: &stmt::While) -> Result<(), Box<dyn Error>> {
-        while Interpreter::is_truthy(&self.evaluate(stmt.condition.clone())?) {
-            self.execute(stmt.body.clone())?;
+    fn visit_while_stmt(&mut self, stmt: &stmt::While) -> Result<Completion, Box<dyn Error>> {
+        while Interpreter::is_truthy(&self.evaluate(&stmt.condition)?) {
+            self.cover_branch(&stmt.keyword, Branch::Taken);
+            match self.execute(&stmt.body)? {
+                Completion::Break => break,
+                Completion::Return(value) => return Ok(Completion::Return(value)),
+                Completion::Normal | Completion::Continue => {}
+            }
+            if let Some(increment) = &stmt.increment {
+                self.evaluate(increment)?;
+            }
+            self.interrupt.check()?;
         }
+        self.cover_branch(&stmt.keyword, Branch::NotTaken);
 
-        Ok(())
+        Ok(Completion::Normal)
     }
 }
@@ -0,0 +1,12 @@
+use crate::object::Object;
+
+/// How a statement finished. Errors, including thrown exceptions, are the `Err`
+/// side of the `Result` that carries this, so `?` unwinds them while the abrupt
+/// completions below are matched by the loop or call that consumes them.
+#[derive(Debug, Clone)]
+pub enum Completion {
+    Normal,
+    Return(Object),
+    Break,
+    Continue,
+}
@@ -0,0 +1,345 @@
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap},
+    fmt::Write,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use crate::{
+    expr::{self, Expr},
+    stmt::{self, Stmt},
+    token::Token,
+};
+
+/// The two ways a branch point can go. For an `if`, whether the then or the else
+/// branch ran (a missing else counts too); for a loop, whether the body ran or the
+/// condition ended it; for `and`, `or` and `??`, whether the left operand decided
+/// the result or the right one was evaluated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Branch {
+    Taken,
+    NotTaken,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FileCoverage {
+    /// Hits per line with a statement on it, including lines that never ran.
+    pub lines: BTreeMap<i64, u64>,
+    /// Hits per branch point, keyed by the line and column of its keyword or
+    /// operator: `[taken, not taken]`.
+    pub branches: BTreeMap<(i64, usize), [u64; 2]>,
+}
+
+impl FileCoverage {
+    pub fn lines_hit(&self) -> usize {
+        self.lines.values().filter(|hits| **hits > 0).count()
+    }
+
+    pub fn branches_found(&self) -> usize {
+        self.branches.len() * 2
+    }
+
+    pub fn branches_hit(&self) -> usize {
+        self.branches
+            .values()
+            .flatten()
+            .filter(|hits| **hits > 0)
+            .count()
+    }
+}
+
+/// Records which statements and branches ran, for `lox --coverage`. Each file's code
+/// is registered before it runs so that the report includes what never ran. Shared
+/// through an `Rc` like the profiler.
+#[derive(Debug, Default)]
+pub struct Coverage {
+    // Keyed by an empty path for code that isn't from a file.
+    files: RefCell<HashMap<PathBuf, FileCoverage>>,
+}
+
+impl Coverage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds every statement and branch point in `statements`, unhit.
+    pub fn register(&self, path: Option<&Path>, statements: &[Stmt]) {
+        let mut collector = Collector::default();
+        collector.statements(statements);
+
+        let mut files = self.files.borrow_mut();
+        let file = files
+            .entry(path.unwrap_or(Path::new("")).to_path_buf())
+            .or_default();
+        for line in collector.lines {
+            file.lines.entry(line).or_default();
+        }
+        for branch in collector.branches {
+            file.branches.entry(branch).or_default();
+        }
+    }
+
+    /// Counts a statement starting on `line` of the file at `path`.
+    pub fn hit(&self, path: Option<&Path>, line: i64) {
+        self.update(path, |file| *file.lines.entry(line).or_default() += 1);
+    }
+
+    /// Counts the way the branch point at `token` went.
+    pub fn branch(&self, path: Option<&Path>, token: &Token, branch: Branch) {
+        let index = match branch {
+            Branch::Taken => 0,
+            Branch::NotTaken => 1,
+        };
+        self.update(path, |file| {
+            file.branches.entry((token.line, token.column)).or_default()[index] += 1
+        });
+    }
+
+    fn update(&self, path: Option<&Path>, f: impl FnOnce(&mut FileCoverage)) {
+        let path = path.unwrap_or(Path::new(""));
+        let mut files = self.files.borrow_mut();
+        match files.get_mut(path) {
+            Some(file) => f(file),
+            None => f(files.entry(path.to_path_buf()).or_default()),
+        }
+    }
+
+    /// Every file seen, by path; `None` is code that isn't from a file.
+    pub fn files(&self) -> Vec<(Option<PathBuf>, FileCoverage)> {
+        let mut files: Vec<(Option<PathBuf>, FileCoverage)> = self
+            .files
+            .borrow()
+            .iter()
+            .map(|(path, file)| {
+                let path = (!path.as_os_str().is_empty()).then(|| path.clone());
+                (path, file.clone())
+            })
+            .collect();
+        files.sort_by(|a, b| a.0.cmp(&b.0));
+        files
+    }
+
+    /// The coverage in the lcov tracefile format that genhtml and most CI services read.
+    pub fn lcov(&self) -> String {
+        let mut out = String::new();
+        for (path, file) in self.files() {
+            let path = path.map_or("<script>".to_string(), |p| p.display().to_string());
+            let _ = writeln!(out, "TN:\nSF:{}", path);
+
+            let mut previous_line = None;
+            let mut block = 0;
+            for ((line, _), hits) in &file.branches {
+                block = if previous_line == Some(*line) {
+                    block + 1
+                } else {
+                    0
+                };
+                previous_line = Some(*line);
+                for (branch, taken) in hits.iter().enumerate() {
+                    // lcov writes `-` for a branch whose condition never ran.
+                    let taken = match hits {
+                        [0, 0] => "-".to_string(),
+                        _ => taken.to_string(),
+                    };
+                    let _ = writeln!(out, "BRDA:{},{},{},{}", line, block, branch, taken);
+                }
+            }
+            let _ = writeln!(out, "BRF:{}", file.branches_found());
+            let _ = writeln!(out, "BRH:{}", file.branches_hit());
+
+            for (line, hits) in &file.lines {
+                let _ = writeln!(out, "DA:{},{}", line, hits);
+            }
+            let _ = writeln!(out, "LF:{}", file.lines.len());
+            let _ = writeln!(out, "LH:{}", file.lines_hit());
+            let _ = writeln!(out, "end_of_record");
+        }
+        out
+    }
+
+    /// A table of line and branch coverage per file.
+    pub fn summary(&self) -> String {
+        let percent = |hit: usize, found: usize| match found {
+            0 => "-".to_string(),
+            _ => format!("{:.1}%", hit as f64 * 100.0 / found as f64),
+        };
+
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "{:<32}{:>12}{:>8}{:>12}{:>8}",
+            "File", "Lines", "%", "Branches", "%"
+        );
+        for (path, file) in self.files() {
+            let name = path.map_or("<script>".to_string(), |p| p.display().to_string());
+            let (lines_hit, lines) = (file.lines_hit(), file.lines.len());
+            let (branches_hit, branches) = (file.branches_hit(), file.branches_found());
+            let _ = writeln!(
+                out,
+                "{:<32}{:>12}{:>8}{:>12}{:>8}",
+                name,
+                format!("{}/{}", lines_hit, lines),
+                percent(lines_hit, lines),
+                format!("{}/{}", branches_hit, branches),
+                percent(branches_hit, branches)
+            );
+        }
+        out
+    }
+}
+
+/// Finds the statement lines and branch points the interpreter reports hits for.
+#[derive(Default)]
+struct Collector {
+    lines: Vec<i64>,
+    branches: Vec<(i64, usize)>,
+}
+
+impl Collector {
+    fn statements(&mut self, statements: &[Stmt]) {
+        for statement in statements {
+            if let Some(line) = statement.line() {
+                self.lines.push(line);
+            }
+            statement.accept(self);
+        }
+    }
+
+    fn statement(&mut self, statement: &Stmt) {
+        self.statements(std::slice::from_ref(statement));
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        expr.accept(self)
+    }
+
+    fn branch(&mut self, token: &Token) {
+        self.branches.push((token.line, token.column));
+    }
+}
+
+impl expr::Visitor<()> for Collector {
+    fn visit_assign_expr(&mut self, expr: &expr::Assign) {
+        self.expr(&expr.value);
+    }
+
+    fn visit_binary_expr(&mut self, expr: &expr::Binary) {
+        self.expr(&expr.left);
+        self.expr(&expr.right);
+    }
+
+    fn visit_call_expr(&mut self, expr: &expr::Call) {
+        self.expr(&expr.callee);
+        for argument in &expr.arguments {
+            self.expr(argument);
+        }
+    }
+
+    fn visit_conditional_expr(&mut self, expr: &expr::Conditional) {
+        self.expr(&expr.condition);
+        self.expr(&expr.then_branch);
+        self.expr(&expr.else_branch);
+    }
+
+    fn visit_get_expr(&mut self, expr: &expr::Get) {
+        self.expr(&expr.object);
+    }
+
+    fn visit_group_expr(&mut self, expr: &expr::Grouping) {
+        self.expr(&expr.expression);
+    }
+
+    fn visit_literal_expr(&mut self, _expr: &expr::Literal) {}
+
+    fn visit_logical_expr(&mut self, expr: &expr::Logical) {
+        self.branch(&expr.operator);
+        self.expr(&expr.left);
+        self.expr(&expr.right);
+    }
+
+    fn visit_set_expr(&mut self, expr: &expr::Set) {
+        self.expr(&expr.object);
+        self.expr(&expr.value);
+    }
+
+    fn visit_super_expr(&mut self, _expr: &expr::Super) {}
+
+    fn visit_this_expr(&mut self, _expr: &expr::This) {}
+
+    fn visit_unary_expr(&mut self, expr: &expr::Unary) {
+        self.expr(&expr.right);
+    }
+
+    fn visit_variable_expr(&mut self, _expr: &expr::Variable) {}
+}
+
+impl stmt::Visitor<()> for Collector {
+    fn visit_block_stmt(&mut self, stmt: &stmt::Block) {
+        self.statements(&stmt.statements);
+    }
+
+    fn visit_break_stmt(&mut self, _stmt: &stmt::Break) {}
+
+    fn visit_class_stmt(&mut self, _stmt: &stmt::Class) {}
+
+    fn visit_continue_stmt(&mut self, _stmt: &stmt::Continue) {}
+
+    fn visit_expr_stmt(&mut self, stmt: &stmt::Expression) {
+        self.expr(&stmt.expression);
+    }
+
+    fn visit_func_stmt(&mut self, stmt: &Rc<stmt::Function>) {
+        self.statements(&stmt.body);
+    }
+
+    fn visit_if_stmt(&mut self, stmt: &stmt::If) {
+        self.branch(&stmt.keyword);
+        self.expr(&stmt.condition);
+        self.statement(&stmt.then_branch);
+        if let Some(else_branch) = &stmt.else_branch {
+            self.statement(else_branch);
+        }
+    }
+
+    fn visit_import_stmt(&mut self, _stmt: &stmt::Import) {}
+
+    fn visit_print_stmt(&mut self, stmt: &stmt::Print) {
+        self.expr(&stmt.expression);
+    }
+
+    fn visit_return_stmt(&mut self, stmt: &stmt::Return) {
+        if let Some(value) = &stmt.value {
+            self.expr(value);
+        }
+    }
+
+    fn visit_throw_stmt(&mut self, stmt: &stmt::Throw) {
+        self.expr(&stmt.value);
+    }
+
+    fn visit_try_stmt(&mut self, stmt: &stmt::Try) {
+        self.statements(&stmt.body);
+        if let Some((_, body)) = &stmt.catch {
+            self.statements(body);
+        }
+        if let Some(body) = &stmt.finally {
+            self.statements(body);
+        }
+    }
+
+    fn visit_var_stmt(&mut self, stmt: &stmt::Var) {
+        if let Some(initializer) = &stmt.initializer {
+            self.expr(initializer);
+        }
+    }
+
+    fn visit_while_stmt(&mut self, stmt: &stmt::While) {
+        self.branch(&stmt.keyword);
+        self.expr(&stmt.condition);
+        self.statement(&stmt.body);
+        if let Some(increment) = &stmt.increment {
+            self.expr(increment);
+        }
+    }
+}
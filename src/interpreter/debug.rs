@@ -0,0 +1,162 @@
+use std::{
+    cell::RefCell,
+    error::Error,
+    fmt,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use crate::{env::Environment, stmt::Stmt};
+
+/// One entry of the call stack as the debugger sees it.
+#[derive(Debug, Clone)]
+pub struct Frame {
+    /// The function running, `<script>` for top-level code or `<module name>` for an
+    /// imported file's.
+    pub name: String,
+    pub path: Option<PathBuf>,
+    /// The line of the statement the frame is running.
+    pub line: i64,
+    // The id of the statement, so that a loop coming back to it can be told apart from
+    // the next statement on the same line.
+    pub(crate) statement: Option<usize>,
+    /// The innermost scope the frame is running in.
+    pub env: Rc<RefCell<Environment>>,
+    // The scope the frame started in: the function's parameters or the file's globals.
+    base: Rc<RefCell<Environment>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScopeKind {
+    /// The frame's own scopes, from its innermost block out to its parameters.
+    Locals,
+    /// Scopes a function closed over, between its parameters and the globals.
+    Closure,
+    Globals,
+}
+
+impl fmt::Display for ScopeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ScopeKind::Locals => "Locals",
+            ScopeKind::Closure => "Closure",
+            ScopeKind::Globals => "Globals",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl Frame {
+    /// The scopes visible from this frame, innermost first. The natives' scope, which
+    /// encloses every file's globals, is left out.
+    pub fn scopes(&self) -> Vec<(ScopeKind, Rc<RefCell<Environment>>)> {
+        let mut scopes = Vec::new();
+        let mut kind = ScopeKind::Locals;
+        let mut env = Some(self.env.clone());
+        while let Some(current) = env {
+            let enclosing = current.borrow().enclosing.clone();
+            let Some(outer) = &enclosing else {
+                break;
+            };
+            if outer.borrow().enclosing.is_none() {
+                scopes.push((ScopeKind::Globals, current));
+                break;
+            }
+            let is_base = Rc::ptr_eq(&current, &self.base);
+            scopes.push((kind, current));
+            if is_base {
+                kind = ScopeKind::Closure;
+            }
+            env = enclosing;
+        }
+        scopes
+    }
+}
+
+/// Lets a debugger front-end watch a running script. The interpreter calls it before
+/// every statement that starts a line and waits for it to return, so pausing is just
+/// not returning until the user says to go on.
+pub trait DebugHook {
+    /// `frames` is the call stack, innermost last. Returning an error stops the script
+    /// with it.
+    fn on_statement(&mut self, frames: &[Frame]) -> Result<(), Box<dyn Error>>;
+
+    /// Receives what `print` writes, for front-ends that own stdout.
+    fn output(&mut self, text: &str) {
+        println!("{}", text);
+    }
+}
+
+/// The hook and the call stack. Shared through an `Rc` so that the interpreter clones
+/// made for each call all report to the same front-end.
+pub struct DebugSession {
+    hook: RefCell<Box<dyn DebugHook>>,
+    frames: RefCell<Vec<Frame>>,
+}
+
+impl fmt::Debug for DebugSession {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DebugSession")
+            .field("frames", &self.frames)
+            .finish_non_exhaustive()
+    }
+}
+
+impl DebugSession {
+    pub fn new(hook: Box<dyn DebugHook>) -> Self {
+        Self {
+            hook: RefCell::new(hook),
+            frames: RefCell::new(Vec::new()),
+        }
+    }
+
+    pub fn enter(&self, name: String, env: Rc<RefCell<Environment>>) {
+        self.frames.borrow_mut().push(Frame {
+            name,
+            path: None,
+            line: 0,
+            statement: None,
+            env: env.clone(),
+            base: env,
+        });
+    }
+
+    pub fn exit(&self) {
+        self.frames.borrow_mut().pop();
+    }
+
+    /// Records where the innermost frame is, then hands control to the hook.
+    pub fn statement(
+        &self,
+        statement: &Stmt,
+        line: i64,
+        env: &Rc<RefCell<Environment>>,
+        path: Option<&Path>,
+    ) -> Result<(), Box<dyn Error>> {
+        {
+            let mut frames = self.frames.borrow_mut();
+            if frames.is_empty() {
+                frames.push(Frame {
+                    name: "<script>".to_string(),
+                    path: None,
+                    line,
+                    statement: None,
+                    env: env.clone(),
+                    base: env.clone(),
+                });
+            }
+            let frame = frames.last_mut().unwrap();
+            frame.line = line;
+            frame.statement = Some(statement.id());
+            frame.env = env.clone();
+            frame.path = path.map(Path::to_path_buf);
+        }
+
+        let frames = self.frames.borrow();
+        self.hook.borrow_mut().on_statement(&frames)
+    }
+
+    pub fn output(&self, text: &str) {
+        self.hook.borrow_mut().output(text);
+    }
+}
@@ -0,0 +1,43 @@
+use std::{
+    error::Error,
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+/// Lets another thread stop a running interpreter. The interpreter polls it at loop
+/// back-edges and function calls and unwinds with `Interrupted`.
+#[derive(Debug, Clone, Default)]
+pub struct InterruptHandle(Arc<AtomicBool>);
+
+impl InterruptHandle {
+    pub fn interrupt(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    /// Fails once per `interrupt()`, so the interpreter can run again afterwards.
+    pub fn check(&self) -> Result<(), Box<dyn Error>> {
+        if self.0.swap(false, Ordering::SeqCst) {
+            return Err(Box::new(Interrupted));
+        }
+        Ok(())
+    }
+}
+
+pub struct Interrupted;
+
+impl fmt::Display for Interrupted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Interrupted.")
+    }
+}
+
+impl fmt::Debug for Interrupted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+impl Error for Interrupted {}
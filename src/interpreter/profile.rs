@@ -0,0 +1,214 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fmt::Write,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+const SCRIPT: &str = "<script>";
+
+/// Time and calls spent in one function. Natives have no declaration line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionProfile {
+    pub name: String,
+    pub line: Option<i64>,
+    pub calls: u64,
+    /// Time from entering to leaving the function, not counting recursive calls twice.
+    pub inclusive: Duration,
+    /// Time spent in the function's own code, leaving out the functions it calls.
+    pub exclusive: Duration,
+}
+
+#[derive(Debug)]
+struct Call {
+    function: (String, Option<i64>),
+    started: Instant,
+    // Inclusive time of the calls made from this one so far.
+    callees: Duration,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    stack: Vec<Call>,
+    functions: HashMap<(String, Option<i64>), FunctionProfile>,
+    // Exclusive time per call stack, as `<script>;outer;inner`.
+    stacks: HashMap<String, Duration>,
+    // Keyed by an empty path for code that isn't from a file.
+    lines: HashMap<PathBuf, HashMap<i64, u64>>,
+}
+
+/// Records calls, timings and line hits for `lox --profile`. Shared through an `Rc`
+/// so that the interpreter clones made for each call all record into one profile.
+#[derive(Debug)]
+pub struct Profiler {
+    state: RefCell<State>,
+}
+
+impl Default for Profiler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Profiler {
+    /// Starts timing the top-level code.
+    pub fn new() -> Self {
+        let profiler = Self {
+            state: RefCell::new(State::default()),
+        };
+        profiler.enter(SCRIPT.to_string(), None);
+        profiler
+    }
+
+    pub fn enter(&self, name: String, line: Option<i64>) {
+        self.state.borrow_mut().stack.push(Call {
+            function: (name, line),
+            started: Instant::now(),
+            callees: Duration::ZERO,
+        });
+    }
+
+    pub fn exit(&self) {
+        let mut state = self.state.borrow_mut();
+        let Some(call) = state.stack.pop() else {
+            return;
+        };
+        let elapsed = call.started.elapsed();
+        let exclusive = elapsed.saturating_sub(call.callees);
+        let recursive = state.stack.iter().any(|c| c.function == call.function);
+
+        let mut path: Vec<&str> = state.stack.iter().map(|c| c.function.0.as_str()).collect();
+        path.push(&call.function.0);
+        let path = path.join(";");
+        *state.stacks.entry(path).or_default() += exclusive;
+
+        if let Some(caller) = state.stack.last_mut() {
+            caller.callees += elapsed;
+        }
+        let (name, line) = call.function.clone();
+        let profile = state
+            .functions
+            .entry(call.function)
+            .or_insert(FunctionProfile {
+                name,
+                line,
+                calls: 0,
+                inclusive: Duration::ZERO,
+                exclusive: Duration::ZERO,
+            });
+        profile.calls += 1;
+        profile.exclusive += exclusive;
+        if !recursive {
+            profile.inclusive += elapsed;
+        }
+    }
+
+    /// Counts a statement starting on `line` of the file at `path`.
+    pub fn hit(&self, path: Option<&Path>, line: i64) {
+        let path = path.unwrap_or(Path::new(""));
+        let mut state = self.state.borrow_mut();
+        if let Some(lines) = state.lines.get_mut(path) {
+            *lines.entry(line).or_default() += 1;
+            return;
+        }
+        state
+            .lines
+            .insert(path.to_path_buf(), HashMap::from([(line, 1)]));
+    }
+
+    /// Stops timing the top-level code, and any calls an error left unfinished.
+    pub fn finish(&self) {
+        while !self.state.borrow().stack.is_empty() {
+            self.exit();
+        }
+    }
+
+    /// Every function called, most exclusive time first.
+    pub fn functions(&self) -> Vec<FunctionProfile> {
+        let mut functions: Vec<FunctionProfile> =
+            self.state.borrow().functions.values().cloned().collect();
+        functions.sort_by(|a, b| {
+            b.exclusive
+                .cmp(&a.exclusive)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        functions
+    }
+
+    /// Hits per file and line, most hits first.
+    pub fn lines(&self) -> Vec<(Option<PathBuf>, i64, u64)> {
+        let state = self.state.borrow();
+        let mut lines: Vec<(Option<PathBuf>, i64, u64)> = state
+            .lines
+            .iter()
+            .flat_map(|(path, lines)| {
+                let path = (!path.as_os_str().is_empty()).then(|| path.clone());
+                lines
+                    .iter()
+                    .map(move |(line, hits)| (path.clone(), *line, *hits))
+            })
+            .collect();
+        lines.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| (&a.0, a.1).cmp(&(&b.0, b.1))));
+        lines
+    }
+
+    /// One line per call stack with its exclusive time in microseconds, the format
+    /// flamegraph tools read.
+    pub fn folded(&self) -> String {
+        let state = self.state.borrow();
+        let mut stacks: Vec<(&String, &Duration)> = state.stacks.iter().collect();
+        stacks.sort();
+        stacks
+            .into_iter()
+            .map(|(stack, time)| format!("{} {}\n", stack, time.as_micros()))
+            .collect()
+    }
+
+    /// The function table followed by the line table, with each line's source where
+    /// the file can be read.
+    pub fn report(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "{:<24}{:>6}{:>10}{:>16}{:>16}",
+            "Function", "Line", "Calls", "Inclusive ms", "Exclusive ms"
+        );
+        for function in self.functions() {
+            let line = function
+                .line
+                .map_or("-".to_string(), |line| line.to_string());
+            let _ = writeln!(
+                out,
+                "{:<24}{:>6}{:>10}{:>16.3}{:>16.3}",
+                function.name,
+                line,
+                function.calls,
+                function.inclusive.as_secs_f64() * 1000.0,
+                function.exclusive.as_secs_f64() * 1000.0
+            );
+        }
+
+        let _ = writeln!(out, "\n{:<24}{:>10}  Source", "Line", "Hits");
+        let mut sources: HashMap<PathBuf, Vec<String>> = HashMap::new();
+        for (path, line, hits) in self.lines() {
+            let (location, text) = match &path {
+                Some(path) => {
+                    let lines = sources.entry(path.clone()).or_insert_with(|| {
+                        let source = std::fs::read_to_string(path).unwrap_or_default();
+                        source.lines().map(str::to_string).collect()
+                    });
+                    let file = path.file_name().unwrap_or_default().to_string_lossy();
+                    let text = usize::try_from(line - 1)
+                        .ok()
+                        .and_then(|i| lines.get(i))
+                        .map_or("", |text| text.trim());
+                    (format!("{}:{}", file, line), text.to_string())
+                }
+                None => (line.to_string(), String::new()),
+            };
+            let _ = writeln!(out, "{:<24}{:>10}  {}", location, hits, text);
+        }
+        out
+    }
+}
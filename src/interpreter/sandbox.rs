@@ -0,0 +1,160 @@
+use std::{
+    cell::Cell,
+    error::Error,
+    fmt,
+    time::{Duration, Instant},
+};
+
+use crate::object::Object;
+
+/// Resource limits for running untrusted scripts. `None` leaves a resource unlimited.
+#[derive(Debug, Clone, Default)]
+pub struct Limits {
+    // Statements executed plus expressions evaluated.
+    pub fuel: Option<u64>,
+    // Measured from the first step the interpreter takes.
+    pub deadline: Option<Duration>,
+    // In characters.
+    pub max_string_len: Option<usize>,
+    pub max_collection_len: Option<usize>,
+    pub max_call_depth: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitKind {
+    Fuel,
+    Deadline,
+    StringSize,
+    CollectionSize,
+    CallDepth,
+}
+
+/// Raised when a script exceeds one of its `Limits`. Hosts tell the kinds apart by
+/// downcasting the interpreter's error to `LimitError`.
+pub struct LimitError {
+    pub kind: LimitKind,
+    pub message: String,
+}
+
+impl fmt::Display for LimitKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            LimitKind::Fuel => "fuel",
+            LimitKind::Deadline => "deadline",
+            LimitKind::StringSize => "string size",
+            LimitKind::CollectionSize => "collection size",
+            LimitKind::CallDepth => "call depth",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl fmt::Display for LimitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "LimitError ({}): {}", self.kind, self.message)
+    }
+}
+
+impl fmt::Debug for LimitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+impl Error for LimitError {}
+
+/// Tracks usage against `Limits`. Shared through an `Rc` so that the interpreter
+/// clones made for each call all draw from the same budget.
+#[derive(Debug, Default)]
+pub struct Sandbox {
+    limits: Limits,
+    fuel_used: Cell<u64>,
+    started: Cell<Option<Instant>>,
+    call_depth: Cell<usize>,
+}
+
+impl Sandbox {
+    pub fn new(limits: Limits) -> Self {
+        Self {
+            limits,
+            ..Self::default()
+        }
+    }
+
+    fn error(kind: LimitKind, message: String) -> Box<dyn Error> {
+        Box::new(LimitError { kind, message })
+    }
+
+    /// Charges one unit of fuel and checks the deadline.
+    pub fn step(&self) -> Result<(), Box<dyn Error>> {
+        let used = self.fuel_used.get() + 1;
+        self.fuel_used.set(used);
+        if let Some(fuel) = self.limits.fuel {
+            if used > fuel {
+                return Err(Self::error(
+                    LimitKind::Fuel,
+                    format!("Fuel budget of {} steps exhausted.", fuel),
+                ));
+            }
+        }
+
+        if let Some(deadline) = self.limits.deadline {
+            let started = *self.started.get().get_or_insert_with(Instant::now);
+            self.started.set(Some(started));
+            if started.elapsed() > deadline {
+                return Err(Self::error(
+                    LimitKind::Deadline,
+                    format!("Deadline of {:?} exceeded.", deadline),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn enter_call(&self) -> Result<(), Box<dyn Error>> {
+        let depth = self.call_depth.get() + 1;
+        if let Some(max) = self.limits.max_call_depth {
+            if depth > max {
+                return Err(Self::error(
+                    LimitKind::CallDepth,
+                    format!("Call depth of {} exceeded.", max),
+                ));
+            }
+        }
+        self.call_depth.set(depth);
+        Ok(())
+    }
+
+    pub fn exit_call(&self) {
+        self.call_depth.set(self.call_depth.get() - 1);
+    }
+
+    /// Rejects strings and lists larger than the configured caps.
+    pub fn check_size(&self, value: &Object) -> Result<(), Box<dyn Error>> {
+        match value {
+            Object::String(s) => {
+                if let Some(max) = self.limits.max_string_len {
+                    if s.len() > max && s.chars().count() > max {
+                        return Err(Self::error(
+                            LimitKind::StringSize,
+                            format!("String longer than {} characters.", max),
+                        ));
+                    }
+                }
+            }
+            Object::List(items) => {
+                if let Some(max) = self.limits.max_collection_len {
+                    if items.borrow().len() > max {
+                        return Err(Self::error(
+                            LimitKind::CollectionSize,
+                            format!("Collection larger than {} elements.", max),
+                        ));
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,32 @@
+use std::{error::Error, fmt};
+
+use crate::object::Object;
+
+/// A value raised by `throw`, unwinding until a `catch` binds it. Kept apart from
+/// `LoxError` so that rethrowing a caught value doesn't change it.
+pub struct Throw {
+    pub value: Object,
+    pub line: i64,
+}
+
+impl fmt::Display for Throw {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.value {
+            // A rethrown runtime error reports where it was first raised.
+            Object::Error(error) => write!(f, "{}", error),
+            value => write!(
+                f,
+                "RuntimeError [line {}] : Uncaught exception: {}",
+                self.line, value
+            ),
+        }
+    }
+}
+
+impl fmt::Debug for Throw {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+impl Error for Throw {}
@@ -0,0 +1,289 @@
+use std::{fmt, iter::Peekable, str::Chars};
+
+/// A JSON value, for the machine-readable output consumed by editor tooling and the
+/// messages exchanged with it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    // Integers are kept apart from floats so that those beyond 2^53 are exact.
+    Int(i64),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    // Keeps insertion order so output is stable.
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    pub fn object<K: Into<String>>(fields: impl IntoIterator<Item = (K, Json)>) -> Self {
+        Json::Object(fields.into_iter().map(|(k, v)| (k.into(), v)).collect())
+    }
+
+    /// Parses a complete JSON document.
+    pub fn parse(text: &str) -> Result<Json, String> {
+        let mut reader = Reader {
+            chars: text.chars().peekable(),
+        };
+        let value = reader.value()?;
+        reader.skip_whitespace();
+        match reader.chars.next() {
+            None => Ok(value),
+            Some(ch) => Err(format!("Unexpected '{}' after JSON value.", ch)),
+        }
+    }
+
+    /// The value of `key` if this is an object that has it.
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Json::Int(n) => Some(*n as f64),
+            Json::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Json]> {
+        match self {
+            Json::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    fn write_string(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+        write!(f, "\"")?;
+        for ch in s.chars() {
+            match ch {
+                '"' => write!(f, "\\\"")?,
+                '\\' => write!(f, "\\\\")?,
+                '\n' => write!(f, "\\n")?,
+                '\r' => write!(f, "\\r")?,
+                '\t' => write!(f, "\\t")?,
+                ch if (ch as u32) < 0x20 => write!(f, "\\u{:04x}", ch as u32)?,
+                ch => write!(f, "{}", ch)?,
+            }
+        }
+        write!(f, "\"")
+    }
+}
+
+struct Reader<'a> {
+    chars: Peekable<Chars<'a>>,
+}
+
+impl Reader<'_> {
+    fn value(&mut self) -> Result<Json, String> {
+        self.skip_whitespace();
+        match self.chars.peek() {
+            Some('n') => self.keyword("null", Json::Null),
+            Some('t') => self.keyword("true", Json::Bool(true)),
+            Some('f') => self.keyword("false", Json::Bool(false)),
+            Some('"') => self.string().map(Json::String),
+            Some('[') => self.array(),
+            Some('{') => self.object(),
+            Some(ch) if *ch == '-' || ch.is_ascii_digit() => self.number(),
+            Some(ch) => Err(format!("Unexpected '{}' in JSON.", ch)),
+            None => Err("Unexpected end of JSON.".to_string()),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.next_if(|ch| ch.is_ascii_whitespace()).is_some() {}
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        self.skip_whitespace();
+        match self.chars.next() {
+            Some(ch) if ch == expected => Ok(()),
+            Some(ch) => Err(format!("Expect '{}' but found '{}' in JSON.", expected, ch)),
+            None => Err(format!("Expect '{}' but found end of JSON.", expected)),
+        }
+    }
+
+    fn keyword(&mut self, keyword: &str, value: Json) -> Result<Json, String> {
+        for expected in keyword.chars() {
+            if self.chars.next() != Some(expected) {
+                return Err(format!("Invalid literal in JSON, expected '{}'.", keyword));
+            }
+        }
+        Ok(value)
+    }
+
+    fn number(&mut self) -> Result<Json, String> {
+        let mut text = String::new();
+        while let Some(ch) = self
+            .chars
+            .next_if(|ch| ch.is_ascii_digit() || matches!(ch, '-' | '+' | '.' | 'e' | 'E'))
+        {
+            text.push(ch);
+        }
+        if let Ok(n) = text.parse() {
+            return Ok(Json::Int(n));
+        }
+        text.parse()
+            .map(Json::Number)
+            .map_err(|_| format!("Invalid number '{}' in JSON.", text))
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+        let mut s = String::new();
+        loop {
+            match self.chars.next() {
+                Some('"') => return Ok(s),
+                Some('\\') => match self.chars.next() {
+                    Some('"') => s.push('"'),
+                    Some('\\') => s.push('\\'),
+                    Some('/') => s.push('/'),
+                    Some('b') => s.push('\u{8}'),
+                    Some('f') => s.push('\u{c}'),
+                    Some('n') => s.push('\n'),
+                    Some('r') => s.push('\r'),
+                    Some('t') => s.push('\t'),
+                    Some('u') => s.push(self.unicode_escape()?),
+                    _ => return Err("Invalid escape sequence in JSON string.".to_string()),
+                },
+                Some(ch) => s.push(ch),
+                None => return Err("Unterminated JSON string.".to_string()),
+            }
+        }
+    }
+
+    /// The character after `\u`, combining a UTF-16 surrogate pair when one follows.
+    fn unicode_escape(&mut self) -> Result<char, String> {
+        let high = self.hex4()?;
+        if !(0xD800..0xDC00).contains(&high) {
+            return char::from_u32(high).ok_or_else(|| "Invalid unicode escape.".to_string());
+        }
+        if self.chars.next() != Some('\\') || self.chars.next() != Some('u') {
+            return Err("Unpaired surrogate in JSON string.".to_string());
+        }
+        let low = self.hex4()?;
+        let code = 0x10000 + ((high - 0xD800) << 10) + (low.wrapping_sub(0xDC00) & 0x3FF);
+        char::from_u32(code).ok_or_else(|| "Invalid unicode escape.".to_string())
+    }
+
+    fn hex4(&mut self) -> Result<u32, String> {
+        let digits: String = (0..4).filter_map(|_| self.chars.next()).collect();
+        u32::from_str_radix(&digits, 16).map_err(|_| "Invalid unicode escape.".to_string())
+    }
+
+    fn array(&mut self) -> Result<Json, String> {
+        self.expect('[')?;
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.chars.next_if_eq(&']').is_some() {
+            return Ok(Json::Array(items));
+        }
+        loop {
+            items.push(self.value()?);
+            self.skip_whitespace();
+            match self.chars.next() {
+                Some(',') => continue,
+                Some(']') => return Ok(Json::Array(items)),
+                _ => return Err("Expect ',' or ']' in JSON array.".to_string()),
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<Json, String> {
+        self.expect('{')?;
+        let mut fields = Vec::new();
+        self.skip_whitespace();
+        if self.chars.next_if_eq(&'}').is_some() {
+            return Ok(Json::Object(fields));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.expect(':')?;
+            fields.push((key, self.value()?));
+            self.skip_whitespace();
+            match self.chars.next() {
+                Some(',') => continue,
+                Some('}') => return Ok(Json::Object(fields)),
+                _ => return Err("Expect ',' or '}' in JSON object.".to_string()),
+            }
+        }
+    }
+}
+
+impl From<&str> for Json {
+    fn from(s: &str) -> Self {
+        Json::String(s.to_string())
+    }
+}
+
+impl From<String> for Json {
+    fn from(s: String) -> Self {
+        Json::String(s)
+    }
+}
+
+impl From<i64> for Json {
+    fn from(n: i64) -> Self {
+        Json::Int(n)
+    }
+}
+
+impl From<usize> for Json {
+    fn from(n: usize) -> Self {
+        i64::try_from(n).map_or(Json::Number(n as f64), Json::Int)
+    }
+}
+
+impl From<bool> for Json {
+    fn from(b: bool) -> Self {
+        Json::Bool(b)
+    }
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(b) => write!(f, "{}", b),
+            Json::Int(n) => write!(f, "{}", n),
+            // JSON has no representation for NaN or the infinities.
+            Json::Number(n) if !n.is_finite() => write!(f, "null"),
+            // A whole float keeps its fraction so that it parses back as a float.
+            Json::Number(n) if n.fract() == 0.0 => write!(f, "{:.1}", n),
+            Json::Number(n) => write!(f, "{}", n),
+            Json::String(s) => Self::write_string(f, s),
+            Json::Array(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            }
+            Json::Object(fields) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    Self::write_string(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
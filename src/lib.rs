@@ -0,0 +1,23 @@
+pub mod ast_printer;
+pub mod callable;
+pub mod debugger;
+pub mod env;
+pub mod error;
+pub mod expr;
+pub mod formatter;
+pub mod function;
+pub mod interpreter;
+pub mod json;
+pub mod linter;
+pub mod lsp;
+pub mod module;
+pub mod object;
+pub mod parser;
+pub mod resolver;
+pub mod scanner;
+pub mod stmt;
+pub mod test_runner;
+pub mod token;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,457 @@
+use std::{collections::HashSet, fmt, rc::Rc, str::FromStr};
+
+use crate::{
+    callable::{self, Capabilities},
+    expr::{self, Expr},
+    object::Object,
+    resolver::{BindingKind, Bindings},
+    stmt::{self, Stmt},
+    token::{token_type::TokenType, Token},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Rule {
+    UnusedVariable,
+    UnusedParameter,
+    ShadowedVariable,
+    UnreachableCode,
+    UndeclaredAssignment,
+    MismatchedComparison,
+    WrongArity,
+}
+
+impl Rule {
+    pub const ALL: [Rule; 7] = [
+        Rule::UnusedVariable,
+        Rule::UnusedParameter,
+        Rule::ShadowedVariable,
+        Rule::UnreachableCode,
+        Rule::UndeclaredAssignment,
+        Rule::MismatchedComparison,
+        Rule::WrongArity,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Rule::UnusedVariable => "unused-variable",
+            Rule::UnusedParameter => "unused-parameter",
+            Rule::ShadowedVariable => "shadowed-variable",
+            Rule::UnreachableCode => "unreachable-code",
+            Rule::UndeclaredAssignment => "undeclared-assignment",
+            Rule::MismatchedComparison => "mismatched-comparison",
+            Rule::WrongArity => "wrong-arity",
+        }
+    }
+}
+
+impl FromStr for Rule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Rule::ALL
+            .into_iter()
+            .find(|rule| rule.name() == s)
+            .ok_or_else(|| {
+                let rules: Vec<&str> = Rule::ALL.iter().map(Rule::name).collect();
+                format!("Unknown rule '{}'. Rules: {}.", s, rules.join(", "))
+            })
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Where a warning points: a 1-based line and column and a length in characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Span {
+    pub line: i64,
+    pub column: usize,
+    pub length: usize,
+}
+
+impl From<&Token> for Span {
+    fn from(token: &Token) -> Self {
+        Self {
+            line: token.line,
+            column: token.column,
+            length: token.lexeme.chars().count(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Warning {
+    pub rule: Rule,
+    pub message: String,
+    pub span: Span,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Warning [line {}:{}] ({}): {}",
+            self.span.line, self.span.column, self.rule, self.message
+        )
+    }
+}
+
+/// A static pass over parsed statements that reports likely mistakes as warnings.
+/// The rules about names work from the `Bindings` the resolver found for the same
+/// statements.
+pub struct Linter {
+    rules: Vec<Rule>,
+    builtins: Vec<&'static str>,
+}
+
+impl Default for Linter {
+    fn default() -> Self {
+        Self::new(Rule::ALL.to_vec())
+    }
+}
+
+impl Linter {
+    pub fn new(rules: Vec<Rule>) -> Self {
+        Self {
+            rules,
+            builtins: callable::get_native_functions(Capabilities::default())
+                .into_iter()
+                .map(|(name, _)| name)
+                .collect(),
+        }
+    }
+
+    /// Returns the warnings for `statements`, ordered by position.
+    pub fn lint(self, statements: &[Stmt], bindings: &Bindings) -> Vec<Warning> {
+        let mut pass = Pass {
+            linter: &self,
+            bindings,
+            read: HashSet::new(),
+            warnings: Vec::new(),
+        };
+        pass.statements(statements);
+        pass.names();
+
+        pass.warnings.sort_by_key(|warning| warning.span);
+        pass.warnings
+    }
+}
+
+struct Pass<'a> {
+    linter: &'a Linter,
+    bindings: &'a Bindings,
+    // The bindings that some expression reads; assigning to a name doesn't use it.
+    read: HashSet<usize>,
+    warnings: Vec<Warning>,
+}
+
+impl Pass<'_> {
+    fn warn(&mut self, rule: Rule, token: &Token, message: String) {
+        if self.linter.rules.contains(&rule) {
+            self.warnings.push(Warning {
+                rule,
+                message,
+                span: Span::from(token),
+            });
+        }
+    }
+
+    /// Warns about local names that are never read or that hide another declaration.
+    fn names(&mut self) {
+        let bindings = self.bindings;
+        for (index, binding) in bindings.bindings.iter().enumerate() {
+            if binding.global {
+                continue;
+            }
+            let name = &binding.name.lexeme;
+
+            if let Some(outer) = binding.shadows {
+                let line = bindings.bindings[outer].name.line;
+                self.warn(
+                    Rule::ShadowedVariable,
+                    &binding.name,
+                    format!("'{}' shadows a declaration on line {}.", name, line),
+                );
+            }
+
+            if self.read.contains(&index) || name.starts_with('_') {
+                continue;
+            }
+            match binding.kind {
+                BindingKind::Parameter => self.warn(
+                    Rule::UnusedParameter,
+                    &binding.name,
+                    format!("Parameter '{}' is never used.", name),
+                ),
+                BindingKind::Function(_) => self.warn(
+                    Rule::UnusedVariable,
+                    &binding.name,
+                    format!("Function '{}' is never used.", name),
+                ),
+                BindingKind::Variable => self.warn(
+                    Rule::UnusedVariable,
+                    &binding.name,
+                    format!("Variable '{}' is never used.", name),
+                ),
+                BindingKind::Import | BindingKind::Catch => {}
+            }
+        }
+    }
+
+    fn statements(&mut self, statements: &[Stmt]) {
+        let mut exit: Option<&Token> = None;
+        for statement in statements {
+            if let Some(keyword) = exit.take() {
+                self.warn(
+                    Rule::UnreachableCode,
+                    Self::first_token(statement).unwrap_or(keyword),
+                    format!("Code after '{}' is unreachable.", keyword.lexeme),
+                );
+            }
+            statement.accept(self);
+            exit = match statement {
+                Stmt::Return(stmt) => Some(&stmt.keyword),
+                Stmt::Break(stmt) => Some(&stmt.keyword),
+                Stmt::Continue(stmt) => Some(&stmt.keyword),
+                Stmt::Throw(stmt) => Some(&stmt.keyword),
+                _ => None,
+            };
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        expr.accept(self)
+    }
+
+    /// The earliest token of `statement` the AST keeps, for pointing at it as a whole.
+    /// `var` and `fun` aren't kept, so declarations point at their name.
+    fn first_token(statement: &Stmt) -> Option<&Token> {
+        match statement {
+            Stmt::Block(stmt) => stmt.statements.first().and_then(Self::first_token),
+            Stmt::Class(_) => None,
+            Stmt::Break(stmt) => Some(&stmt.keyword),
+            Stmt::Continue(stmt) => Some(&stmt.keyword),
+            Stmt::Expression(stmt) => Self::first_expr_token(&stmt.expression),
+            Stmt::Function(stmt) => Some(&stmt.name),
+            Stmt::If(stmt) => Some(&stmt.keyword),
+            Stmt::Import(stmt) => Some(&stmt.path),
+            Stmt::Print(stmt) => Some(&stmt.keyword),
+            Stmt::Return(stmt) => Some(&stmt.keyword),
+            Stmt::Throw(stmt) => Some(&stmt.keyword),
+            Stmt::Try(stmt) => Some(&stmt.keyword),
+            Stmt::Var(stmt) => Some(&stmt.name),
+            Stmt::While(stmt) => Some(&stmt.keyword),
+        }
+    }
+
+    fn first_expr_token(expr: &Expr) -> Option<&Token> {
+        match expr {
+            Expr::Assign(expr) => Some(&expr.name),
+            Expr::Binary(expr) => Self::first_expr_token(&expr.left).or(Some(&expr.operator)),
+            Expr::Call(expr) => Self::first_expr_token(&expr.callee).or(Some(&expr.paren)),
+            Expr::Conditional(expr) => Self::first_expr_token(&expr.condition),
+            Expr::Get(expr) => Self::first_expr_token(&expr.object).or(Some(&expr.name)),
+            Expr::Grouping(expr) => Self::first_expr_token(&expr.expression),
+            Expr::Logical(expr) => Self::first_expr_token(&expr.left).or(Some(&expr.operator)),
+            Expr::Set(expr) => Self::first_expr_token(&expr.object).or(Some(&expr.name)),
+            Expr::Unary(expr) => Some(&expr.operator),
+            Expr::Variable(expr) => Some(&expr.name),
+            Expr::Literal(_) | Expr::Super(_) | Expr::This(_) => None,
+        }
+    }
+
+    /// The type of an expression whose type is known without running it.
+    fn static_type(expr: &Expr) -> Option<&'static str> {
+        match expr {
+            Expr::Literal(literal) => Some(match literal.value {
+                Object::Int(_) | Object::Number(_) => "number",
+                ref value => value.type_name(),
+            }),
+            Expr::Grouping(group) => Self::static_type(&group.expression),
+            Expr::Unary(unary) if unary.operator.type_ == TokenType::BANG => Some("boolean"),
+            Expr::Unary(unary) => Self::static_type(&unary.right),
+            _ => None,
+        }
+    }
+}
+
+impl expr::Visitor<()> for Pass<'_> {
+    fn visit_assign_expr(&mut self, expr: &expr::Assign) {
+        self.expr(&expr.value);
+        let declared = self.bindings.get(expr.id).is_some()
+            || self.linter.builtins.contains(&&*expr.name.lexeme);
+        if !declared {
+            self.warn(
+                Rule::UndeclaredAssignment,
+                &expr.name,
+                format!("Assignment to undeclared variable '{}'.", expr.name.lexeme),
+            );
+        }
+    }
+
+    fn visit_binary_expr(&mut self, expr: &expr::Binary) {
+        self.expr(&expr.left);
+        self.expr(&expr.right);
+
+        use TokenType::*;
+        if !matches!(
+            expr.operator.type_,
+            EQUAL_EQUAL | BANG_EQUAL | LESS | LESS_EQUAL | GREATER | GREATER_EQUAL
+        ) {
+            return;
+        }
+        if let (Some(left), Some(right)) = (
+            Self::static_type(&expr.left),
+            Self::static_type(&expr.right),
+        ) {
+            if left != right {
+                self.warn(
+                    Rule::MismatchedComparison,
+                    &expr.operator,
+                    format!(
+                        "Comparison '{}' between a {} and a {}.",
+                        expr.operator.lexeme, left, right
+                    ),
+                );
+            }
+        }
+    }
+
+    fn visit_call_expr(&mut self, expr: &expr::Call) {
+        self.expr(&expr.callee);
+        for argument in &expr.arguments {
+            self.expr(argument);
+        }
+
+        let Expr::Variable(callee) = expr.callee.as_ref() else {
+            return;
+        };
+        let Some(BindingKind::Function(arity)) = self.bindings.get(callee.id).map(|b| b.kind)
+        else {
+            return;
+        };
+        if arity != expr.arguments.len() {
+            self.warn(
+                Rule::WrongArity,
+                &callee.name,
+                format!(
+                    "Function '{}' takes {} arguments but is called with {}.",
+                    callee.name.lexeme,
+                    arity,
+                    expr.arguments.len()
+                ),
+            );
+        }
+    }
+
+    fn visit_conditional_expr(&mut self, expr: &expr::Conditional) {
+        self.expr(&expr.condition);
+        self.expr(&expr.then_branch);
+        self.expr(&expr.else_branch);
+    }
+
+    fn visit_get_expr(&mut self, expr: &expr::Get) {
+        self.expr(&expr.object);
+    }
+
+    fn visit_group_expr(&mut self, expr: &expr::Grouping) {
+        self.expr(&expr.expression);
+    }
+
+    fn visit_literal_expr(&mut self, _expr: &expr::Literal) {}
+
+    fn visit_logical_expr(&mut self, expr: &expr::Logical) {
+        self.expr(&expr.left);
+        self.expr(&expr.right);
+    }
+
+    fn visit_set_expr(&mut self, expr: &expr::Set) {
+        self.expr(&expr.object);
+        self.expr(&expr.value);
+    }
+
+    fn visit_super_expr(&mut self, _expr: &expr::Super) {}
+
+    fn visit_this_expr(&mut self, _expr: &expr::This) {}
+
+    fn visit_unary_expr(&mut self, expr: &expr::Unary) {
+        self.expr(&expr.right);
+    }
+
+    fn visit_variable_expr(&mut self, expr: &expr::Variable) {
+        if let Some(&binding) = self.bindings.resolved.get(&expr.id) {
+            self.read.insert(binding);
+        }
+    }
+}
+
+impl stmt::Visitor<()> for Pass<'_> {
+    fn visit_block_stmt(&mut self, stmt: &stmt::Block) {
+        self.statements(&stmt.statements);
+    }
+
+    fn visit_break_stmt(&mut self, _stmt: &stmt::Break) {}
+
+    fn visit_class_stmt(&mut self, _stmt: &stmt::Class) {}
+
+    fn visit_continue_stmt(&mut self, _stmt: &stmt::Continue) {}
+
+    fn visit_expr_stmt(&mut self, stmt: &stmt::Expression) {
+        self.expr(&stmt.expression);
+    }
+
+    fn visit_func_stmt(&mut self, stmt: &Rc<stmt::Function>) {
+        self.statements(&stmt.body);
+    }
+
+    fn visit_if_stmt(&mut self, stmt: &stmt::If) {
+        self.expr(&stmt.condition);
+        stmt.then_branch.accept(self);
+        if let Some(else_branch) = &stmt.else_branch {
+            else_branch.accept(self);
+        }
+    }
+
+    fn visit_import_stmt(&mut self, _stmt: &stmt::Import) {}
+
+    fn visit_print_stmt(&mut self, stmt: &stmt::Print) {
+        self.expr(&stmt.expression);
+    }
+
+    fn visit_return_stmt(&mut self, stmt: &stmt::Return) {
+        if let Some(value) = &stmt.value {
+            self.expr(value);
+        }
+    }
+
+    fn visit_throw_stmt(&mut self, stmt: &stmt::Throw) {
+        self.expr(&stmt.value);
+    }
+
+    fn visit_try_stmt(&mut self, stmt: &stmt::Try) {
+        self.statements(&stmt.body);
+        if let Some((_, body)) = &stmt.catch {
+            self.statements(body);
+        }
+        if let Some(body) = &stmt.finally {
+            self.statements(body);
+        }
+    }
+
+    fn visit_var_stmt(&mut self, stmt: &stmt::Var) {
+        if let Some(initializer) = &stmt.initializer {
+            self.expr(initializer);
+        }
+    }
+
+    fn visit_while_stmt(&mut self, stmt: &stmt::While) {
+        self.expr(&stmt.condition);
+        stmt.body.accept(self);
+        if let Some(increment) = &stmt.increment {
+            self.expr(increment);
+        }
+    }
+}
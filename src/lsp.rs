@@ -0,0 +1,375 @@
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, Write},
+};
+
+use crate::{json::Json, linter::Span};
+
+pub mod analysis;
+
+use analysis::{Analysis, Position, Severity, SymbolKind};
+
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// A language server for Lox documents. It handles one JSON-RPC message at a time and
+/// returns what to send back, so it can be driven without an editor; `serve` connects
+/// it to a reader and writer using LSP's `Content-Length` framing.
+///
+/// Documents are synced in full and analysed again on every request. Positions are
+/// converted as if every character were one UTF-16 code unit.
+#[derive(Default)]
+pub struct Server {
+    documents: HashMap<String, String>,
+    shut_down: bool,
+    exited: bool,
+}
+
+impl Server {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether the client has sent `exit`.
+    pub fn exited(&self) -> bool {
+        self.exited
+    }
+
+    /// Handles a request or notification and returns the responses and notifications
+    /// to send.
+    pub fn handle(&mut self, message: &Json) -> Vec<Json> {
+        let Some(method) = message.get("method").and_then(Json::as_str) else {
+            // A response to a request we never make.
+            return Vec::new();
+        };
+        let params = message.get("params").unwrap_or(&Json::Null);
+
+        let Some(id) = message.get("id") else {
+            return self.notification(method, params);
+        };
+        let response = match self.request(method, params) {
+            Some(Ok(result)) => ("result", result),
+            Some(Err((code, message))) => ("error", Self::error(code, message)),
+            None => (
+                "error",
+                Self::error(METHOD_NOT_FOUND, format!("Unhandled method '{}'.", method)),
+            ),
+        };
+        vec![Json::object([
+            ("jsonrpc", Json::from("2.0")),
+            ("id", id.clone()),
+            response,
+        ])]
+    }
+
+    fn error(code: i64, message: String) -> Json {
+        Json::object([("code", Json::from(code)), ("message", Json::from(message))])
+    }
+
+    fn notification(&mut self, method: &str, params: &Json) -> Vec<Json> {
+        if method == "exit" {
+            self.exited = true;
+            return Vec::new();
+        }
+        let document = params.get("textDocument");
+        let Some(uri) = document.and_then(|d| d.get("uri")).and_then(Json::as_str) else {
+            return Vec::new();
+        };
+
+        let text = match method {
+            "textDocument/didOpen" => document.and_then(|d| d.get("text")),
+            // Changes are full documents, as `initialize` asks for.
+            "textDocument/didChange" => params
+                .get("contentChanges")
+                .and_then(Json::as_array)
+                .and_then(|changes| changes.last())
+                .and_then(|change| change.get("text")),
+            "textDocument/didClose" => {
+                self.documents.remove(uri);
+                return vec![Self::publish_diagnostics(uri, Json::Array(Vec::new()))];
+            }
+            _ => return Vec::new(),
+        };
+        let Some(text) = text.and_then(Json::as_str) else {
+            return Vec::new();
+        };
+
+        self.documents.insert(uri.to_string(), text.to_string());
+        let diagnostics = Analysis::new(text)
+            .diagnostics
+            .iter()
+            .map(|diagnostic| {
+                let severity = match diagnostic.severity {
+                    Severity::Error => 1,
+                    Severity::Warning => 2,
+                };
+                let mut fields = vec![
+                    ("range", Self::range(&diagnostic.span)),
+                    ("severity", Json::from(severity as i64)),
+                    ("source", Json::from("lox")),
+                    ("message", Json::from(diagnostic.message.as_str())),
+                ];
+                if let Some(code) = diagnostic.code {
+                    fields.push(("code", Json::from(code)));
+                }
+                Json::object(fields)
+            })
+            .collect();
+        vec![Self::publish_diagnostics(uri, Json::Array(diagnostics))]
+    }
+
+    fn publish_diagnostics(uri: &str, diagnostics: Json) -> Json {
+        Json::object([
+            ("jsonrpc", Json::from("2.0")),
+            ("method", Json::from("textDocument/publishDiagnostics")),
+            (
+                "params",
+                Json::object([("uri", Json::from(uri)), ("diagnostics", diagnostics)]),
+            ),
+        ])
+    }
+
+    /// The result of a request, or `None` if the method isn't supported.
+    fn request(&mut self, method: &str, params: &Json) -> Option<Result<Json, (i64, String)>> {
+        let result = match method {
+            "initialize" => Some(Self::capabilities()),
+            "shutdown" => {
+                self.shut_down = true;
+                Some(Json::Null)
+            }
+            "textDocument/definition" => self.definition(params),
+            "textDocument/references" => self.references(params),
+            "textDocument/hover" => self.hover(params),
+            "textDocument/documentSymbol" => self.document_symbols(params),
+            "textDocument/completion" => self.completion(params),
+            _ => return None,
+        };
+        Some(result.ok_or_else(|| (INVALID_PARAMS, format!("Invalid params for '{}'.", method))))
+    }
+
+    fn capabilities() -> Json {
+        Json::object([
+            (
+                "capabilities",
+                Json::object([
+                    ("textDocumentSync", Json::from(1i64)),
+                    ("definitionProvider", Json::from(true)),
+                    ("referencesProvider", Json::from(true)),
+                    ("hoverProvider", Json::from(true)),
+                    ("documentSymbolProvider", Json::from(true)),
+                    ("completionProvider", Json::object::<&str>([])),
+                ]),
+            ),
+            (
+                "serverInfo",
+                Json::object([
+                    ("name", Json::from("lox-lsp")),
+                    ("version", Json::from(env!("CARGO_PKG_VERSION"))),
+                ]),
+            ),
+        ])
+    }
+
+    /// The document a request is about, analysed, and the position it gives, if any.
+    fn document<'a>(&self, params: &'a Json) -> Option<(&'a str, Analysis, Option<Position>)> {
+        let uri = params.get("textDocument")?.get("uri")?.as_str()?;
+        let source = self.documents.get(uri).map_or("", String::as_str);
+        let position = params.get("position").and_then(|position| {
+            let line = position.get("line")?.as_f64()? as i64;
+            let character = position.get("character")?.as_f64()? as usize;
+            Some((line + 1, character + 1))
+        });
+        Some((uri, Analysis::new(source), position))
+    }
+
+    fn definition(&self, params: &Json) -> Option<Json> {
+        let (uri, analysis, position) = self.document(params)?;
+        let declaration = analysis
+            .symbol_at(position?)
+            .and_then(|symbol| analysis.symbols[symbol].declaration.as_ref());
+        Some(declaration.map_or(Json::Null, |token| Self::location(uri, &Span::from(token))))
+    }
+
+    fn references(&self, params: &Json) -> Option<Json> {
+        let (uri, analysis, position) = self.document(params)?;
+        let include_declaration = params
+            .get("context")
+            .and_then(|context| context.get("includeDeclaration"))
+            .is_none_or(|include| *include == Json::Bool(true));
+
+        let Some(symbol) = analysis.symbol_at(position?) else {
+            return Some(Json::Null);
+        };
+        let declaration = analysis.symbols[symbol]
+            .declaration
+            .as_ref()
+            .map(Span::from);
+        let locations = analysis
+            .references(symbol)
+            .filter(|o| include_declaration || Some(o.span) != declaration)
+            .map(|o| Self::location(uri, &o.span))
+            .collect();
+        Some(Json::Array(locations))
+    }
+
+    fn hover(&self, params: &Json) -> Option<Json> {
+        let (_, analysis, position) = self.document(params)?;
+        let Some(occurrence) = analysis.occurrence_at(position?) else {
+            return Some(Json::Null);
+        };
+        let symbol = &analysis.symbols[occurrence.symbol];
+
+        let mut contents = format!("```lox\n{}\n```", symbol.detail);
+        if let Some(doc) = &symbol.doc {
+            contents.push_str("\n\n");
+            contents.push_str(doc);
+        }
+        Some(Json::object([
+            (
+                "contents",
+                Json::object([
+                    ("kind", Json::from("markdown")),
+                    ("value", Json::from(contents)),
+                ]),
+            ),
+            ("range", Self::range(&occurrence.span)),
+        ]))
+    }
+
+    fn document_symbols(&self, params: &Json) -> Option<Json> {
+        let (uri, analysis, _) = self.document(params)?;
+        let symbols = analysis
+            .symbols
+            .iter()
+            .filter_map(|symbol| {
+                // LSP's SymbolKind numbers.
+                let kind: i64 = match symbol.kind {
+                    SymbolKind::Function => 12,
+                    SymbolKind::Variable => 13,
+                    SymbolKind::Import => 2,
+                    SymbolKind::Parameter | SymbolKind::Native | SymbolKind::Constant => {
+                        return None
+                    }
+                };
+                let token = symbol.declaration.as_ref()?;
+                let mut fields = vec![
+                    ("name", Json::from(symbol.name.as_str())),
+                    ("kind", Json::from(kind)),
+                    ("location", Self::location(uri, &Span::from(token))),
+                ];
+                if let Some(container) = &symbol.container {
+                    fields.push(("containerName", Json::from(container.as_str())));
+                }
+                Some(Json::object(fields))
+            })
+            .collect();
+        Some(Json::Array(symbols))
+    }
+
+    fn completion(&self, params: &Json) -> Option<Json> {
+        let (_, analysis, position) = self.document(params)?;
+        let items = analysis
+            .completions(position?)
+            .into_iter()
+            .map(|symbol| {
+                // LSP's CompletionItemKind numbers.
+                let kind: i64 = match symbol.kind {
+                    SymbolKind::Function | SymbolKind::Native => 3,
+                    SymbolKind::Variable | SymbolKind::Parameter => 6,
+                    SymbolKind::Import => 9,
+                    SymbolKind::Constant => 21,
+                };
+                Json::object([
+                    ("label", Json::from(symbol.name.as_str())),
+                    ("kind", Json::from(kind)),
+                    ("detail", Json::from(symbol.detail.as_str())),
+                ])
+            })
+            .collect();
+        Some(Json::Array(items))
+    }
+
+    fn range(span: &Span) -> Json {
+        let line = Json::from((span.line - 1).max(0));
+        let start = span.column.saturating_sub(1);
+        Json::object([
+            (
+                "start",
+                Json::object([("line", line.clone()), ("character", Json::from(start))]),
+            ),
+            (
+                "end",
+                Json::object([
+                    ("line", line),
+                    ("character", Json::from(start + span.length)),
+                ]),
+            ),
+        ])
+    }
+
+    fn location(uri: &str, span: &Span) -> Json {
+        Json::object([("uri", Json::from(uri)), ("range", Self::range(span))])
+    }
+}
+
+/// Reads one `Content-Length` framed message, or `None` at the end of the input.
+pub fn read_message(reader: &mut impl BufRead) -> io::Result<Option<String>> {
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+
+    let Some(length) = length else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Message without a Content-Length header.",
+        ));
+    };
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    String::from_utf8(body)
+        .map(Some)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+pub fn write_message(writer: &mut impl Write, message: &Json) -> io::Result<()> {
+    let body = message.to_string();
+    write!(writer, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    writer.flush()
+}
+
+/// Runs a server until the client sends `exit` or closes the input. Returns whether it
+/// was asked to shut down first, which decides the process's exit code.
+pub fn serve(mut reader: impl BufRead, mut writer: impl Write) -> io::Result<bool> {
+    let mut server = Server::new();
+    while let Some(body) = read_message(&mut reader)? {
+        let replies = match Json::parse(&body) {
+            Ok(message) => server.handle(&message),
+            Err(err) => vec![Json::object([
+                ("jsonrpc", Json::from("2.0")),
+                ("id", Json::Null),
+                ("error", Server::error(PARSE_ERROR, err)),
+            ])],
+        };
+        for reply in &replies {
+            write_message(&mut writer, reply)?;
+        }
+        if server.exited() {
+            break;
+        }
+    }
+    Ok(server.shut_down)
+}
@@ -0,0 +1,405 @@
+use std::{collections::HashMap, error::Error, rc::Rc};
+
+use crate::{
+    callable::{self, Capabilities},
+    error::LoxError,
+    interpreter::Interpreter,
+    linter::{Linter, Span},
+    object::Object,
+    parser::Parser,
+    resolver::{Bindings, Resolver},
+    scanner::Scanner,
+    stmt::{self, Stmt},
+    token::{token_type::TokenType, Token},
+};
+
+/// A 1-based line and column, as tokens report them.
+pub type Position = (i64, usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub span: Span,
+    /// The lint rule that produced a warning.
+    pub code: Option<&'static str>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolKind {
+    Function,
+    Variable,
+    Parameter,
+    Import,
+    Native,
+    /// A native value that isn't a function, like `PI`.
+    Constant,
+}
+
+#[derive(Debug, Clone)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    /// Where the name is declared; natives have no declaration.
+    pub declaration: Option<Token>,
+    /// How the declaration reads, e.g. `fun add(a, b)`.
+    pub detail: String,
+    pub doc: Option<String>,
+    /// The function the symbol is declared in.
+    pub container: Option<String>,
+    // From the declaration to the end of its scope; `None` for globals.
+    visible: Option<(Position, Position)>,
+}
+
+/// A place in the source where a symbol's name appears, including its declaration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Occurrence {
+    pub span: Span,
+    pub symbol: usize,
+}
+
+/// What an editor needs to know about one version of a document: the problems in it
+/// and which declaration every name refers to.
+pub struct Analysis {
+    pub diagnostics: Vec<Diagnostic>,
+    pub symbols: Vec<Symbol>,
+    pub occurrences: Vec<Occurrence>,
+}
+
+impl Analysis {
+    /// Scans, parses, resolves and lints `source`. Names are only indexed when it
+    /// parses, and link to whatever the resolver bound them to; the first scan, parse
+    /// or resolve error becomes an error diagnostic.
+    pub fn new(source: &str) -> Self {
+        let mut analysis = Self {
+            diagnostics: Vec::new(),
+            symbols: Vec::new(),
+            occurrences: Vec::new(),
+        };
+
+        let statements = Scanner::new(source.to_string())
+            .scan_tokens()
+            .and_then(|tokens| Ok((Parser::new(tokens.clone()).parse()?, tokens)));
+        let (statements, tokens) = match statements {
+            Ok(parsed) => parsed,
+            Err(err) => {
+                analysis.error(err);
+                analysis.index(&[], &[], &Bindings::default());
+                return analysis;
+            }
+        };
+
+        let mut interpreter = Interpreter::new();
+        let mut resolver = Resolver::new(&mut interpreter);
+        if let Err(err) = resolver.resolve(&statements) {
+            analysis.error(err);
+        }
+        let bindings = resolver.bindings();
+        for warning in Linter::default().lint(&statements, &bindings) {
+            analysis.diagnostics.push(Diagnostic {
+                severity: Severity::Warning,
+                message: warning.message,
+                span: warning.span,
+                code: Some(warning.rule.name()),
+            });
+        }
+
+        analysis.index(&statements, &tokens, &bindings);
+        analysis
+    }
+
+    fn error(&mut self, err: Box<dyn Error>) {
+        let (message, span) = match err.downcast_ref::<LoxError>() {
+            Some(err) => (
+                err.get_message().unwrap_or_default().to_string(),
+                err.token().map(Span::from),
+            ),
+            None => (err.to_string(), None),
+        };
+        self.diagnostics.push(Diagnostic {
+            severity: Severity::Error,
+            message,
+            span: span.unwrap_or(Span {
+                line: 1,
+                column: 1,
+                length: 0,
+            }),
+            code: None,
+        });
+    }
+
+    fn index(&mut self, statements: &[Stmt], tokens: &[Token], bindings: &Bindings) {
+        let mut indexer = Indexer {
+            symbols: Vec::new(),
+            occurrences: Vec::new(),
+            declared: HashMap::new(),
+            depth: 0,
+            functions: Vec::new(),
+            braces: Indexer::braces(tokens),
+        };
+        let mut natives = HashMap::new();
+        for (name, value) in callable::get_native_functions(Capabilities::default()) {
+            let (kind, detail) = match value {
+                Object::Function(..) => (SymbolKind::Native, format!("native fun {}", name)),
+                value => (SymbolKind::Constant, format!("native {} = {}", name, value)),
+            };
+            let symbol = indexer.add(Symbol {
+                name: name.to_string(),
+                kind,
+                declaration: None,
+                detail,
+                doc: None,
+                container: None,
+                visible: None,
+            });
+            natives.insert(name, symbol);
+        }
+
+        indexer.statements(statements);
+
+        for binding in &bindings.bindings {
+            if let Some(&symbol) = indexer.declared.get(&Indexer::position(&binding.name)) {
+                for name in &binding.uses {
+                    indexer.occur(name, symbol);
+                }
+            }
+        }
+        for name in &bindings.unresolved {
+            if let Some(&symbol) = natives.get(name.lexeme.as_str()) {
+                indexer.occur(name, symbol);
+            }
+        }
+
+        indexer.occurrences.sort();
+        indexer.occurrences.dedup();
+        self.symbols = indexer.symbols;
+        self.occurrences = indexer.occurrences;
+    }
+
+    /// The name at `position`, which may also be just past its end.
+    pub fn occurrence_at(&self, (line, column): Position) -> Option<&Occurrence> {
+        self.occurrences.iter().find(|o| {
+            o.span.line == line
+                && o.span.column <= column
+                && column <= o.span.column + o.span.length
+        })
+    }
+
+    pub fn symbol_at(&self, position: Position) -> Option<usize> {
+        self.occurrence_at(position).map(|o| o.symbol)
+    }
+
+    /// Every occurrence of `symbol`, in source order.
+    pub fn references(&self, symbol: usize) -> impl Iterator<Item = &Occurrence> {
+        self.occurrences.iter().filter(move |o| o.symbol == symbol)
+    }
+
+    /// The symbols that can be referred to at `position`, ordered by name. Where a name
+    /// is declared more than once, the innermost declaration wins.
+    pub fn completions(&self, position: Position) -> Vec<&Symbol> {
+        let mut visible: HashMap<&str, (u8, Position, &Symbol)> = HashMap::new();
+        for symbol in &self.symbols {
+            let rank = match (symbol.kind, symbol.visible) {
+                (SymbolKind::Native | SymbolKind::Constant, _) => (0, (0, 0)),
+                (_, None) => (1, (0, 0)),
+                (_, Some((start, end))) if start < position && position <= end => (2, start),
+                _ => continue,
+            };
+            let best = visible.get(symbol.name.as_str());
+            if best.is_none_or(|&(tier, start, _)| (tier, start) <= rank) {
+                visible.insert(&symbol.name, (rank.0, rank.1, symbol));
+            }
+        }
+
+        let mut symbols: Vec<&Symbol> = visible.into_values().map(|(_, _, s)| s).collect();
+        symbols.sort_by(|a, b| a.name.cmp(&b.name));
+        symbols
+    }
+}
+
+/// Collects a symbol for every declaration; the resolver's bindings then link the
+/// names that refer to it.
+struct Indexer {
+    symbols: Vec<Symbol>,
+    occurrences: Vec<Occurrence>,
+    // The symbol declared at each position.
+    declared: HashMap<Position, usize>,
+    // Blocks and function bodies enclosing the current statement.
+    depth: usize,
+    // Names of the functions enclosing the current statement.
+    functions: Vec<String>,
+    // Where each `{` and its matching `}` are.
+    braces: Vec<(Position, Position)>,
+}
+
+impl Indexer {
+    fn braces(tokens: &[Token]) -> Vec<(Position, Position)> {
+        let mut open = Vec::new();
+        let mut braces = Vec::new();
+        for token in tokens {
+            match token.type_ {
+                TokenType::LEFT_BRACE => open.push(Self::position(token)),
+                TokenType::RIGHT_BRACE => {
+                    if let Some(start) = open.pop() {
+                        braces.push((start, Self::position(token)));
+                    }
+                }
+                _ => {}
+            }
+        }
+        braces
+    }
+
+    fn position(token: &Token) -> Position {
+        (token.line, token.column)
+    }
+
+    fn add(&mut self, symbol: Symbol) -> usize {
+        self.symbols.push(symbol);
+        self.symbols.len() - 1
+    }
+
+    fn occur(&mut self, token: &Token, symbol: usize) {
+        self.occurrences.push(Occurrence {
+            span: Span::from(token),
+            symbol,
+        });
+    }
+
+    fn declare(&mut self, name: &Token, kind: SymbolKind, detail: String, doc: Option<String>) {
+        self.declare_in(name, kind, detail, doc, false);
+    }
+
+    /// Declares `name` in the current scope. `in_body` is for names that belong to the
+    /// block after them, like parameters, rather than the block they appear in.
+    fn declare_in(
+        &mut self,
+        name: &Token,
+        kind: SymbolKind,
+        detail: String,
+        doc: Option<String>,
+        in_body: bool,
+    ) {
+        let start = Self::position(name);
+        let visible = (self.depth > 0 || in_body).then(|| {
+            let block = if in_body {
+                self.braces
+                    .iter()
+                    .filter(|(open, _)| *open > start)
+                    .min_by_key(|(open, _)| *open)
+            } else {
+                self.braces
+                    .iter()
+                    .filter(|(open, close)| *open < start && start < *close)
+                    .max_by_key(|(open, _)| *open)
+            };
+            let end = block.map_or((i64::MAX, usize::MAX), |(_, close)| *close);
+            (start, end)
+        });
+
+        let symbol = self.add(Symbol {
+            name: name.lexeme.clone(),
+            kind,
+            declaration: Some(name.clone()),
+            detail,
+            doc,
+            container: self.functions.last().cloned(),
+            visible,
+        });
+        self.declared.insert(start, symbol);
+        self.occur(name, symbol);
+    }
+
+    fn statements(&mut self, statements: &[Stmt]) {
+        for statement in statements {
+            statement.accept(self);
+        }
+    }
+
+    fn block(&mut self, statements: &[Stmt]) {
+        self.depth += 1;
+        self.statements(statements);
+        self.depth -= 1;
+    }
+
+    fn signature(function: &stmt::Function) -> String {
+        let params: Vec<&str> = function.params.iter().map(|p| p.lexeme.as_str()).collect();
+        format!("fun {}({})", function.name.lexeme, params.join(", "))
+    }
+}
+
+impl stmt::Visitor<()> for Indexer {
+    fn visit_block_stmt(&mut self, stmt: &stmt::Block) {
+        self.block(&stmt.statements);
+    }
+
+    fn visit_break_stmt(&mut self, _stmt: &stmt::Break) {}
+
+    fn visit_class_stmt(&mut self, _stmt: &stmt::Class) {}
+
+    fn visit_continue_stmt(&mut self, _stmt: &stmt::Continue) {}
+
+    fn visit_expr_stmt(&mut self, _stmt: &stmt::Expression) {}
+
+    fn visit_func_stmt(&mut self, stmt: &Rc<stmt::Function>) {
+        self.declare(
+            &stmt.name,
+            SymbolKind::Function,
+            Self::signature(stmt),
+            stmt.doc.clone(),
+        );
+
+        self.functions.push(stmt.name.lexeme.clone());
+        for param in &stmt.params {
+            let detail = format!("param {}", param.lexeme);
+            self.declare_in(param, SymbolKind::Parameter, detail, None, true);
+        }
+        self.block(&stmt.body);
+        self.functions.pop();
+    }
+
+    fn visit_if_stmt(&mut self, stmt: &stmt::If) {
+        stmt.then_branch.accept(self);
+        if let Some(else_branch) = &stmt.else_branch {
+            else_branch.accept(self);
+        }
+    }
+
+    fn visit_import_stmt(&mut self, stmt: &stmt::Import) {
+        let detail = format!("import {} as {}", stmt.path.lexeme, stmt.name.lexeme);
+        self.declare(&stmt.name, SymbolKind::Import, detail, None);
+    }
+
+    fn visit_print_stmt(&mut self, _stmt: &stmt::Print) {}
+
+    fn visit_return_stmt(&mut self, _stmt: &stmt::Return) {}
+
+    fn visit_throw_stmt(&mut self, _stmt: &stmt::Throw) {}
+
+    fn visit_try_stmt(&mut self, stmt: &stmt::Try) {
+        self.block(&stmt.body);
+        if let Some((name, body)) = &stmt.catch {
+            let detail = format!("catch {}", name.lexeme);
+            self.declare_in(name, SymbolKind::Variable, detail, None, true);
+            self.block(body);
+        }
+        if let Some(body) = &stmt.finally {
+            self.block(body);
+        }
+    }
+
+    fn visit_var_stmt(&mut self, stmt: &stmt::Var) {
+        let detail = format!("var {}", stmt.name.lexeme);
+        self.declare(&stmt.name, SymbolKind::Variable, detail, stmt.doc.clone());
+    }
+
+    fn visit_while_stmt(&mut self, stmt: &stmt::While) {
+        stmt.body.accept(self);
+    }
+}
@@ -1,30 +1,83 @@
-use std::{error::Error, io::Write, process::exit};
-
-mod callable;
-mod env;
-mod error;
-mod expr;
-mod function;
-mod interpreter;
-mod object;
-mod parser;
-mod resolver;
-mod scanner;
-mod stmt;
-mod token;
-
-#[cfg(test)]
-mod tests;
-
-use interpreter::Interpreter;
-use parser::Parser;
-use resolver::Resolver;
-use scanner::Scanner;
-use token::Token;
+use std::{
+    error::Error, ffi::c_int, io, path::Path, process::exit, rc::Rc, sync::OnceLock, time::Duration,
+};
+
+use lox::{
+    ast_printer::{AstPrinter, AstStyle},
+    callable::Capabilities,
+    debugger::{self, dap, Quit},
+    formatter,
+    interpreter::{
+        coverage::Coverage, interrupt::InterruptHandle, profile::Profiler, sandbox::Limits,
+        Interpreter,
+    },
+    json::Json,
+    linter::{Linter, Rule},
+    parser::{self, Parser},
+    resolver::Resolver,
+    scanner::Scanner,
+    test_runner,
+    token::Token,
+};
+
+const USAGE: &str = "Usage: lox [options] <script>
+       lox fmt [--check] <file>...
+       lox lint [--enable <rule>]... [--disable <rule>]... <file>...
+       lox debug <script> | --dap
+       lox test [--fuel|--timeout|--max-* <n>]... <path>...
+
+Options:
+  --tokens[=json]          Print the scanned tokens instead of running the script
+  --dump-ast[=tree]        Print the parsed syntax tree instead of running the script
+  --profile[=folded]       Print call counts, timings and line hits to stderr; with
+                           =folded, print folded stacks for flamegraph tools instead
+  --coverage <file>        Write line and branch coverage to the file in lcov format
+                           and print a summary per file to stderr
+  --fuel <steps>           Stop after this many statements and expressions
+  --timeout <ms>           Stop after this much wall-clock time
+  --max-string <chars>     Cap the length of any string
+  --max-collection <len>   Cap the length of any list
+  --max-depth <calls>      Cap the call depth
+  --no-clock               Don't define clock()
+  --no-fs                  Don't define the file natives or allow import
+  --no-stdin               Don't define read_line()
+
+Commands:
+  fmt                      Rewrite files in the canonical style
+    --check                Only list files that aren't formatted; exit 1 if any
+  lint                     Report likely mistakes; exit 1 if there are any
+    --enable <rule>        Check only the given rules
+    --disable <rule>       Skip a rule. Rules: unused-variable, unused-parameter,
+                           shadowed-variable, unreachable-code, undeclared-assignment,
+                           mismatched-comparison, wrong-arity
+  debug                    Run a script under a line debugger; type 'help' at its prompt
+    --dap                  Serve the Debug Adapter Protocol over stdin and stdout
+  test                     Run every test_* function in the .lox files under each path,
+                           each in a fresh interpreter with the given limits; exit 1
+                           if any fail";
 
 struct Lox {
     had_error: bool,
     had_runtime_error: bool,
+    capabilities: Capabilities,
+    limits: Limits,
+    dump_ast: Option<AstStyle>,
+    dump_tokens: Option<TokenFormat>,
+    debug: bool,
+    profile: Option<ProfileFormat>,
+    coverage: Option<String>,
+}
+
+#[derive(Clone, Copy)]
+enum TokenFormat {
+    Text,
+    Json,
+}
+
+#[derive(Clone, Copy)]
+enum ProfileFormat {
+    Table,
+    Folded,
 }
 
 impl Lox {
@@ -32,26 +85,257 @@ impl Lox {
         Lox {
             had_error: false,
             had_runtime_error: false,
+            capabilities: Capabilities::default(),
+            limits: Limits::default(),
+            dump_ast: None,
+            dump_tokens: None,
+            debug: false,
+            profile: None,
+            coverage: None,
         }
     }
 
     pub fn exec(&mut self, args: Vec<String>) -> Result<(), Box<dyn Error>> {
-        let n = args.len();
-        if n < 2 || n > 2 {
-            println!("Usage: lox <script>");
-            exit(64);
-        } else {
-            let mut args = args.into_iter();
-            args.next();
-            self.run_file(args.next().unwrap())?;
+        let mut script = None;
+        let mut args = args.into_iter().skip(1).peekable();
+
+        if args.next_if_eq("fmt").is_some() {
+            return Self::fmt(args.collect());
+        }
+        if args.next_if_eq("lint").is_some() {
+            return Self::lint(args);
+        }
+        if args.next_if_eq("test").is_some() {
+            return self.test(args);
+        }
+        if args.next_if_eq("debug").is_some() {
+            return match (args.next(), args.next()) {
+                (Some(arg), None) if arg == "--dap" => dap::serve(io::stdin().lock(), io::stdout()),
+                (Some(script), None) if !script.starts_with("--") => {
+                    self.debug = true;
+                    self.run_file(script)
+                }
+                _ => Self::usage(),
+            };
+        }
+
+        while let Some(arg) = args.next() {
+            if self.limit_flag(&arg, &mut args) {
+                continue;
+            }
+            match arg.as_str() {
+                "--no-clock" => self.capabilities.clock = false,
+                "--no-fs" => self.capabilities.filesystem = false,
+                "--no-stdin" => self.capabilities.stdin = false,
+                "--tokens" => self.dump_tokens = Some(TokenFormat::Text),
+                "--tokens=json" => self.dump_tokens = Some(TokenFormat::Json),
+                "--dump-ast" => self.dump_ast = Some(AstStyle::SExpr),
+                "--dump-ast=tree" => self.dump_ast = Some(AstStyle::Tree),
+                "--profile" => self.profile = Some(ProfileFormat::Table),
+                "--profile=folded" => self.profile = Some(ProfileFormat::Folded),
+                "--coverage" => self.coverage = Some(Self::flag_value(args.next())),
+                _ if script.is_none() && !arg.starts_with("--") => script = Some(arg),
+                _ => Self::usage(),
+            }
+        }
+
+        match script {
+            Some(script) => self.run_file(script),
+            None => Self::usage(),
+        }
+    }
+
+    /// Sets the limit `arg` names from the value after it, if it's a limit option.
+    fn limit_flag(&mut self, arg: &str, args: &mut impl Iterator<Item = String>) -> bool {
+        match arg {
+            "--fuel" => self.limits.fuel = Some(Self::flag_value(args.next())),
+            "--timeout" => {
+                self.limits.deadline = Some(Duration::from_millis(Self::flag_value(args.next())))
+            }
+            "--max-string" => self.limits.max_string_len = Some(Self::flag_value(args.next())),
+            "--max-collection" => {
+                self.limits.max_collection_len = Some(Self::flag_value(args.next()))
+            }
+            "--max-depth" => self.limits.max_call_depth = Some(Self::flag_value(args.next())),
+            _ => return false,
+        }
+        true
+    }
+
+    /// `lox fmt`: formats each file in place, or with `--check` only reports those
+    /// that would change.
+    fn fmt(args: Vec<String>) -> Result<(), Box<dyn Error>> {
+        let check = args.iter().any(|arg| arg == "--check");
+        let paths: Vec<&String> = args.iter().filter(|arg| *arg != "--check").collect();
+        if paths.is_empty() || paths.iter().any(|path| path.starts_with("--")) {
+            Self::usage();
+        }
+
+        let mut unformatted = false;
+        let mut had_error = false;
+        for path in paths {
+            let source = std::fs::read_to_string(path)?;
+            let formatted = match formatter::format(&source) {
+                Ok(formatted) => formatted,
+                Err(err) => {
+                    eprintln!("{}: {}", path, err);
+                    had_error = true;
+                    continue;
+                }
+            };
+
+            if formatted == source {
+                continue;
+            }
+            if check {
+                println!("{}", path);
+                unformatted = true;
+            } else {
+                std::fs::write(path, formatted)?;
+            }
+        }
+
+        if had_error {
+            exit(65);
+        } else if unformatted {
+            exit(1);
+        }
+        Ok(())
+    }
+
+    fn lint(mut args: impl Iterator<Item = String>) -> Result<(), Box<dyn Error>> {
+        let mut enabled: Vec<Rule> = Vec::new();
+        let mut disabled: Vec<Rule> = Vec::new();
+        let mut paths: Vec<String> = Vec::new();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--enable" => enabled.push(Self::rule(args.next())),
+                "--disable" => disabled.push(Self::rule(args.next())),
+                _ if !arg.starts_with("--") => paths.push(arg),
+                _ => Self::usage(),
+            }
+        }
+        if paths.is_empty() {
+            Self::usage();
+        }
+        if enabled.is_empty() {
+            enabled = Rule::ALL.to_vec();
+        }
+        enabled.retain(|rule| !disabled.contains(rule));
+
+        let mut warned = false;
+        let mut had_error = false;
+        for path in paths {
+            let source = std::fs::read_to_string(&path)?;
+            let statements = match Scanner::new(source).scan_tokens() {
+                Ok(tokens) => Parser::new(tokens).parse(),
+                Err(err) => Err(err),
+            };
+            let statements = match statements {
+                Ok(statements) => statements,
+                Err(err) => {
+                    eprintln!("{}: {}", path, err);
+                    had_error = true;
+                    continue;
+                }
+            };
+
+            let mut interpreter = Interpreter::new();
+            let mut resolver = Resolver::new(&mut interpreter);
+            if let Err(err) = resolver.resolve(&statements) {
+                eprintln!("{}: {}", path, err);
+                had_error = true;
+            }
+            let bindings = resolver.bindings();
+            for warning in Linter::new(enabled.clone()).lint(&statements, &bindings) {
+                println!("{}: {}", path, warning);
+                warned = true;
+            }
+        }
+
+        if had_error {
+            exit(65);
+        } else if warned {
+            exit(1);
+        }
+        Ok(())
+    }
+
+    /// `lox test`: runs the `test_*` functions of every `.lox` file under each path in
+    /// `args`, with the limits the options among them set.
+    fn test(&mut self, mut args: impl Iterator<Item = String>) -> Result<(), Box<dyn Error>> {
+        let mut paths = Vec::new();
+        while let Some(arg) = args.next() {
+            if self.limit_flag(&arg, &mut args) {
+                continue;
+            }
+            if arg.starts_with("--") {
+                Self::usage();
+            }
+            paths.push(arg);
+        }
+        if paths.is_empty() {
+            Self::usage();
+        }
+
+        let (mut passed, mut failed) = (0, 0);
+        for path in paths {
+            for file in test_runner::discover(Path::new(&path))? {
+                let results = match test_runner::run_file(&file, &self.limits) {
+                    Ok(results) => results,
+                    Err(err) => {
+                        println!("{}: {}", file.display(), err);
+                        failed += 1;
+                        continue;
+                    }
+                };
+
+                for result in results {
+                    match result.outcome {
+                        Ok(()) => {
+                            println!("test {}::{} ... ok", file.display(), result.name);
+                            passed += 1;
+                        }
+                        Err(err) => {
+                            println!("test {}::{} ... FAILED", file.display(), result.name);
+                            println!("    {}", err);
+                            failed += 1;
+                        }
+                    }
+                }
+            }
         }
 
+        println!("\n{} passed; {} failed", passed, failed);
+        if failed > 0 {
+            exit(1);
+        }
         Ok(())
     }
 
+    fn flag_value<T: std::str::FromStr>(value: Option<String>) -> T {
+        value
+            .and_then(|value| value.parse().ok())
+            .unwrap_or_else(|| Self::usage())
+    }
+
+    /// The rule a `--enable` or `--disable` names; an unknown name lists the rules.
+    fn rule(value: Option<String>) -> Rule {
+        let value = value.unwrap_or_else(|| Self::usage());
+        value.parse().unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            exit(64);
+        })
+    }
+
+    fn usage() -> ! {
+        println!("{}", USAGE);
+        exit(64);
+    }
+
     fn run_file(&mut self, path: String) -> Result<(), Box<dyn Error>> {
-        let contents = std::fs::read_to_string(path)?;
-        self.run(contents)?;
+        let contents = std::fs::read_to_string(&path)?;
+        self.run(contents, Path::new(&path))?;
         if self.had_error {
             exit(65);
         } else if self.had_runtime_error {
@@ -60,20 +344,98 @@ impl Lox {
         Ok(())
     }
 
-    fn run(&mut self, source: String) -> Result<(), Box<dyn Error>> {
-        let mut scanner = Scanner::new(source);
-        let tokens: Vec<Token> = scanner.scan_tokens();
+    fn run(&mut self, source: String, path: &Path) -> Result<(), Box<dyn Error>> {
+        let mut scanner = Scanner::new(source.clone());
+        let tokens: Vec<Token> = scanner.scan_tokens()?;
+
+        match self.dump_tokens {
+            Some(TokenFormat::Text) => {
+                tokens.iter().for_each(|token| println!("{}", token));
+                return Ok(());
+            }
+            Some(TokenFormat::Json) => {
+                let tokens = tokens.iter().map(Token::to_json).collect();
+                println!("{}", Json::Array(tokens));
+                return Ok(());
+            }
+            None => {}
+        }
 
         let mut parser_: Parser = parser::Parser::new(tokens);
-        let mut statements = parser_.parse()?;
+        let statements = parser_.parse()?;
 
-        let mut interpreter = Interpreter::new();
+        if let Some(style) = self.dump_ast {
+            print!("{}", AstPrinter::print(&statements, style));
+            return Ok(());
+        }
+
+        let mut interpreter = Interpreter::new()
+            .with_capabilities(self.capabilities)
+            .with_limits(self.limits.clone());
+        if self.debug {
+            let terminal = debugger::Terminal::new(&source, io::stdin().lock(), io::stdout());
+            interpreter = interpreter.with_debugger(Box::new(terminal));
+        }
+        let profiler = self.profile.map(|_| Rc::new(Profiler::new()));
+        if let Some(profiler) = &profiler {
+            interpreter = interpreter.with_profiler(profiler.clone());
+        }
+        let coverage = self.coverage.as_ref().map(|_| Rc::new(Coverage::new()));
+        if let Some(coverage) = &coverage {
+            interpreter = interpreter.with_coverage(coverage.clone());
+        }
+        interpreter.set_script_path(path)?;
         let mut resolver: Resolver<'_> = Resolver::new(&mut interpreter);
-        resolver.resolve(&mut statements)?;
+        resolver.resolve(&statements)?;
+        if let Some(coverage) = &coverage {
+            coverage.register(interpreter.script_path.as_deref(), &statements);
+        }
 
-        interpreter.interpret(statements)?;
+        on_ctrl_c(interpreter.interrupt_handle());
+        let result = interpreter.interpret(&statements);
+        // A profile of a script that failed still shows where the time went.
+        if let (Some(profiler), Some(format)) = (profiler, self.profile) {
+            profiler.finish();
+            match format {
+                ProfileFormat::Table => eprint!("{}", profiler.report()),
+                ProfileFormat::Folded => eprint!("{}", profiler.folded()),
+            }
+        }
+        if let (Some(coverage), Some(out)) = (coverage, &self.coverage) {
+            std::fs::write(out, coverage.lcov())?;
+            eprint!("{}", coverage.summary());
+        }
+        match result {
+            Err(err) if err.is::<Quit>() => Ok(()),
+            result => result.map(|_| ()),
+        }
+    }
+}
 
-        Ok(())
+// The C runtime's `signal`, which std links on every platform it supports. Declared
+// by hand to avoid a dependency; `SIGINT` is 2 on Unix and Windows alike, and a null
+// handler is `SIG_DFL`, the default action.
+extern "C" {
+    fn signal(signum: c_int, handler: Option<extern "C" fn(c_int)>) -> usize;
+}
+
+const SIGINT: c_int = 2;
+
+static INTERRUPT: OnceLock<InterruptHandle> = OnceLock::new();
+
+/// Makes the first Ctrl-C stop the script with an `Interrupted` error, so that it
+/// unwinds and its profile and coverage are still reported. A second Ctrl-C kills
+/// the process as usual, e.g. while the script is blocked reading stdin.
+fn on_ctrl_c(handle: InterruptHandle) {
+    extern "C" fn handler(_: c_int) {
+        if let Some(handle) = INTERRUPT.get() {
+            handle.interrupt();
+        }
+        unsafe { signal(SIGINT, None) };
+    }
+
+    if INTERRUPT.set(handle).is_ok() {
+        unsafe { signal(SIGINT, Some(handler)) };
     }
 }
 
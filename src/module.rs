@@ -0,0 +1,20 @@
+use std::{cell::RefCell, collections::HashMap, path::PathBuf, rc::Rc};
+
+use crate::env::Environment;
+
+/// A loaded `.lox` file. Its top-level bindings live in `env` and are read through the
+/// namespace object bound by `import "path" as name;`.
+#[derive(Debug)]
+pub struct Module {
+    pub name: String,
+    pub path: PathBuf,
+    pub env: Rc<RefCell<Environment>>,
+}
+
+/// Every module imported by a program, keyed by canonical path so that each file runs once.
+#[derive(Debug, Default)]
+pub struct Modules {
+    pub loaded: HashMap<PathBuf, Rc<Module>>,
+    // Files whose top-level code is running, outermost first; used to report import cycles.
+    pub loading: Vec<PathBuf>,
+}
@@ -1,63 +1,144 @@
 use std::{
     cell::RefCell,
+    cmp::Ordering,
     error::Error,
     fmt,
-    ops::{Add, Div, Mul, Sub},
+    ops::{Add, BitAnd, BitOr, BitXor, Div, Mul, Rem, Shl, Shr, Sub},
 };
 
 use std::rc::Rc;
 
 use crate::{callable::Callable, interpreter::Interpreter};
-use crate::{function, token::Token};
+use crate::{function, module::Module, token::Token};
+
+pub type NativeFn = fn(Vec<Object>, &Token) -> Result<Object, Box<dyn Error>>;
+pub type CallbackFn = fn(Interpreter, Vec<Object>, &Token) -> Result<Object, Box<dyn Error>>;
+
+#[derive(Debug, Clone, Copy)]
+pub enum Native {
+    Fn(NativeFn),
+    // Natives that call back into the script, like `assert_throws`.
+    Callback(CallbackFn),
+}
 
 #[derive(Debug, Clone)]
 pub enum Object {
     String(String),
     Number(f64),
+    Int(i64),
     Boolean(bool),
     Nil,
-    Function(
-        Option<Rc<RefCell<function::Function>>>,
-        Option<fn(Vec<Object>) -> Result<Object, Box<dyn Error>>>,
-    ),
+    Function(Option<Rc<RefCell<function::Function>>>, Option<Native>),
+    Module(Rc<Module>),
+    List(Rc<RefCell<Vec<Object>>>),
+    Error(Rc<ErrorObject>),
+}
+
+/// A runtime error caught by `catch`. Scripts read its `message` and `line`.
+#[derive(Debug)]
+pub struct ErrorObject {
+    pub message: String,
+    pub line: i64,
+}
+
+impl fmt::Display for ErrorObject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "RuntimeError [line {}] : {}", self.line, self.message)
+    }
 }
 
 impl Object {
     pub fn is_nil(&self) -> bool {
         self == &Self::Nil
     }
-}
 
-impl Into<f64> for Object {
-    fn into(self) -> f64 {
+    pub fn is_number(&self) -> bool {
+        matches!(self, Object::Number(_) | Object::Int(_))
+    }
+
+    pub fn type_name(&self) -> &'static str {
         match self {
+            Object::String(_) => "string",
+            Object::Number(_) => "float",
+            Object::Int(_) => "int",
+            Object::Boolean(_) => "boolean",
+            Object::Nil => "nil",
+            Object::Function(..) => "function",
+            Object::Module(_) => "module",
+            Object::List(_) => "list",
+            Object::Error(_) => "error",
+        }
+    }
+
+    /// Widens an `Int` to `f64` so that mixed arithmetic promotes to floating point.
+    pub fn as_float(&self) -> Option<f64> {
+        match self {
+            Object::Number(n) => Some(*n),
+            Object::Int(i) => Some(*i as f64),
+            _ => None,
+        }
+    }
+
+    /// `Int ** Int` stays an `Int` for non-negative exponents; anything else is a float power.
+    pub fn pow(self, rhs: Self) -> Object {
+        match (self, rhs) {
+            (Object::Int(base), Object::Int(exp)) if exp >= 0 => u32::try_from(exp)
+                .ok()
+                .and_then(|exp| base.checked_pow(exp))
+                .map_or(Object::Nil, Object::Int),
+            (lhs, rhs) => match (lhs.as_float(), rhs.as_float()) {
+                (Some(n1), Some(n2)) => Object::Number(n1.powf(n2)),
+                _ => Object::Nil,
+            },
+        }
+    }
+
+    /// Truncating integer division; `Int div Int` stays an `Int`.
+    pub fn int_div(self, rhs: Self) -> Object {
+        match (self, rhs) {
+            (Object::Int(i1), Object::Int(i2)) => {
+                i1.checked_div(i2).map_or(Object::Nil, Object::Int)
+            }
+            (lhs, rhs) => match (lhs.as_float(), rhs.as_float()) {
+                (Some(_), Some(0.0)) => Object::Nil,
+                (Some(n1), Some(n2)) => Object::Number((n1 / n2).trunc()),
+                _ => Object::Nil,
+            },
+        }
+    }
+}
+
+impl From<Object> for f64 {
+    fn from(object: Object) -> f64 {
+        match object {
             Object::Number(n) => n,
+            Object::Int(i) => i as f64,
             _ => 0.0,
         }
     }
 }
 
-impl Into<bool> for Object {
-    fn into(self) -> bool {
-        match self {
+impl From<Object> for bool {
+    fn from(object: Object) -> bool {
+        match object {
             Object::Boolean(b) => b,
             _ => false,
         }
     }
 }
 
-impl Into<String> for Object {
-    fn into(self) -> String {
-        match self {
+impl From<Object> for String {
+    fn from(object: Object) -> String {
+        match object {
             Object::String(s) => s,
             _ => String::from(""),
         }
     }
 }
 
-impl Into<String> for &Object {
-    fn into(self) -> String {
-        match self {
+impl From<&Object> for String {
+    fn from(object: &Object) -> String {
+        match object {
             Object::String(s) => s.to_string(),
             _ => String::from(""),
         }
@@ -68,10 +149,20 @@ impl fmt::Display for Object {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Object::String(s) => write!(f, "{}", s),
+            // A whole float keeps its fraction so that it doesn't read as an `Int`.
+            Object::Number(n) if n.is_finite() && n.fract() == 0.0 => write!(f, "{:.1}", n),
             Object::Number(n) => write!(f, "{}", n),
+            Object::Int(i) => write!(f, "{}", i),
             Object::Boolean(b) => write!(f, "{}", b),
             Object::Nil => write!(f, "nil"),
-            _ => Ok(()),
+            Object::Function(Some(function), _) => write!(f, "{}", function.borrow().to_string()),
+            Object::Function(None, _) => write!(f, "{}", Callable::to_string(self)),
+            Object::Module(module) => write!(f, "<module {}>", module.name),
+            Object::Error(error) => write!(f, "{}", error),
+            Object::List(items) => {
+                let items: Vec<String> = items.borrow().iter().map(|i| format!("{}", i)).collect();
+                write!(f, "[{}]", items.join(", "))
+            }
         }
     }
 }
@@ -81,19 +172,48 @@ impl PartialEq for Object {
         match (self, other) {
             (Object::String(s1), Object::String(s2)) => s1 == s2,
             (Object::Number(n1), Object::Number(n2)) => n1 == n2,
+            (Object::Int(i1), Object::Int(i2)) => i1 == i2,
+            (Object::Int(i), Object::Number(n)) | (Object::Number(n), Object::Int(i)) => {
+                compare_int_float(*i, *n) == Some(Ordering::Equal)
+            }
             (Object::Boolean(b1), Object::Boolean(b2)) => b1 == b2,
             (Object::Nil, Object::Nil) => true,
+            (Object::Module(m1), Object::Module(m2)) => Rc::ptr_eq(m1, m2),
+            (Object::List(l1), Object::List(l2)) => *l1.borrow() == *l2.borrow(),
+            (Object::Error(e1), Object::Error(e2)) => Rc::ptr_eq(e1, e2),
             _ => false,
         }
     }
 }
 
+/// Compares an `Int` with a float exactly. Converting the `Int` to `f64` would round
+/// above 2^53, making e.g. `9007199254740993 == 9007199254740992.0` true.
+fn compare_int_float(i: i64, n: f64) -> Option<Ordering> {
+    // 2^63 is exact as an f64; every float in [-2^63, 2^63) truncates to an i64.
+    const LIMIT: f64 = 9_223_372_036_854_775_808.0;
+    if n.is_nan() {
+        None
+    } else if n >= LIMIT {
+        Some(Ordering::Less)
+    } else if n < -LIMIT {
+        Some(Ordering::Greater)
+    } else {
+        let whole = n.trunc();
+        Some(i.cmp(&(whole as i64)).then(whole.partial_cmp(&n)?))
+    }
+}
+
 impl PartialOrd for Object {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
         match (self, other) {
-            (Object::Number(n1), Object::Number(n2)) => n1.partial_cmp(n2),
+            (Object::Int(i1), Object::Int(i2)) => i1.partial_cmp(i2),
+            (Object::Int(i), Object::Number(n)) => compare_int_float(*i, *n),
+            (Object::Number(n), Object::Int(i)) => compare_int_float(*i, *n).map(Ordering::reverse),
             (Object::Boolean(b1), Object::Boolean(b2)) => b1.partial_cmp(b2),
-            _ => None,
+            _ => match (self.as_float(), other.as_float()) {
+                (Some(n1), Some(n2)) => n1.partial_cmp(&n2),
+                _ => None,
+            },
         }
     }
 }
@@ -103,21 +223,20 @@ impl Add for Object {
 
     fn add(self, rhs: Self) -> Self::Output {
         match (self, rhs) {
-            (Object::Number(n1), Object::String(n2)) => {
-                Object::String(n1.to_string() + n2.as_ref())
-            }
-            (Object::String(n1), Object::Number(n2)) => {
-                Object::String(n1 + n2.to_string().as_ref())
+            (Object::String(s1), Object::String(s2)) => Object::String(format!("{}{}", s1, s2)),
+            (Object::String(s), v @ (Object::Number(_) | Object::Int(_) | Object::Boolean(_))) => {
+                Object::String(format!("{}{}", s, v))
             }
-            (Object::Boolean(n1), Object::String(n2)) => {
-                Object::String(n1.to_string() + n2.as_ref())
+            (v @ (Object::Number(_) | Object::Int(_) | Object::Boolean(_)), Object::String(s)) => {
+                Object::String(format!("{}{}", v, s))
             }
-            (Object::String(n1), Object::Boolean(n2)) => {
-                Object::String(n1 + n2.to_string().as_ref())
+            (Object::Int(i1), Object::Int(i2)) => {
+                i1.checked_add(i2).map_or(Object::Nil, Object::Int)
             }
-            (Object::Number(n1), Object::Number(n2)) => Object::Number(n1 + n2),
-            (Object::String(s1), Object::String(s2)) => Object::String(format!("{}{}", s1, s2)),
-            _ => Object::Nil,
+            (lhs, rhs) => match (lhs.as_float(), rhs.as_float()) {
+                (Some(n1), Some(n2)) => Object::Number(n1 + n2),
+                _ => Object::Nil,
+            },
         }
     }
 }
@@ -127,8 +246,13 @@ impl Sub for Object {
 
     fn sub(self, rhs: Self) -> Self::Output {
         match (self, rhs) {
-            (Object::Number(n1), Object::Number(n2)) => Object::Number(n1 - n2),
-            _ => Object::Nil,
+            (Object::Int(i1), Object::Int(i2)) => {
+                i1.checked_sub(i2).map_or(Object::Nil, Object::Int)
+            }
+            (lhs, rhs) => match (lhs.as_float(), rhs.as_float()) {
+                (Some(n1), Some(n2)) => Object::Number(n1 - n2),
+                _ => Object::Nil,
+            },
         }
     }
 }
@@ -137,9 +261,9 @@ impl Div for Object {
     type Output = Object;
 
     fn div(self, rhs: Self) -> Self::Output {
-        match (self, rhs) {
-            (Object::Number(_), Object::Number(0.0)) => Object::Nil,
-            (Object::Number(n1), Object::Number(n2)) => Object::Number(n1 / n2),
+        match (self.as_float(), rhs.as_float()) {
+            (Some(_), Some(0.0)) => Object::Nil,
+            (Some(n1), Some(n2)) => Object::Number(n1 / n2),
             _ => Object::Nil,
         }
     }
@@ -150,7 +274,90 @@ impl Mul for Object {
 
     fn mul(self, rhs: Self) -> Self::Output {
         match (self, rhs) {
-            (Object::Number(n1), Object::Number(n2)) => Object::Number(n1 * n2),
+            (Object::Int(i1), Object::Int(i2)) => {
+                i1.checked_mul(i2).map_or(Object::Nil, Object::Int)
+            }
+            (lhs, rhs) => match (lhs.as_float(), rhs.as_float()) {
+                (Some(n1), Some(n2)) => Object::Number(n1 * n2),
+                _ => Object::Nil,
+            },
+        }
+    }
+}
+
+impl Rem for Object {
+    type Output = Object;
+
+    fn rem(self, rhs: Self) -> Self::Output {
+        match (self, rhs) {
+            (Object::Int(i1), Object::Int(i2)) => {
+                i1.checked_rem(i2).map_or(Object::Nil, Object::Int)
+            }
+            (lhs, rhs) => match (lhs.as_float(), rhs.as_float()) {
+                (Some(_), Some(0.0)) => Object::Nil,
+                (Some(n1), Some(n2)) => Object::Number(n1 % n2),
+                _ => Object::Nil,
+            },
+        }
+    }
+}
+
+impl BitAnd for Object {
+    type Output = Object;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        match (self, rhs) {
+            (Object::Int(i1), Object::Int(i2)) => Object::Int(i1 & i2),
+            _ => Object::Nil,
+        }
+    }
+}
+
+impl BitOr for Object {
+    type Output = Object;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        match (self, rhs) {
+            (Object::Int(i1), Object::Int(i2)) => Object::Int(i1 | i2),
+            _ => Object::Nil,
+        }
+    }
+}
+
+impl BitXor for Object {
+    type Output = Object;
+
+    fn bitxor(self, rhs: Self) -> Self::Output {
+        match (self, rhs) {
+            (Object::Int(i1), Object::Int(i2)) => Object::Int(i1 ^ i2),
+            _ => Object::Nil,
+        }
+    }
+}
+
+impl Shl for Object {
+    type Output = Object;
+
+    fn shl(self, rhs: Self) -> Self::Output {
+        match (self, rhs) {
+            (Object::Int(i1), Object::Int(i2)) => u32::try_from(i2)
+                .ok()
+                .and_then(|shift| i1.checked_shl(shift))
+                .map_or(Object::Nil, Object::Int),
+            _ => Object::Nil,
+        }
+    }
+}
+
+impl Shr for Object {
+    type Output = Object;
+
+    fn shr(self, rhs: Self) -> Self::Output {
+        match (self, rhs) {
+            (Object::Int(i1), Object::Int(i2)) => u32::try_from(i2)
+                .ok()
+                .and_then(|shift| i1.checked_shr(shift))
+                .map_or(Object::Nil, Object::Int),
             _ => Object::Nil,
         }
     }
@@ -164,24 +371,27 @@ impl Callable for Object {
         paren: Token,
     ) -> Result<Object, Box<dyn Error>> {
         match self {
-            Object::Function(fun, fn_ptr) => {
-                if let Some(function) = fn_ptr {
-                    return function(arguments);
+            Object::Function(fun, native) => {
+                match native {
+                    Some(Native::Fn(function)) => return function(arguments, &paren),
+                    Some(Native::Callback(function)) => {
+                        return function(interpreter, arguments, &paren)
+                    }
+                    None => {}
                 }
 
                 let mut retunred_v: Object = Object::Nil;
 
                 if let Some(func) = fun {
-                    let (expected_len, found_len) =
-                        (func.borrow_mut().declaration.params.len(), arguments.len());
+                    let (expected_len, found_len) = (func.borrow().arity(), arguments.len());
                     if expected_len != found_len {
                         return Err(interpreter.error(
                             &format!("Expected {} arguments but got {}.", expected_len, found_len),
-                            &func.borrow_mut().declaration.name,
+                            &func.borrow().declaration.name,
                         ));
                     }
 
-                    retunred_v = func.borrow_mut().call(interpreter, arguments, paren)?;
+                    retunred_v = func.borrow().call(interpreter, arguments, paren)?;
                 }
 
                 Ok(retunred_v)
@@ -192,7 +402,7 @@ impl Callable for Object {
 
     fn arity(&self) -> usize {
         match self {
-            Object::Function(fun, _) => fun.as_ref().unwrap().borrow_mut().declaration.params.len(),
+            Object::Function(fun, _) => fun.as_ref().unwrap().borrow().declaration.params.len(),
             _ => 0,
         }
     }
@@ -14,42 +14,98 @@ pub struct Parser {
 
 impl Parser {
     pub fn new(tokens: Vec<Token>) -> Self {
-        Self { tokens, current: 0 }
-    }
-
-    pub fn expression(&mut self) -> Result<Box<dyn Expr>, Box<dyn Error>> {
-        return expression::assignment(self);
+        Self {
+            tokens: Self::attach_doc_comments(tokens),
+            current: 0,
+        }
     }
 
-    pub fn statement(&mut self) -> Result<Box<dyn Stmt>, Box<dyn Error>> {
+    /// Keeps only the doc comments in declaration position: directly before a `fun`,
+    /// `class` or `var` that starts a statement. Any other doc comment, such as one
+    /// inside a `for (` header, is treated like an ordinary comment.
+    fn attach_doc_comments(tokens: Vec<Token>) -> Vec<Token> {
+        let significant = |token: &&Token| token.type_ != TokenType::DOC_COMMENT;
+        let keep: Vec<bool> = (0..tokens.len())
+            .map(|i| {
+                if tokens[i].type_ != TokenType::DOC_COMMENT {
+                    return true;
+                }
+                let next = tokens[i + 1..].iter().find(significant);
+                let previous = tokens[..i].iter().rev().find(significant);
+                matches!(
+                    next.map(|token| &token.type_),
+                    Some(TokenType::FUN | TokenType::CLASS | TokenType::VAR)
+                ) && matches!(
+                    previous.map(|token| &token.type_),
+                    None | Some(
+                        TokenType::SEMICOLON | TokenType::LEFT_BRACE | TokenType::RIGHT_BRACE
+                    )
+                )
+            })
+            .collect();
+        tokens
+            .into_iter()
+            .zip(keep)
+            .filter_map(|(token, keep)| keep.then_some(token))
+            .collect()
+    }
+
+    pub fn expression(&mut self) -> Result<Expr, Box<dyn Error>> {
+        expression::assignment(self)
+    }
+
+    pub fn statement(&mut self) -> Result<Stmt, Box<dyn Error>> {
         if self.match_(vec![TokenType::PRINT]) {
             return statement::print(self);
         } else if self.match_(vec![TokenType::LEFT_BRACE]) {
-            return Ok(Box::new(stmt::Block::new(statement::block(self)?)));
+            return Ok(Stmt::Block(stmt::Block::new(statement::block(self)?)));
         } else if self.match_(vec![TokenType::IF]) {
             return statement::if_statement(self);
         } else if self.match_(vec![TokenType::WHILE]) {
             return statement::while_statement(self);
         } else if self.match_(vec![TokenType::FOR]) {
             return statement::for_statement(self);
-        } else if self.match_(vec![TokenType::FUN]) {
-            return statement::function_definition(self, "function");
         } else if self.match_(vec![TokenType::RETURN]) {
             return statement::return_statement(self);
+        } else if self.match_(vec![TokenType::BREAK, TokenType::CONTINUE]) {
+            return statement::loop_jump(self);
+        } else if self.match_(vec![TokenType::THROW]) {
+            return statement::throw_statement(self);
+        } else if self.match_(vec![TokenType::TRY]) {
+            return statement::try_statement(self);
         }
 
         statement::expression(self)
     }
 
-    pub fn declaration(&mut self) -> Result<Box<dyn Stmt>, Box<dyn Error>> {
+    pub fn declaration(&mut self) -> Result<Stmt, Box<dyn Error>> {
+        let doc = self.doc_comment();
+
         if self.match_(vec![TokenType::VAR]) {
-            return statement::var_declaration(self);
+            return statement::var_declaration(self, doc);
+        } else if self.match_(vec![TokenType::FUN]) {
+            return statement::function_definition(self, "function", doc);
+        } else if self.match_(vec![TokenType::IMPORT]) {
+            return statement::import_declaration(self);
         }
 
         self.statement()
     }
 
-    pub fn parse(&mut self) -> Result<Vec<Box<dyn Stmt>>, Box<dyn Error>> {
+    fn doc_comment(&mut self) -> Option<String> {
+        let mut lines: Vec<String> = Vec::new();
+        while self.match_(vec![TokenType::DOC_COMMENT]) {
+            lines.push(String::from(*self.previous().literal.unwrap()));
+        }
+
+        if lines.is_empty() {
+            None
+        } else {
+            Some(lines.join("\n"))
+        }
+    }
+
+    pub fn parse(&mut self) -> Result<Vec<Stmt>, Box<dyn Error>> {
         let mut statements = Vec::new();
         while !self.is_at_end() {
             statements.push(self.declaration()?);
@@ -81,15 +137,15 @@ impl Parser {
         if !self.is_at_end() {
             self.current += 1;
         }
-        return self.previous();
+        self.previous()
     }
 
     fn is_at_end(&self) -> bool {
-        return self.peek().type_ == TokenType::EOF;
+        self.peek().type_ == TokenType::EOF
     }
 
     fn peek(&self) -> &Token {
-        return self.tokens[self.current as usize].borrow();
+        self.tokens[self.current as usize].borrow()
     }
 
     fn previous(&self) -> Token {
@@ -143,141 +199,367 @@ mod expression {
     use crate::token::{token_type::TokenType, Token};
     use std::error::Error;
 
-    pub fn assignment(parser: &mut Parser) -> Result<Box<dyn Expr>, Box<dyn Error>> {
-        let exp = or(parser)?;
+    pub fn assignment(parser: &mut Parser) -> Result<Expr, Box<dyn Error>> {
+        use TokenType::*;
+
+        let exp = conditional(parser)?;
 
-        if parser.match_(vec![TokenType::EQUAL]) {
+        if parser.match_(vec![EQUAL]) {
             let equals: Token = parser.previous();
-            let value: Box<dyn Expr> = assignment(parser)?;
+            let value: Expr = assignment(parser)?;
 
-            if let Some(expr::Variable { name }) = exp.as_any().downcast_ref::<expr::Variable>() {
-                return Ok(Box::new(expr::Assign::new(name.clone(), value)));
-            }
+            return match exp {
+                Expr::Variable(expr::Variable { name, .. }) => {
+                    Ok(Expr::Assign(expr::Assign::new(name, value)))
+                }
+                Expr::Get(expr::Get { object, name }) => {
+                    Ok(Expr::Set(expr::Set::new(object, name, None, value)))
+                }
+                _ => Err(parser.error(&equals, "Invalid assignment target.")),
+            };
+        }
 
-            return Err(parser.error(&equals, "Invalid assignment target."));
+        if parser.match_(vec![
+            PLUS_EQUAL,
+            MINUS_EQUAL,
+            STAR_EQUAL,
+            SLASH_EQUAL,
+            PERCENT_EQUAL,
+        ]) {
+            let operator: Token = parser.previous();
+            let value: Expr = assignment(parser)?;
+            return compound_assignment(parser, exp, operator, value);
         }
 
         Ok(exp)
     }
 
-    pub fn equality(parser: &mut Parser) -> Result<Box<dyn Expr>, Box<dyn Error>> {
-        let mut expression: Box<dyn Expr> = comparison(parser)?;
+    /// Desugars `target op= value` into `target = target op value`. A property target
+    /// keeps the operator on the `Set` instead, so its object is evaluated only once.
+    fn compound_assignment(
+        parser: &mut Parser,
+        target: Expr,
+        operator: Token,
+        value: Expr,
+    ) -> Result<Expr, Box<dyn Error>> {
+        use TokenType::*;
+
+        let (type_, lexeme) = match operator.type_ {
+            PLUS_EQUAL | PLUS_PLUS => (PLUS, "+"),
+            MINUS_EQUAL | MINUS_MINUS => (MINUS, "-"),
+            STAR_EQUAL => (STAR, "*"),
+            SLASH_EQUAL => (SLASH, "/"),
+            _ => (PERCENT, "%"),
+        };
+        let binary_operator = Token::new(type_, lexeme.to_string(), None, operator.line);
+
+        match target {
+            Expr::Variable(expr::Variable { name, .. }) => {
+                let current = Expr::Variable(expr::Variable::new(name.clone()));
+                let value = Expr::Binary(expr::Binary::new(current, binary_operator, value));
+                Ok(Expr::Assign(expr::Assign::new(name, value)))
+            }
+            Expr::Get(expr::Get { object, name }) => Ok(Expr::Set(expr::Set::new(
+                object,
+                name,
+                Some(binary_operator),
+                value,
+            ))),
+            _ => Err(parser.error(&operator, "Invalid assignment target.")),
+        }
+    }
+
+    pub fn equality(parser: &mut Parser) -> Result<Expr, Box<dyn Error>> {
+        let mut expression: Expr = comparison(parser)?;
 
         while parser.match_(vec![TokenType::BANG_EQUAL, TokenType::EQUAL_EQUAL]) {
             let operator: Token = parser.previous();
-            let right: Box<dyn Expr> = comparison(parser)?;
-            expression = Box::new(expr::Binary::new(expression, operator, right));
+            let right: Expr = comparison(parser)?;
+            expression = Expr::Binary(expr::Binary::new(expression, operator, right));
         }
 
         Ok(expression)
     }
 
-    fn comparison(parser: &mut Parser) -> Result<Box<dyn Expr>, Box<dyn Error>> {
+    fn comparison(parser: &mut Parser) -> Result<Expr, Box<dyn Error>> {
         use TokenType::*;
 
-        let mut expression: Box<dyn Expr> = term(parser)?;
+        let mut expression: Expr = bit_or(parser)?;
         while parser.match_(vec![GREATER, GREATER_EQUAL, LESS, LESS_EQUAL]) {
             let operator: Token = parser.previous();
-            let right: Box<dyn Expr> = term(parser)?;
-            expression = Box::new(expr::Binary::new(expression, operator, right));
+            let right: Expr = bit_or(parser)?;
+            expression = Expr::Binary(expr::Binary::new(expression, operator, right));
         }
-        return Ok(expression);
+        Ok(expression)
     }
 
-    fn term(parser: &mut Parser) -> Result<Box<dyn Expr>, Box<dyn Error>> {
-        let mut expression: Box<dyn Expr> = factor(parser)?;
+    fn bit_or(parser: &mut Parser) -> Result<Expr, Box<dyn Error>> {
+        let mut expression: Expr = bit_xor(parser)?;
+
+        while parser.match_(vec![TokenType::PIPE]) {
+            let operator: Token = parser.previous();
+            let right: Expr = bit_xor(parser)?;
+            expression = Expr::Binary(expr::Binary::new(expression, operator, right));
+        }
+        Ok(expression)
+    }
+
+    fn bit_xor(parser: &mut Parser) -> Result<Expr, Box<dyn Error>> {
+        let mut expression: Expr = bit_and(parser)?;
+
+        while parser.match_(vec![TokenType::CARET]) {
+            let operator: Token = parser.previous();
+            let right: Expr = bit_and(parser)?;
+            expression = Expr::Binary(expr::Binary::new(expression, operator, right));
+        }
+        Ok(expression)
+    }
+
+    fn bit_and(parser: &mut Parser) -> Result<Expr, Box<dyn Error>> {
+        let mut expression: Expr = shift(parser)?;
+
+        while parser.match_(vec![TokenType::AMPERSAND]) {
+            let operator: Token = parser.previous();
+            let right: Expr = shift(parser)?;
+            expression = Expr::Binary(expr::Binary::new(expression, operator, right));
+        }
+        Ok(expression)
+    }
+
+    fn shift(parser: &mut Parser) -> Result<Expr, Box<dyn Error>> {
+        let mut expression: Expr = term(parser)?;
+
+        while parser.match_(vec![TokenType::LESS_LESS, TokenType::GREATER_GREATER]) {
+            let operator: Token = parser.previous();
+            let right: Expr = term(parser)?;
+            expression = Expr::Binary(expr::Binary::new(expression, operator, right));
+        }
+        Ok(expression)
+    }
+
+    fn term(parser: &mut Parser) -> Result<Expr, Box<dyn Error>> {
+        let mut expression: Expr = factor(parser)?;
 
         while parser.match_(vec![TokenType::MINUS, TokenType::PLUS]) {
             let operator: Token = parser.previous();
-            let right: Box<dyn Expr> = factor(parser)?;
-            expression = Box::new(expr::Binary::new(expression, operator, right));
+            let right: Expr = factor(parser)?;
+            expression = Expr::Binary(expr::Binary::new(expression, operator, right));
         }
-        return Ok(expression);
+        Ok(expression)
     }
 
-    fn factor(parser: &mut Parser) -> Result<Box<dyn Expr>, Box<dyn Error>> {
-        let mut expression: Box<dyn Expr> = unary(parser)?;
+    fn factor(parser: &mut Parser) -> Result<Expr, Box<dyn Error>> {
+        let mut expression: Expr = unary(parser)?;
 
-        while parser.match_(vec![TokenType::SLASH, TokenType::STAR]) {
+        while parser.match_(vec![
+            TokenType::SLASH,
+            TokenType::STAR,
+            TokenType::PERCENT,
+            TokenType::DIV,
+        ]) {
             let operator: Token = parser.previous();
-            let right: Box<dyn Expr> = unary(parser)?;
-            expression = Box::new(expr::Binary::new(expression, operator, right));
+            let right: Expr = unary(parser)?;
+            expression = Expr::Binary(expr::Binary::new(expression, operator, right));
         }
-        return Ok(expression);
+        Ok(expression)
     }
 
-    fn unary(parser: &mut Parser) -> Result<Box<dyn Expr>, Box<dyn Error>> {
+    fn unary(parser: &mut Parser) -> Result<Expr, Box<dyn Error>> {
         if parser.match_(vec![TokenType::BANG, TokenType::MINUS]) {
             let operator: Token = parser.previous();
-            let right: Box<dyn Expr> = unary(parser)?;
-            return Ok(Box::new(expr::Unary::new(operator, right)));
+            let right: Expr = unary(parser)?;
+            return Ok(Expr::Unary(expr::Unary::new(operator, right)));
+        }
+
+        if parser.match_(vec![TokenType::PLUS_PLUS, TokenType::MINUS_MINUS]) {
+            let operator: Token = parser.previous();
+            let target: Expr = unary(parser)?;
+            return compound_assignment(parser, target, operator, one());
+        }
+
+        power(parser)
+    }
+
+    /// `**` binds tighter than unary minus on its left and is right-associative.
+    fn power(parser: &mut Parser) -> Result<Expr, Box<dyn Error>> {
+        let expression: Expr = postfix(parser)?;
+
+        if parser.match_(vec![TokenType::STAR_STAR]) {
+            let operator: Token = parser.previous();
+            let right: Expr = unary(parser)?;
+            return Ok(Expr::Binary(expr::Binary::new(expression, operator, right)));
         }
+        Ok(expression)
+    }
+
+    /// Desugars `x++` into `(x += 1) - 1`, which yields the value from before the increment.
+    fn postfix(parser: &mut Parser) -> Result<Expr, Box<dyn Error>> {
+        let expression: Expr = call(parser)?;
+
+        if parser.match_(vec![TokenType::PLUS_PLUS, TokenType::MINUS_MINUS]) {
+            let operator: Token = parser.previous();
+            let (type_, lexeme) = if operator.type_ == TokenType::PLUS_PLUS {
+                (TokenType::MINUS, "-")
+            } else {
+                (TokenType::PLUS, "+")
+            };
+            let undo = Token::new(type_, lexeme.to_string(), None, operator.line);
+            let updated = compound_assignment(parser, expression, operator, one())?;
+            return Ok(Expr::Binary(expr::Binary::new(updated, undo, one())));
+        }
+        Ok(expression)
+    }
 
-        return call(parser);
+    fn one() -> Expr {
+        Expr::Literal(expr::Literal::new(Object::Int(1)))
     }
 
-    fn primary(parser: &mut Parser) -> Result<Box<dyn Expr>, Box<dyn Error>> {
+    fn primary(parser: &mut Parser) -> Result<Expr, Box<dyn Error>> {
         if parser.match_(vec![TokenType::FALSE]) {
-            return Ok(Box::new(expr::Literal::new(Object::Boolean(false))));
+            return Ok(Expr::Literal(expr::Literal::new(Object::Boolean(false))));
         }
 
         if parser.match_(vec![TokenType::TRUE]) {
-            return Ok(Box::new(expr::Literal::new(Object::Boolean(true))));
+            return Ok(Expr::Literal(expr::Literal::new(Object::Boolean(true))));
         }
 
         if parser.match_(vec![TokenType::NIL]) {
-            return Ok(Box::new(expr::Literal::new(Object::Nil)));
+            return Ok(Expr::Literal(expr::Literal::new(Object::Nil)));
         }
 
         if parser.match_(vec![TokenType::NUMBER, TokenType::STRING]) {
-            return Ok(Box::new(expr::Literal::new(
+            return Ok(Expr::Literal(expr::Literal::new(
                 *parser.previous().literal.unwrap(),
             )));
         }
 
+        if parser.match_(vec![TokenType::INTERPOLATION]) {
+            return interpolation(parser);
+        }
+
         if parser.match_(vec![TokenType::IDENTIFIER]) {
-            return Ok(Box::new(expr::Variable::new(parser.previous())));
+            return Ok(Expr::Variable(expr::Variable::new(parser.previous())));
         }
 
         if parser.match_(vec![TokenType::LEFT_PAREN]) {
-            let expression: Box<dyn Expr> = parser.expression()?;
+            let expression: Expr = parser.expression()?;
             parser.consume(TokenType::RIGHT_PAREN, "Expect ')' after expression.")?;
-            return Ok(Box::new(expr::Grouping::new(expression)));
+            return Ok(Expr::Grouping(expr::Grouping::new(expression)));
+        }
+
+        Err(parser.error(parser.peek(), "Expect expression."))
+    }
+
+    /// Desugars `"a ${x} b"`, scanned as INTERPOLATION("a ") x STRING(" b"), into `"a " + x + " b"`.
+    /// The leading segment is always kept so that `+` concatenates even when `x` is a number.
+    fn interpolation(parser: &mut Parser) -> Result<Expr, Box<dyn Error>> {
+        let mut expression: Expr =
+            Expr::Literal(expr::Literal::new(*parser.previous().literal.unwrap()));
+
+        loop {
+            let plus = Token::new(
+                TokenType::PLUS,
+                "+".to_string(),
+                None,
+                parser.previous().line,
+            );
+            let value: Expr = parser.expression()?;
+            expression = Expr::Binary(expr::Binary::new(expression, plus.clone(), value));
+
+            let segment = if parser.match_(vec![TokenType::INTERPOLATION]) {
+                parser.previous()
+            } else {
+                parser.consume(
+                    TokenType::STRING,
+                    "Expect end of string after interpolation.",
+                )?
+            };
+            let done = segment.type_ == TokenType::STRING;
+
+            let literal = *segment.literal.unwrap();
+            if literal != Object::String(String::new()) {
+                expression = Expr::Binary(expr::Binary::new(
+                    expression,
+                    plus,
+                    Expr::Literal(expr::Literal::new(literal)),
+                ));
+            }
+
+            if done {
+                return Ok(expression);
+            }
         }
+    }
 
-        panic!("Expected expression.");
+    /// `condition ? then : else`, right-associative so that `a ? b : c ? d : e`
+    /// groups as `a ? b : (c ? d : e)`.
+    fn conditional(parser: &mut Parser) -> Result<Expr, Box<dyn Error>> {
+        let condition: Expr = coalesce(parser)?;
+
+        if parser.match_(vec![TokenType::QUESTION]) {
+            let then_branch: Expr = parser.expression()?;
+            parser.consume(
+                TokenType::COLON,
+                "Expect ':' after then branch of conditional expression.",
+            )?;
+            let else_branch: Expr = conditional(parser)?;
+            return Ok(Expr::Conditional(expr::Conditional::new(
+                condition,
+                then_branch,
+                else_branch,
+            )));
+        }
+
+        Ok(condition)
     }
 
-    fn or(parser: &mut Parser) -> Result<Box<dyn Expr>, Box<dyn Error>> {
-        let mut expression: Box<dyn Expr> = and(parser)?;
+    /// `a ?? b` short-circuits like `or`, but only falls through to `b` when `a` is nil.
+    fn coalesce(parser: &mut Parser) -> Result<Expr, Box<dyn Error>> {
+        let mut expression: Expr = or(parser)?;
+
+        while parser.match_(vec![TokenType::QUESTION_QUESTION]) {
+            let operator: Token = parser.previous();
+            let right: Expr = or(parser)?;
+            expression = Expr::Logical(expr::Logical::new(expression, operator, right));
+        }
+
+        Ok(expression)
+    }
+
+    fn or(parser: &mut Parser) -> Result<Expr, Box<dyn Error>> {
+        let mut expression: Expr = and(parser)?;
 
         while parser.match_(vec![TokenType::OR]) {
             let operator: Token = parser.previous();
-            let right: Box<dyn Expr> = and(parser)?;
-            expression = Box::new(expr::Logical::new(expression, operator, right));
+            let right: Expr = and(parser)?;
+            expression = Expr::Logical(expr::Logical::new(expression, operator, right));
         }
 
         Ok(expression)
     }
 
-    fn and(parser: &mut Parser) -> Result<Box<dyn Expr>, Box<dyn Error>> {
-        let mut expression: Box<dyn Expr> = equality(parser)?;
+    fn and(parser: &mut Parser) -> Result<Expr, Box<dyn Error>> {
+        let mut expression: Expr = equality(parser)?;
 
         while parser.match_(vec![TokenType::AND]) {
             let operator: Token = parser.previous();
-            let right: Box<dyn Expr> = equality(parser)?;
-            expression = Box::new(expr::Logical::new(expression, operator, right));
+            let right: Expr = equality(parser)?;
+            expression = Expr::Logical(expr::Logical::new(expression, operator, right));
         }
 
         Ok(expression)
     }
 
-    fn call(parser: &mut Parser) -> Result<Box<dyn Expr>, Box<dyn Error>> {
-        let mut expr: Box<dyn Expr> = primary(parser)?;
+    fn call(parser: &mut Parser) -> Result<Expr, Box<dyn Error>> {
+        let mut expr: Expr = primary(parser)?;
 
         loop {
             if parser.match_(vec![TokenType::LEFT_PAREN]) {
                 expr = finish_call(parser, expr)?;
+            } else if parser.match_(vec![TokenType::DOT]) {
+                let name: Token =
+                    parser.consume(TokenType::IDENTIFIER, "Expect property name after '.'.")?;
+                expr = Expr::Get(expr::Get::new(expr, name));
             } else {
                 break;
             }
@@ -286,11 +568,8 @@ mod expression {
         Ok(expr)
     }
 
-    fn finish_call(
-        parser: &mut Parser,
-        callee: Box<dyn Expr>,
-    ) -> Result<Box<dyn Expr>, Box<dyn Error>> {
-        let mut arguments: Vec<Box<dyn Expr>> = Vec::new();
+    fn finish_call(parser: &mut Parser, callee: Expr) -> Result<Expr, Box<dyn Error>> {
+        let mut arguments: Vec<Expr> = Vec::new();
 
         if !parser.check(TokenType::RIGHT_PAREN) {
             loop {
@@ -307,7 +586,7 @@ mod expression {
 
         let paren = parser.consume(TokenType::RIGHT_PAREN, "Expected ')' after arguments.")?;
 
-        Ok(Box::new(expr::Call::new(callee, paren, arguments)))
+        Ok(Expr::Call(expr::Call::new(callee, paren, arguments)))
     }
 }
 
@@ -317,30 +596,33 @@ mod statement {
     use crate::stmt::{self, Stmt};
     use crate::token::token_type::TokenType;
     use crate::token::Token;
-    use std::cell::RefCell;
     use std::error::Error;
     use std::rc::Rc;
 
-    pub fn print(parser: &mut Parser) -> Result<Box<dyn Stmt>, Box<dyn Error>> {
-        let value: Box<dyn Expr> = parser.expression()?;
+    pub fn print(parser: &mut Parser) -> Result<Stmt, Box<dyn Error>> {
+        let keyword: Token = parser.previous();
+        let value: Expr = parser.expression()?;
         parser.consume(TokenType::SEMICOLON, "Expect ';' after value.")?;
 
-        Ok(Box::new(stmt::Print::new(value)))
+        Ok(Stmt::Print(stmt::Print::new(keyword, value)))
     }
 
-    pub fn expression(parser: &mut Parser) -> Result<Box<dyn Stmt>, Box<dyn Error>> {
-        let expr: Box<dyn Expr> = parser.expression()?;
+    pub fn expression(parser: &mut Parser) -> Result<Stmt, Box<dyn Error>> {
+        let expr: Expr = parser.expression()?;
         parser.consume(TokenType::SEMICOLON, "Expect ';' after expression.")?;
 
-        Ok(Box::new(stmt::Expression::new(expr)))
+        Ok(Stmt::Expression(stmt::Expression::new(expr)))
     }
 
-    pub fn var_declaration(parser: &mut Parser) -> Result<Box<dyn Stmt>, Box<dyn Error>> {
+    pub fn var_declaration(
+        parser: &mut Parser,
+        doc: Option<String>,
+    ) -> Result<Stmt, Box<dyn Error>> {
         let name: Token = parser
             .consume(TokenType::IDENTIFIER, "Expect variable name.")?
             .to_owned();
 
-        let mut initializer: Option<Box<dyn Expr>> = None;
+        let mut initializer: Option<Expr> = None;
         if parser.match_(vec![TokenType::EQUAL]) {
             initializer = Some(parser.expression()?);
         }
@@ -350,91 +632,105 @@ mod statement {
             "Expect ';' after variable declaration.",
         )?;
 
-        Ok(Box::new(stmt::Var::new(name, initializer)))
+        Ok(Stmt::Var(stmt::Var::new(name, initializer, doc)))
     }
 
-    pub fn block(
-        parser: &mut Parser,
-    ) -> Result<Vec<Rc<RefCell<Box<dyn stmt::Stmt>>>>, Box<dyn Error>> {
-        let mut statements: Vec<Rc<RefCell<Box<dyn stmt::Stmt>>>> = Vec::new();
+    pub fn import_declaration(parser: &mut Parser) -> Result<Stmt, Box<dyn Error>> {
+        let path: Token =
+            parser.consume(TokenType::STRING, "Expect module path after 'import'.")?;
+        parser.consume(TokenType::AS, "Expect 'as' after module path.")?;
+        let name: Token =
+            parser.consume(TokenType::IDENTIFIER, "Expect module name after 'as'.")?;
+        parser.consume(TokenType::SEMICOLON, "Expect ';' after import.")?;
+
+        Ok(Stmt::Import(stmt::Import::new(path, name)))
+    }
+
+    pub fn block(parser: &mut Parser) -> Result<Vec<Stmt>, Box<dyn Error>> {
+        let mut statements: Vec<Stmt> = Vec::new();
 
         while !parser.check(TokenType::RIGHT_BRACE) && !parser.is_at_end() {
-            statements.push(Rc::new(RefCell::new(parser.declaration()?)));
+            statements.push(parser.declaration()?);
         }
 
         parser.consume(TokenType::RIGHT_BRACE, "Expect '}' after block.")?;
         Ok(statements)
     }
 
-    pub fn if_statement(parser: &mut Parser) -> Result<Box<dyn Stmt>, Box<dyn Error>> {
+    pub fn if_statement(parser: &mut Parser) -> Result<Stmt, Box<dyn Error>> {
+        let keyword: Token = parser.previous();
         parser.consume(TokenType::LEFT_PAREN, "Expect '(' after if.")?;
-        let condition: Box<dyn Expr> = parser.expression()?;
+        let condition: Expr = parser.expression()?;
         parser.consume(TokenType::RIGHT_PAREN, "Expect ')' after if condition.")?;
-        let then_branch: Box<dyn Stmt> = parser.statement()?;
-        let else_branch: Option<Box<dyn Stmt>> = if parser.match_(vec![TokenType::ELSE]) {
+        let then_branch: Stmt = parser.statement()?;
+        let else_branch: Option<Stmt> = if parser.match_(vec![TokenType::ELSE]) {
             Some(parser.statement()?)
         } else {
             None
         };
-        Ok(Box::new(stmt::If::new(condition, then_branch, else_branch)))
+        Ok(Stmt::If(stmt::If::new(
+            keyword,
+            condition,
+            then_branch,
+            else_branch,
+        )))
     }
 
-    pub fn while_statement(parser: &mut Parser) -> Result<Box<dyn Stmt>, Box<dyn Error>> {
+    pub fn while_statement(parser: &mut Parser) -> Result<Stmt, Box<dyn Error>> {
+        let keyword: Token = parser.previous();
         parser.consume(TokenType::LEFT_PAREN, "Expect '(' after if.")?;
-        let condition: Box<dyn Expr> = parser.expression()?;
+        let condition: Expr = parser.expression()?;
         parser.consume(TokenType::RIGHT_PAREN, "Expect ')' after if condition.")?;
-        let body: Box<dyn Stmt> = parser.statement()?;
+        let body: Stmt = parser.statement()?;
 
-        Ok(Box::new(stmt::While::new(condition, body)))
+        Ok(Stmt::While(stmt::While::new(
+            keyword, condition, body, None,
+        )))
     }
 
-    pub fn for_statement(parser: &mut Parser) -> Result<Box<dyn Stmt>, Box<dyn Error>> {
+    pub fn for_statement(parser: &mut Parser) -> Result<Stmt, Box<dyn Error>> {
+        let keyword: Token = parser.previous();
         parser.consume(TokenType::LEFT_PAREN, "Expect '(' after for.")?;
 
-        let initializer: Option<Box<dyn Stmt>> = if parser.match_(vec![TokenType::SEMICOLON]) {
+        let initializer: Option<Stmt> = if parser.match_(vec![TokenType::SEMICOLON]) {
             None
         } else if parser.match_(vec![TokenType::VAR]) {
-            Some(self::var_declaration(parser)?)
+            Some(self::var_declaration(parser, None)?)
         } else {
             Some(self::expression(parser)?)
         };
 
-        let mut condition: Option<Box<dyn Expr>> = if !parser.match_(vec![TokenType::SEMICOLON]) {
+        let mut condition: Option<Expr> = if !parser.check(TokenType::SEMICOLON) {
             Some(parser.expression()?)
         } else {
             None
         };
         parser.consume(TokenType::SEMICOLON, "Expect ';' after loop condition.")?;
 
-        let increment: Option<Box<dyn Expr>> = if !parser.match_(vec![TokenType::RIGHT_PAREN]) {
+        let increment: Option<Expr> = if !parser.check(TokenType::RIGHT_PAREN) {
             Some(parser.expression()?)
         } else {
             None
         };
         parser.consume(TokenType::RIGHT_PAREN, "Expect ')' after for clauses.")?;
 
-        let mut body: Box<dyn Stmt> = parser.statement()?;
-
-        if let Some(increment) = increment {
-            body = Box::new(stmt::Block::new(vec![
-                Rc::new(RefCell::new(body)),
-                Rc::new(RefCell::new(Box::new(stmt::Expression::new(increment)))),
-            ]));
-        }
+        let mut body: Stmt = parser.statement()?;
 
         if condition.is_none() {
-            condition = Some(Box::new(expr::Literal::new(
+            condition = Some(Expr::Literal(expr::Literal::new(
                 crate::object::Object::Boolean(true),
             )));
         };
 
-        body = Box::new(stmt::While::new(condition.unwrap(), body));
+        body = Stmt::While(stmt::While::new(
+            keyword,
+            condition.unwrap(),
+            body,
+            increment,
+        ));
 
         if let Some(initializer) = initializer {
-            body = Box::new(stmt::Block::new(vec![
-                Rc::new(RefCell::new(initializer)),
-                Rc::new(RefCell::new(body)),
-            ]));
+            body = Stmt::Block(stmt::Block::new(vec![initializer, body]));
         }
 
         Ok(body)
@@ -443,7 +739,8 @@ mod statement {
     pub fn function_definition(
         parser: &mut Parser,
         kind: &str,
-    ) -> Result<Box<dyn Stmt>, Box<dyn Error>> {
+        doc: Option<String>,
+    ) -> Result<Stmt, Box<dyn Error>> {
         let name: Token = parser
             .consume(
                 TokenType::IDENTIFIER,
@@ -469,16 +766,72 @@ mod statement {
             format!("Expect '{{' before {} body.", kind).as_str(),
         )?;
         let body = block(parser)?;
-        Ok(Box::new(stmt::Function::new(name, parameters, body)))
+        Ok(Stmt::Function(Rc::new(stmt::Function::new(
+            name, parameters, body, doc,
+        ))))
     }
 
-    pub fn return_statement(parser: &mut Parser) -> Result<Box<dyn Stmt>, Box<dyn Error>> {
+    pub fn return_statement(parser: &mut Parser) -> Result<Stmt, Box<dyn Error>> {
         let keyword: Token = parser.previous();
-        let mut value: Option<Box<dyn Expr>> = None;
+        let mut value: Option<Expr> = None;
         if !parser.check(TokenType::SEMICOLON) {
             value = Some(parser.expression()?);
         }
         parser.consume(TokenType::SEMICOLON, "Expect ';' after return value.")?;
-        Ok(Box::new(stmt::Return::new(keyword, value)))
+        Ok(Stmt::Return(stmt::Return::new(keyword, value)))
+    }
+
+    pub fn loop_jump(parser: &mut Parser) -> Result<Stmt, Box<dyn Error>> {
+        let keyword: Token = parser.previous();
+        parser.consume(
+            TokenType::SEMICOLON,
+            format!("Expect ';' after '{}'.", keyword.lexeme).as_str(),
+        )?;
+
+        if keyword.type_ == TokenType::BREAK {
+            Ok(Stmt::Break(stmt::Break::new(keyword)))
+        } else {
+            Ok(Stmt::Continue(stmt::Continue::new(keyword)))
+        }
+    }
+
+    pub fn throw_statement(parser: &mut Parser) -> Result<Stmt, Box<dyn Error>> {
+        let keyword: Token = parser.previous();
+        let value: Expr = parser.expression()?;
+        parser.consume(TokenType::SEMICOLON, "Expect ';' after thrown value.")?;
+        Ok(Stmt::Throw(stmt::Throw::new(keyword, value)))
+    }
+
+    pub fn try_statement(parser: &mut Parser) -> Result<Stmt, Box<dyn Error>> {
+        let keyword: Token = parser.previous();
+        parser.consume(TokenType::LEFT_BRACE, "Expect '{' after 'try'.")?;
+        let body = block(parser)?;
+
+        let mut catch = None;
+        if parser.match_(vec![TokenType::CATCH]) {
+            parser.consume(TokenType::LEFT_PAREN, "Expect '(' after 'catch'.")?;
+            let name = parser.consume(TokenType::IDENTIFIER, "Expect exception variable name.")?;
+            parser.consume(
+                TokenType::RIGHT_PAREN,
+                "Expect ')' after exception variable.",
+            )?;
+            parser.consume(TokenType::LEFT_BRACE, "Expect '{' before catch body.")?;
+            catch = Some((name, block(parser)?));
+        }
+
+        let mut finally = None;
+        if parser.match_(vec![TokenType::FINALLY]) {
+            parser.consume(TokenType::LEFT_BRACE, "Expect '{' after 'finally'.")?;
+            finally = Some(block(parser)?);
+        }
+
+        if catch.is_none() && finally.is_none() {
+            return Err(parser.error(
+                parser.peek(),
+                "Expect 'catch' or 'finally' after try block.",
+            ));
+        }
+
+        Ok(Stmt::Try(stmt::Try::new(keyword, body, catch, finally)))
     }
 }
@@ -1,20 +1,74 @@
 use crate::{
     expr::{self, Expr},
     interpreter::Interpreter,
-    object::Object,
     stmt::{self, Stmt},
     token::Token,
 };
-use std::cell::RefCell;
 use std::cmp::PartialEq;
-use std::ops::DerefMut;
 use std::rc::Rc;
 use std::{collections::HashMap, error::Error};
 
+/// What a declaration introduces its name as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BindingKind {
+    Variable,
+    Parameter,
+    /// A function and the number of parameters it takes.
+    Function(usize),
+    Import,
+    /// The exception a `catch` clause names.
+    Catch,
+}
+
+/// A declared name and every expression that refers to it.
+#[derive(Debug, Clone)]
+pub struct Binding {
+    pub name: Token,
+    pub kind: BindingKind,
+    /// Whether it's declared at the top level, where names are looked up at run time
+    /// rather than by scope distance.
+    pub global: bool,
+    /// The binding of the same name in an enclosing scope that this one hides.
+    pub shadows: Option<usize>,
+    /// The names that read or assign it.
+    pub uses: Vec<Token>,
+}
+
+/// The names the resolver found declared and what each variable refers to, for the
+/// linter and the language server.
+#[derive(Debug, Default)]
+pub struct Bindings {
+    pub bindings: Vec<Binding>,
+    /// The binding each `Variable` and `Assign` expression refers to, by expression id.
+    pub resolved: HashMap<usize, usize>,
+    /// Names that refer to no declaration, such as natives or typos.
+    pub unresolved: Vec<Token>,
+}
+
+impl Bindings {
+    /// The binding the expression with this id refers to.
+    pub fn get(&self, id: usize) -> Option<&Binding> {
+        self.resolved.get(&id).map(|&index| &self.bindings[index])
+    }
+}
+
+struct Local {
+    defined: bool,
+    binding: usize,
+}
+
 pub struct Resolver<'a> {
     interpreter: &'a mut Interpreter,
-    scopes: Vec<HashMap<String, bool>>,
+    scopes: Vec<HashMap<String, Local>>,
     current_func: FuncType,
+    // Loops enclosing the current statement within the current function.
+    loop_depth: usize,
+    bindings: Bindings,
+    // The latest top-level binding of each name.
+    globals: HashMap<String, usize>,
+    // Names no local scope declares, by expression id; they may be globals declared
+    // further down.
+    free: Vec<(usize, Token)>,
 }
 
 #[derive(Clone, PartialEq)]
@@ -29,32 +83,60 @@ impl<'a> Resolver<'a> {
             interpreter,
             scopes: Vec::new(),
             current_func: FuncType::None,
+            loop_depth: 0,
+            bindings: Bindings::default(),
+            globals: HashMap::new(),
+            free: Vec::new(),
         }
     }
 
-    pub fn resolve(&mut self, statements: &mut Vec<Box<dyn Stmt>>) -> Result<(), Box<dyn Error>> {
-        for statement in statements.iter_mut() {
-            self.resolve_statement(statement.as_mut())?;
+    /// Resolves a program and returns its first error. Resolution carries on with the
+    /// next top-level statement after an error, so that `bindings` covers all of it.
+    pub fn resolve(&mut self, statements: &[Stmt]) -> Result<(), Box<dyn Error>> {
+        let mut first_error = None;
+        for statement in statements {
+            if let Err(err) = self.resolve_statement(statement) {
+                first_error.get_or_insert(err);
+                self.scopes.clear();
+                self.current_func = FuncType::None;
+                self.loop_depth = 0;
+            }
         }
-        Ok(())
+        first_error.map_or(Ok(()), Err)
+    }
+
+    /// What every name in the resolved statements refers to. Names no scope declares
+    /// refer to the global of that name, wherever it's declared.
+    pub fn bindings(mut self) -> Bindings {
+        for (id, name) in std::mem::take(&mut self.free) {
+            match self.globals.get(&name.lexeme) {
+                Some(&global) => {
+                    self.bindings.resolved.insert(id, global);
+                    self.bindings.bindings[global].uses.push(name);
+                }
+                None => self.bindings.unresolved.push(name),
+            }
+        }
+        for binding in &mut self.bindings.bindings {
+            if !binding.global && binding.shadows.is_none() {
+                binding.shadows = self.globals.get(&binding.name.lexeme).copied();
+            }
+        }
+        self.bindings
     }
 
-    pub fn resolve_rc(
-        &mut self,
-        statements: &mut Vec<Rc<RefCell<Box<dyn Stmt>>>>,
-    ) -> Result<(), Box<dyn Error>> {
-        for statement in statements.iter_mut() {
-            self.resolve_statement(statement.borrow_mut().as_mut())?;
+    fn resolve_statements(&mut self, statements: &[Stmt]) -> Result<(), Box<dyn Error>> {
+        for statement in statements {
+            self.resolve_statement(statement)?;
         }
         Ok(())
     }
 
-    fn resolve_statement(&mut self, statement: &mut dyn Stmt) -> Result<(), Box<dyn Error>> {
-        statement.accept(self)?;
-        Ok(())
+    fn resolve_statement(&mut self, statement: &Stmt) -> Result<(), Box<dyn Error>> {
+        statement.accept(self)
     }
 
-    fn resolve_expression(&mut self, expr: &mut dyn Expr) -> Result<Object, Box<dyn Error>> {
+    fn resolve_expression(&mut self, expr: &Expr) -> Result<(), Box<dyn Error>> {
         expr.accept(self)
     }
 
@@ -78,42 +160,81 @@ impl<'a> Resolver<'a> {
         }
     }
 
-    pub fn declare(&mut self, name: &str) -> Result<(), Box<dyn Error>> {
-        if let Some(scope) = self.scopes.last_mut() {
-            if scope.contains_key(name) {
-                return Err(self.interpreter.error(
-                    "ResolverError: Already a variable with this name in this scope.",
-                    &Token::new(
-                        crate::token::token_type::TokenType::NIL,
-                        "None".to_string(),
-                        None,
-                        0,
-                    ),
-                ));
-            }
-            scope.insert(name.to_string(), false);
+    pub fn declare(&mut self, name: &Token, kind: BindingKind) -> Result<(), Box<dyn Error>> {
+        let binding = self.bindings.bindings.len();
+        let Some((scope, enclosing)) = self.scopes.split_last_mut() else {
+            self.bindings.bindings.push(Binding {
+                name: name.clone(),
+                kind,
+                global: true,
+                shadows: None,
+                uses: Vec::new(),
+            });
+            self.globals.insert(name.lexeme.clone(), binding);
+            return Ok(());
+        };
+        if scope.contains_key(&name.lexeme) {
+            return Err(self.interpreter.error(
+                "ResolverError: Already a variable with this name in this scope.",
+                name,
+            ));
         }
+        scope.insert(
+            name.lexeme.clone(),
+            Local {
+                defined: false,
+                binding,
+            },
+        );
+
+        let shadows = enclosing
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(&name.lexeme))
+            .map(|local| local.binding);
+        self.bindings.bindings.push(Binding {
+            name: name.clone(),
+            kind,
+            global: false,
+            shadows,
+            uses: Vec::new(),
+        });
         Ok(())
     }
 
     pub fn define(&mut self, name: &str) {
-        if self.scopes.is_empty() {
-            return;
-        }
-        if let Some(scope) = self.scopes.last_mut() {
-            scope.insert(name.to_string(), true);
+        if let Some(local) = self.scopes.last_mut().and_then(|scope| scope.get_mut(name)) {
+            local.defined = true;
         }
     }
 
-    pub fn resolve_local(&mut self, expr: &dyn Expr, name: &str) {
+    pub fn resolve_local(&mut self, id: usize, name: &Token) {
         let n = self.scopes.len();
         for i in (0..n).rev() {
-            if self.scopes.get(i).unwrap().contains_key(name) {
+            if let Some(local) = self.scopes[i].get(&name.lexeme) {
                 let distance = self.scopes.len() - 1 - i;
-                self.interpreter.resolve(expr.clone_box(), distance as i32);
+                self.interpreter.resolve(id, distance);
+                self.bindings.resolved.insert(id, local.binding);
+                self.bindings.bindings[local.binding]
+                    .uses
+                    .push(name.clone());
                 return;
             }
         }
+        self.free.push((id, name.clone()));
+    }
+
+    fn check_in_loop(&self, keyword: &Token) -> Result<(), Box<dyn Error>> {
+        if self.loop_depth == 0 {
+            return Err(self.interpreter.error(
+                &format!(
+                    "ResolverError: Can't use '{}' outside of a loop.",
+                    keyword.lexeme
+                ),
+                keyword,
+            ));
+        }
+        Ok(())
     }
 
     fn resolve_func(
@@ -123,53 +244,77 @@ impl<'a> Resolver<'a> {
     ) -> Result<(), Box<dyn Error>> {
         let enclosing_func = self.current_func.clone();
         self.current_func = func_type;
+        let enclosing_loop_depth = std::mem::take(&mut self.loop_depth);
 
         self.begin_scope();
         for param in &func.params {
-            self.declare(param.lexeme.as_str())?;
+            self.declare(param, BindingKind::Parameter)?;
             self.define(param.lexeme.as_str());
         }
+        self.resolve_statements(&func.body)?;
         self.end_scope()?;
         self.current_func = enclosing_func;
+        self.loop_depth = enclosing_loop_depth;
         Ok(())
     }
 }
 
-impl<'a> stmt::Visitor for Resolver<'a> {
-    fn visit_block_stmt(&mut self, stmt: &mut stmt::Block) -> Result<(), Box<dyn Error>> {
+impl<'a> stmt::Visitor<Result<(), Box<dyn Error>>> for Resolver<'a> {
+    fn visit_block_stmt(&mut self, stmt: &stmt::Block) -> Result<(), Box<dyn Error>> {
         self.begin_scope();
-        self.resolve_rc(&mut stmt.statements)?;
+        self.resolve_statements(&stmt.statements)?;
         self.end_scope()?;
         Ok(())
     }
 
-    fn visit_class_stmt(&self, stmt: &stmt::Class) -> Result<(), Box<dyn Error>> {
+    fn visit_break_stmt(&mut self, stmt: &stmt::Break) -> Result<(), Box<dyn Error>> {
+        self.check_in_loop(&stmt.keyword)?;
+        Ok(())
+    }
+
+    fn visit_class_stmt(&mut self, _stmt: &stmt::Class) -> Result<(), Box<dyn Error>> {
         todo!()
     }
 
-    fn visit_expr_stmt(&mut self, stmt: &mut stmt::Expression) -> Result<(), Box<dyn Error>> {
-        self.resolve_expression(stmt.expression.borrow_mut().as_mut())?;
+    fn visit_continue_stmt(&mut self, stmt: &stmt::Continue) -> Result<(), Box<dyn Error>> {
+        self.check_in_loop(&stmt.keyword)?;
         Ok(())
     }
 
-    fn visit_func_stmt(&mut self, stmt: &stmt::Function) -> Result<(), Box<dyn Error>> {
-        self.declare(stmt.name.lexeme.as_str())?;
+    fn visit_expr_stmt(&mut self, stmt: &stmt::Expression) -> Result<(), Box<dyn Error>> {
+        self.resolve_expression(&stmt.expression)?;
+        Ok(())
+    }
+
+    fn visit_func_stmt(&mut self, stmt: &Rc<stmt::Function>) -> Result<(), Box<dyn Error>> {
+        self.declare(&stmt.name, BindingKind::Function(stmt.params.len()))?;
         self.define(stmt.name.lexeme.as_str());
         self.resolve_func(stmt, FuncType::Function)?;
         Ok(())
     }
 
-    fn visit_if_stmt(&mut self, stmt: &mut stmt::If) -> Result<(), Box<dyn Error>> {
-        self.resolve_expression(stmt.condition.borrow_mut().as_mut())?;
-        self.resolve_statement(stmt.then_branch.borrow_mut().as_mut())?;
+    fn visit_if_stmt(&mut self, stmt: &stmt::If) -> Result<(), Box<dyn Error>> {
+        self.resolve_expression(&stmt.condition)?;
+        self.resolve_statement(&stmt.then_branch)?;
         if let Some(else_branch) = &stmt.else_branch {
-            self.resolve_statement(else_branch.borrow_mut().as_mut())?;
+            self.resolve_statement(else_branch)?;
         }
         Ok(())
     }
 
-    fn visit_print_stmt(&mut self, stmt: &mut stmt::Print) -> Result<(), Box<dyn Error>> {
-        self.resolve_expression(stmt.expression.borrow_mut().as_mut())?;
+    fn visit_import_stmt(&mut self, stmt: &stmt::Import) -> Result<(), Box<dyn Error>> {
+        if !self.scopes.is_empty() {
+            return Err(self
+                .interpreter
+                .error("Can only import at the top level.", &stmt.path));
+        }
+        self.declare(&stmt.name, BindingKind::Import)?;
+        self.define(&stmt.name.lexeme);
+        Ok(())
+    }
+
+    fn visit_print_stmt(&mut self, stmt: &stmt::Print) -> Result<(), Box<dyn Error>> {
+        self.resolve_expression(&stmt.expression)?;
         Ok(())
     }
 
@@ -186,18 +331,40 @@ impl<'a> stmt::Visitor for Resolver<'a> {
             ));
         }
         if let Some(value) = &stmt.value {
-            self.resolve_expression(value.borrow_mut().as_mut())?;
+            self.resolve_expression(value)?;
+        }
+        Ok(())
+    }
+
+    fn visit_throw_stmt(&mut self, stmt: &stmt::Throw) -> Result<(), Box<dyn Error>> {
+        self.resolve_expression(&stmt.value)?;
+        Ok(())
+    }
+
+    fn visit_try_stmt(&mut self, stmt: &stmt::Try) -> Result<(), Box<dyn Error>> {
+        self.begin_scope();
+        self.resolve_statements(&stmt.body)?;
+        self.end_scope()?;
+
+        if let Some((name, body)) = &stmt.catch {
+            self.begin_scope();
+            self.declare(name, BindingKind::Catch)?;
+            self.define(&name.lexeme);
+            self.resolve_statements(body)?;
+            self.end_scope()?;
+        }
+
+        if let Some(body) = &stmt.finally {
+            self.begin_scope();
+            self.resolve_statements(body)?;
+            self.end_scope()?;
         }
         Ok(())
     }
 
-    fn visit_var_stmt(&mut self, stmt: &mut stmt::Var) -> Result<(), Box<dyn Error>> {
-        self.declare(&stmt.name.lexeme)?;
-        if stmt.initializer.is_some() {
-            let expr = stmt.initializer.as_ref().unwrap();
-            let mut c = expr.borrow_mut();
-            let expr: &mut Box<dyn Expr> = c.deref_mut();
-            let expr = expr.as_mut();
+    fn visit_var_stmt(&mut self, stmt: &stmt::Var) -> Result<(), Box<dyn Error>> {
+        self.declare(&stmt.name, BindingKind::Variable)?;
+        if let Some(expr) = &stmt.initializer {
             self.resolve_expression(expr)?;
         }
         self.define(stmt.name.lexeme.as_str());
@@ -205,80 +372,97 @@ impl<'a> stmt::Visitor for Resolver<'a> {
     }
 
     fn visit_while_stmt(&mut self, stmt: &stmt::While) -> Result<(), Box<dyn Error>> {
-        self.resolve_expression(stmt.condition.borrow_mut().as_mut())?;
-        self.resolve_statement(stmt.body.borrow_mut().as_mut())?;
+        self.resolve_expression(&stmt.condition)?;
+        self.loop_depth += 1;
+        self.resolve_statement(&stmt.body)?;
+        self.loop_depth -= 1;
+        if let Some(increment) = &stmt.increment {
+            self.resolve_expression(increment)?;
+        }
         Ok(())
     }
 }
 
-impl<'a> expr::Visitor for Resolver<'a> {
-    fn visit_assign_expr(&mut self, expr: &expr::Assign) -> Result<Object, Box<dyn Error>> {
-        self.resolve_expression(expr.value.borrow_mut().as_mut())?;
-        self.resolve_local(expr, expr.name.lexeme.as_str());
-        Ok(Object::Nil)
+impl<'a> expr::Visitor<Result<(), Box<dyn Error>>> for Resolver<'a> {
+    fn visit_assign_expr(&mut self, expr: &expr::Assign) -> Result<(), Box<dyn Error>> {
+        self.resolve_expression(&expr.value)?;
+        self.resolve_local(expr.id, &expr.name);
+        Ok(())
     }
 
-    fn visit_binary_expr(&mut self, expr: &mut expr::Binary) -> Result<Object, Box<dyn Error>> {
-        self.resolve_expression(expr.left.borrow_mut().as_mut())?;
-        self.resolve_expression(expr.right.borrow_mut().as_mut())?;
-        Ok(Object::Nil)
+    fn visit_binary_expr(&mut self, expr: &expr::Binary) -> Result<(), Box<dyn Error>> {
+        self.resolve_expression(&expr.left)?;
+        self.resolve_expression(&expr.right)?;
+        Ok(())
     }
 
-    fn visit_call_expr(&mut self, expr: &expr::Call) -> Result<Object, Box<dyn Error>> {
-        self.resolve_expression(expr.callee.borrow_mut().as_mut())?;
+    fn visit_call_expr(&mut self, expr: &expr::Call) -> Result<(), Box<dyn Error>> {
+        self.resolve_expression(&expr.callee)?;
         for arg in &expr.arguments {
-            self.resolve_expression(arg.borrow_mut().as_mut())?;
+            self.resolve_expression(arg)?;
         }
-        Ok(Object::Nil)
+        Ok(())
     }
 
-    fn visit_get_expr(&self, expr: &expr::Get) -> Result<Object, Box<dyn Error>> {
-        todo!()
+    fn visit_conditional_expr(&mut self, expr: &expr::Conditional) -> Result<(), Box<dyn Error>> {
+        self.resolve_expression(&expr.condition)?;
+        self.resolve_expression(&expr.then_branch)?;
+        self.resolve_expression(&expr.else_branch)?;
+        Ok(())
+    }
+
+    fn visit_get_expr(&mut self, expr: &expr::Get) -> Result<(), Box<dyn Error>> {
+        self.resolve_expression(&expr.object)?;
+        Ok(())
     }
 
-    fn visit_group_expr(&mut self, expr: &mut expr::Grouping) -> Result<Object, Box<dyn Error>> {
-        self.resolve_expression(expr.expression.borrow_mut().as_mut())?;
-        Ok(Object::Nil)
+    fn visit_group_expr(&mut self, expr: &expr::Grouping) -> Result<(), Box<dyn Error>> {
+        self.resolve_expression(&expr.expression)?;
+        Ok(())
     }
 
-    fn visit_literal_expr(&self, _expr: &expr::Literal) -> Result<Object, Box<dyn Error>> {
-        Ok(Object::Nil)
+    fn visit_literal_expr(&mut self, _expr: &expr::Literal) -> Result<(), Box<dyn Error>> {
+        Ok(())
     }
 
-    fn visit_logical_expr(&mut self, expr: &expr::Logical) -> Result<Object, Box<dyn Error>> {
-        self.resolve_expression(expr.left.borrow_mut().as_mut())?;
-        self.resolve_expression(expr.right.borrow_mut().as_mut())?;
-        Ok(Object::Nil)
+    fn visit_logical_expr(&mut self, expr: &expr::Logical) -> Result<(), Box<dyn Error>> {
+        self.resolve_expression(&expr.left)?;
+        self.resolve_expression(&expr.right)?;
+        Ok(())
     }
 
-    fn visit_set_expr(&self, expr: &expr::Set) -> Result<Object, Box<dyn Error>> {
-        todo!()
+    fn visit_set_expr(&mut self, expr: &expr::Set) -> Result<(), Box<dyn Error>> {
+        self.resolve_expression(&expr.value)?;
+        self.resolve_expression(&expr.object)?;
+        Ok(())
     }
 
-    fn visit_super_expr(&self, expr: &expr::Super) -> Result<Object, Box<dyn Error>> {
+    fn visit_super_expr(&mut self, _expr: &expr::Super) -> Result<(), Box<dyn Error>> {
         todo!()
     }
 
-    fn visit_this_expr(&self, expr: &expr::This) -> Result<Object, Box<dyn Error>> {
+    fn visit_this_expr(&mut self, _expr: &expr::This) -> Result<(), Box<dyn Error>> {
         todo!()
     }
 
-    fn visit_unary_expr(&mut self, expr: &mut expr::Unary) -> Result<Object, Box<dyn Error>> {
-        self.resolve_expression(expr.right.borrow_mut().as_mut())?;
-        Ok(Object::Nil)
+    fn visit_unary_expr(&mut self, expr: &expr::Unary) -> Result<(), Box<dyn Error>> {
+        self.resolve_expression(&expr.right)?;
+        Ok(())
     }
 
-    fn visit_variable_expr(&mut self, expr: &expr::Variable) -> Result<Object, Box<dyn Error>> {
-        if !self.scopes.is_empty() {
-            let tmp = self.scopes.last().unwrap().get(&expr.name.lexeme);
-            if !tmp.is_none() && tmp.unwrap() == &false {
+    fn visit_variable_expr(&mut self, expr: &expr::Variable) -> Result<(), Box<dyn Error>> {
+        if let Some(scope) = self.scopes.last() {
+            if scope
+                .get(&expr.name.lexeme)
+                .is_some_and(|local| !local.defined)
+            {
                 return Err(self.interpreter.error(
                     "ResolverError: cannot read local variable in its own initializer.",
                     &expr.name,
                 ));
             }
         }
-        self.resolve_local(expr, expr.name.lexeme.as_str());
-        Ok(Object::Nil)
+        self.resolve_local(expr.id, &expr.name);
+        Ok(())
     }
 }
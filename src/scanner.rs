@@ -1,12 +1,24 @@
+use std::error::Error;
+
+use crate::error::{error_types::ScanError, LoxError};
 use crate::object::Object;
 use crate::token::{token_type::TokenType, Token};
 
+/// Scans source text into tokens in a single pass.
+/// `start` and `current` are byte offsets into `source`, always on UTF-8 character boundaries.
 pub struct Scanner {
     source: String,
     tokens: Vec<Token>,
-    start: i64,
-    current: i64,
+    start: usize,
+    current: usize,
     line: i64,
+    // 1-based character columns of `start` and `current`, kept as the cursor moves so
+    // that no token has to rescan its line.
+    start_column: usize,
+    column: usize,
+    // One entry per open `${`, counting the `{` nested inside it that are still unclosed.
+    interpolations: Vec<usize>,
+    keep_comments: bool,
 }
 
 impl Scanner {
@@ -17,39 +29,117 @@ impl Scanner {
             start: 0,
             current: 0,
             line: 1,
+            start_column: 1,
+            column: 1,
+            interpolations: Vec::new(),
+            keep_comments: false,
+        }
+    }
+
+    /// Emits ordinary comments as `COMMENT` tokens instead of discarding them, for
+    /// tools that must reproduce the source. The parser doesn't accept them.
+    pub fn with_comments(self) -> Self {
+        Self {
+            keep_comments: true,
+            ..self
         }
     }
 
-    pub fn scan_tokens(&mut self) -> Vec<Token> {
+    pub fn scan_tokens(&mut self) -> Result<Vec<Token>, Box<dyn Error>> {
         while !self.is_at_end() {
             self.start = self.current;
-            self.scan_token();
+            self.start_column = self.column;
+            self.scan_token()?;
         }
 
-        self.tokens
-            .push(Token::new(TokenType::EOF, "".to_string(), None, self.line));
+        if !self.interpolations.is_empty() {
+            return Err(self.error("Unterminated string interpolation."));
+        }
+
+        self.tokens.push(
+            Token::new(TokenType::EOF, "".to_string(), None, self.line).with_column(self.column),
+        );
 
-        self.tokens.clone()
+        Ok(std::mem::take(&mut self.tokens))
     }
 
     fn is_at_end(&self) -> bool {
-        self.current >= self.source.len() as i64
+        self.current >= self.source.len()
     }
 
-    fn scan_token(&mut self) {
+    fn scan_token(&mut self) -> Result<(), Box<dyn Error>> {
         let ch: char = self.advance();
         use TokenType::*;
-        let token_type: Result<Option<TokenType>, Option<()>> = match ch {
+        let token_type: Result<Option<TokenType>, Box<dyn Error>> = match ch {
             '(' => Ok(Some(LEFT_PAREN)),
             ')' => Ok(Some(RIGHT_PAREN)),
-            '{' => Ok(Some(LEFT_BRACE)),
-            '}' => Ok(Some(RIGHT_BRACE)),
+            '{' => {
+                if let Some(depth) = self.interpolations.last_mut() {
+                    *depth += 1;
+                }
+                Ok(Some(LEFT_BRACE))
+            }
+            '}' => match self.interpolations.last_mut() {
+                Some(0) => {
+                    self.interpolations.pop();
+                    self.string().map(|_| None)
+                }
+                Some(depth) => {
+                    *depth -= 1;
+                    Ok(Some(RIGHT_BRACE))
+                }
+                None => Ok(Some(RIGHT_BRACE)),
+            },
             ',' => Ok(Some(COMMA)),
             '.' => Ok(Some(DOT)),
-            '-' => Ok(Some(MINUS)),
-            '+' => Ok(Some(PLUS)),
+            '-' => {
+                if self.peek() == '-' && self.is_increment() {
+                    self.advance();
+                    Ok(Some(MINUS_MINUS))
+                } else if self.match_('=') {
+                    Ok(Some(MINUS_EQUAL))
+                } else {
+                    Ok(Some(MINUS))
+                }
+            }
+            '+' => {
+                if self.peek() == '+' && self.is_increment() {
+                    self.advance();
+                    Ok(Some(PLUS_PLUS))
+                } else if self.match_('=') {
+                    Ok(Some(PLUS_EQUAL))
+                } else {
+                    Ok(Some(PLUS))
+                }
+            }
             ';' => Ok(Some(SEMICOLON)),
-            '*' => Ok(Some(STAR)),
+            '*' => {
+                if self.match_('*') {
+                    Ok(Some(STAR_STAR))
+                } else if self.match_('=') {
+                    Ok(Some(STAR_EQUAL))
+                } else {
+                    Ok(Some(STAR))
+                }
+            }
+            '%' => {
+                if self.match_('=') {
+                    Ok(Some(PERCENT_EQUAL))
+                } else {
+                    Ok(Some(PERCENT))
+                }
+            }
+            '&' => Ok(Some(AMPERSAND)),
+            '|' => Ok(Some(PIPE)),
+            '^' => Ok(Some(CARET)),
+            ':' => Ok(Some(COLON)),
+            '?' => {
+                if self.match_('?') {
+                    Ok(Some(QUESTION_QUESTION))
+                } else {
+                    Ok(Some(QUESTION))
+                }
+            }
             '!' => {
                 if self.match_('=') {
                     Ok(Some(BANG_EQUAL))
@@ -67,6 +157,8 @@ impl Scanner {
             '<' => {
                 if self.match_('=') {
                     Ok(Some(LESS_EQUAL))
+                } else if self.match_('<') {
+                    Ok(Some(LESS_LESS))
                 } else {
                     Ok(Some(LESS))
                 }
@@ -74,16 +166,30 @@ impl Scanner {
             '>' => {
                 if self.match_('=') {
                     Ok(Some(GREATER_EQUAL))
+                } else if self.match_('>') {
+                    Ok(Some(GREATER_GREATER))
                 } else {
                     Ok(Some(GREATER))
                 }
             }
             '/' => {
                 if self.match_('/') {
+                    // `///` starts a doc comment, but `////` is an ordinary comment.
+                    let is_doc = self.peek() == '/' && self.peek_next() != '/';
                     while self.peek() != '\n' && !self.is_at_end() {
                         self.advance();
                     }
-                    Ok(None)
+                    if is_doc {
+                        self.doc_comment();
+                        Ok(None)
+                    } else {
+                        Ok(self.keep_comments.then_some(COMMENT))
+                    }
+                } else if self.match_('*') {
+                    self.block_comment()?;
+                    Ok(self.keep_comments.then_some(COMMENT))
+                } else if self.match_('=') {
+                    Ok(Some(SLASH_EQUAL))
                 } else {
                     Ok(Some(SLASH))
                 }
@@ -93,141 +199,313 @@ impl Scanner {
                 Ok(None)
             }
             ' ' | '\r' | '\t' => Ok(None),
-            '"' => {
-                if let Err(_) = self.string() {
-                    Err(None)
-                } else {
-                    Ok(None)
-                }
-            }
-            ch if Self::is_digit(ch) => {
-                self.number();
-                Ok(None)
-            }
+            '"' => self.string().map(|_| None),
+            ch if Self::is_digit(ch) => self.number(ch).map(|_| None),
             ch if Self::is_alpha(ch) => {
                 self.identifier();
                 Ok(None)
             }
-            _ => Err(None),
+            _ => Err(self.error("Unexpected character.")),
         };
 
-        match token_type {
-            Ok(Some(tt)) => self.add_token(tt),
-            Ok(None) => {}
-            Err(_) => panic!("Error: Invalid Token; Line: {}", self.line),
+        if let Some(tt) = token_type? {
+            self.add_token(tt);
         }
+        Ok(())
     }
 
     fn advance(&mut self) -> char {
-        let ch = self.source.chars().nth(self.current as usize).unwrap();
-        self.current += 1;
-
+        let ch = self.peek();
+        self.step(ch);
         ch
     }
 
+    fn step(&mut self, ch: char) {
+        self.current += ch.len_utf8();
+        self.column = if ch == '\n' { 1 } else { self.column + 1 };
+    }
+
     fn add_token(&mut self, type_: TokenType) {
         self.add_token_(type_, None);
     }
 
     fn add_token_(&mut self, type_: TokenType, literal: Option<Object>) {
-        if let Some(text) = self
-            .source
-            .get((self.start as usize)..(self.current as usize))
-        {
-            self.tokens
-                .push(Token::new(type_, text.to_string(), literal, self.line));
-        } else {
-            panic!(
-                "Error: while adding token; File: scanner.rs; Line: {}",
-                line!()
-            );
-        }
+        let text = self.source[self.start..self.current].to_string();
+        self.tokens
+            .push(Token::new(type_, text, literal, self.line).with_column(self.start_column));
     }
 
     fn match_(&mut self, ch: char) -> bool {
-        if self.is_at_end() {
-            return false;
-        }
-        if self.source.chars().nth(self.current as usize) != Some(ch) {
+        if self.is_at_end() || self.peek() != ch {
             return false;
         }
 
-        self.current += 1;
+        self.step(ch);
         true
     }
 
-    fn peek(&self) -> char {
-        if self.is_at_end() {
-            '\0'
-        } else {
-            self.source.chars().nth(self.current as usize).unwrap()
+    /// Whether the doubled `-` or `+` starting at the previous character is a decrement
+    /// or increment rather than two signs. Only a variable or property can be
+    /// incremented, so `a--` and `--a` are, but `1--1` and `a--b` subtract a negation.
+    fn is_increment(&self) -> bool {
+        let after = self.source[self.current + 1..].trim_start_matches([' ', '\t']);
+        let next = after.chars().next().unwrap_or('\0');
+        let starts_name = Self::is_alpha(next);
+        let starts_operand = starts_name || Self::is_digit(next) || matches!(next, '(' | '"' | '!');
+
+        let previous = self
+            .tokens
+            .iter()
+            .rev()
+            .find(|token| !matches!(token.type_, TokenType::COMMENT | TokenType::DOC_COMMENT));
+        match previous.map(|token| &token.type_) {
+            Some(TokenType::IDENTIFIER) => !starts_operand,
+            Some(
+                TokenType::NUMBER
+                | TokenType::STRING
+                | TokenType::TRUE
+                | TokenType::FALSE
+                | TokenType::NIL
+                | TokenType::THIS
+                | TokenType::SUPER
+                | TokenType::RIGHT_PAREN,
+            ) => false,
+            _ => starts_name,
         }
     }
 
-    fn string(&mut self) -> Result<(), ()> {
-        while self.peek() != '"' && !self.is_at_end() {
-            if self.peek() == '\n' {
+    fn peek(&self) -> char {
+        self.source[self.current..].chars().next().unwrap_or('\0')
+    }
+
+    fn doc_comment(&mut self) {
+        let text = &self.source[self.start + 3..self.current];
+        let text = text
+            .strip_prefix(' ')
+            .unwrap_or(text)
+            .trim_end()
+            .to_string();
+        self.add_token_(TokenType::DOC_COMMENT, Some(Object::String(text)));
+    }
+
+    /// Skips a `/* ... */` comment, which may contain nested block comments.
+    fn block_comment(&mut self) -> Result<(), Box<dyn Error>> {
+        let start_line = self.line;
+        let mut depth = 1;
+
+        while depth > 0 {
+            if self.is_at_end() {
+                // Point the diagnostic at the opening `/*` rather than the whole comment.
+                self.line = start_line;
+                self.current = self.start + 2;
+                return Err(self.error("Unterminated block comment."));
+            }
+
+            if self.peek() == '/' && self.peek_next() == '*' {
+                self.advance();
+                self.advance();
+                depth += 1;
+            } else if self.peek() == '*' && self.peek_next() == '/' {
+                self.advance();
+                self.advance();
+                depth -= 1;
+            } else if self.advance() == '\n' {
                 self.line += 1;
             }
-            self.advance();
         }
 
-        if self.is_at_end() {
-            eprintln!("Line: {}; Message: Unterminated string;", self.line);
-            return Err(());
-        }
+        Ok(())
+    }
 
-        self.advance();
+    /// Scans a string segment up to the closing quote or the next `${`.
+    /// The segment either starts at an opening quote or at the `}` that closes an interpolation.
+    fn string(&mut self) -> Result<(), Box<dyn Error>> {
+        let mut value = String::new();
+
+        loop {
+            if self.is_at_end() {
+                return Err(self.error("Unterminated string."));
+            }
+
+            match self.advance() {
+                '"' => break,
+                '\\' => value.push(self.escape()?),
+                '$' if self.peek() == '{' => {
+                    self.advance();
+                    self.add_token_(TokenType::INTERPOLATION, Some(Object::String(value)));
+                    self.interpolations.push(0);
+                    return Ok(());
+                }
+                ch => {
+                    if ch == '\n' {
+                        self.line += 1;
+                    }
+                    value.push(ch);
+                }
+            }
+        }
 
-        let value = self
-            .source
-            .get(((self.start + 1) as usize)..((self.current - 1) as usize))
-            .unwrap()
-            .to_string();
         self.add_token_(TokenType::STRING, Some(Object::String(value)));
         Ok(())
     }
 
+    fn escape(&mut self) -> Result<char, Box<dyn Error>> {
+        if self.is_at_end() {
+            return Err(self.error("Unterminated string."));
+        }
+
+        match self.advance() {
+            'n' => Ok('\n'),
+            't' => Ok('\t'),
+            'r' => Ok('\r'),
+            '0' => Ok('\0'),
+            '\\' => Ok('\\'),
+            '"' => Ok('"'),
+            '$' => Ok('$'),
+            'u' => self.unicode_escape(),
+            ch => Err(self.error(&format!("Invalid escape sequence '\\{}'.", ch))),
+        }
+    }
+
+    /// Scans the `{XXXX}` part of a `\u{XXXX}` escape: one to six hex digits naming a code point.
+    fn unicode_escape(&mut self) -> Result<char, Box<dyn Error>> {
+        if !self.match_('{') {
+            return Err(self.error("Expect '{' after '\\u'."));
+        }
+
+        let mut digits = String::new();
+        while self.peek().is_ascii_hexdigit() && digits.len() < 6 {
+            digits.push(self.advance());
+        }
+
+        if digits.is_empty() || !self.match_('}') {
+            return Err(self.error("Invalid unicode escape sequence."));
+        }
+
+        u32::from_str_radix(&digits, 16)
+            .ok()
+            .and_then(char::from_u32)
+            .ok_or_else(|| self.error(&format!("Invalid unicode code point '{}'.", digits)))
+    }
+
+    fn error(&self, message: &str) -> Box<dyn Error> {
+        let lexeme = self.source[self.start..self.current].to_string();
+        Box::new(
+            LoxError::new()
+                .type_(Box::new(ScanError))
+                .at_token(
+                    Token::new(TokenType::ERROR, lexeme, None, self.line)
+                        .with_column(self.start_column),
+                )
+                .message(message.to_string()),
+        )
+    }
+
     fn is_digit(ch: char) -> bool {
-        ch >= '0' && ch <= '9'
+        ch.is_ascii_digit()
     }
 
-    fn number(&mut self) {
-        while Self::is_digit(self.peek()) {
-            self.advance();
+    /// Scans a number literal whose first digit has already been consumed:
+    /// `0xFF`, `0b1010`, `1_000_000`, `3.14` or `1.5e-3`.
+    fn number(&mut self, first: char) -> Result<(), Box<dyn Error>> {
+        if first == '0' && matches!(self.peek(), 'x' | 'X' | 'b' | 'B') {
+            let radix = if self.advance().eq_ignore_ascii_case(&'x') {
+                16
+            } else {
+                2
+            };
+            let digits = self.digits(radix, false)?;
+            if digits.is_empty() {
+                return Err(self.error("Expect digits after number prefix."));
+            }
+            self.end_of_number()?;
+
+            // Hex and binary literals are bit patterns, so the full 64 bits are
+            // allowed and wrap to negative values.
+            let value = u64::from_str_radix(&digits, radix)
+                .map_err(|_| self.error("Number literal is too large."))?;
+            self.add_token_(TokenType::NUMBER, Some(Object::Int(value as i64)));
+            return Ok(());
         }
 
+        let mut text = String::from(first);
+        text.push_str(&self.digits(10, true)?);
+        let mut is_float = false;
+
         if self.peek() == '.' && Self::is_digit(self.peek_next()) {
-            self.advance();
-            while Self::is_digit(self.peek()) {
-                self.advance();
+            is_float = true;
+            text.push(self.advance());
+            text.push_str(&self.digits(10, false)?);
+        }
+
+        if matches!(self.peek(), 'e' | 'E') {
+            is_float = true;
+            text.push(self.advance());
+            if matches!(self.peek(), '+' | '-') {
+                text.push(self.advance());
             }
+            let exponent = self.digits(10, false)?;
+            if exponent.is_empty() {
+                return Err(self.error("Expect digits in exponent."));
+            }
+            text.push_str(&exponent);
+        }
+        self.end_of_number()?;
+
+        // A whole number that doesn't fit in an `Int` becomes a float.
+        if let (false, Ok(value)) = (is_float, text.parse::<i64>()) {
+            self.add_token_(TokenType::NUMBER, Some(Object::Int(value)));
+            return Ok(());
         }
 
-        let double_str = self
-            .source
-            .get(self.start as usize..self.current as usize)
-            .unwrap();
-        let double = double_str.parse::<f64>().unwrap();
+        let double = text
+            .parse::<f64>()
+            .map_err(|_| self.error("Invalid number literal."))?;
         self.add_token_(TokenType::NUMBER, Some(Object::Number(double)));
+        Ok(())
     }
 
-    fn peek_next(&self) -> char {
-        if self.current + 1 >= self.source.len() as i64 {
-            return '\0';
+    /// Consumes digits in `radix`, which may be separated by single underscores,
+    /// and returns them with the underscores removed.
+    fn digits(&mut self, radix: u32, mut after_digit: bool) -> Result<String, Box<dyn Error>> {
+        let mut digits = String::new();
+
+        loop {
+            let ch = self.peek();
+            if ch.is_digit(radix) {
+                digits.push(self.advance());
+                after_digit = true;
+            } else if ch == '_' {
+                self.advance();
+                if !after_digit || !self.peek().is_digit(radix) {
+                    return Err(self.error("Underscores in numbers must separate digits."));
+                }
+            } else {
+                return Ok(digits);
+            }
+        }
+    }
+
+    fn end_of_number(&mut self) -> Result<(), Box<dyn Error>> {
+        if Self::is_alphanumeric(self.peek()) {
+            self.advance();
+            return Err(self.error("Invalid character in number literal."));
         }
-        self.source
-            .chars()
-            .nth((self.current + 1) as usize)
-            .unwrap()
+        Ok(())
+    }
+
+    fn peek_next(&self) -> char {
+        let mut chars = self.source[self.current..].chars();
+        chars.next();
+        chars.next().unwrap_or('\0')
     }
 
     fn is_alpha(ch: char) -> bool {
-        (ch >= 'a' && ch <= 'z') || (ch >= 'A' && ch <= 'Z') || ch == '_'
+        ch.is_alphabetic() || ch == '_'
     }
 
     fn is_alphanumeric(ch: char) -> bool {
-        Self::is_alpha(ch) || Self::is_digit(ch)
+        Self::is_alpha(ch) || ch.is_alphanumeric()
     }
 
     fn identifier(&mut self) {
@@ -235,10 +513,7 @@ impl Scanner {
             self.advance();
         }
 
-        let text = self
-            .source
-            .get(self.start as usize..self.current as usize)
-            .unwrap();
+        let text = &self.source[self.start..self.current];
         let token_type = self.keyword(text);
         if [TokenType::TRUE, TokenType::FALSE].contains(&token_type) {
             self.add_token_(token_type, Some(Object::Boolean(text == "true")));
@@ -252,19 +527,28 @@ impl Scanner {
     fn keyword(&self, text: &str) -> TokenType {
         match text {
             "and" => TokenType::AND,
+            "as" => TokenType::AS,
+            "break" => TokenType::BREAK,
+            "catch" => TokenType::CATCH,
             "class" => TokenType::CLASS,
+            "continue" => TokenType::CONTINUE,
+            "div" => TokenType::DIV,
             "else" => TokenType::ELSE,
             "false" => TokenType::FALSE,
+            "finally" => TokenType::FINALLY,
             "for" => TokenType::FOR,
             "fun" => TokenType::FUN,
             "if" => TokenType::IF,
+            "import" => TokenType::IMPORT,
             "nil" => TokenType::NIL,
             "or" => TokenType::OR,
             "print" => TokenType::PRINT,
             "return" => TokenType::RETURN,
             "super" => TokenType::SUPER,
             "this" => TokenType::THIS,
+            "throw" => TokenType::THROW,
             "true" => TokenType::TRUE,
+            "try" => TokenType::TRY,
             "var" => TokenType::VAR,
             "while" => TokenType::WHILE,
             _ => TokenType::IDENTIFIER,
@@ -1,211 +1,374 @@
-use crate::expr::{self, Expr};
+use crate::expr::{self, next_id, Expr};
 use crate::token::Token;
-use std::cell::RefCell;
-use std::error::Error;
-use std::fmt::Debug;
 use std::rc::Rc;
 
-pub trait Stmt: Debug {
-    fn accept(&mut self, visitor: &mut dyn Visitor) -> Result<(), Box<dyn Error>>;
+#[derive(Debug, Clone)]
+pub enum Stmt {
+    Block(Block),
+    Break(Break),
+    Class(Class),
+    Continue(Continue),
+    Expression(Expression),
+    // Shared so that every closure created from a declaration reuses its body.
+    Function(Rc<Function>),
+    If(If),
+    Import(Import),
+    Print(Print),
+    Return(Return),
+    Throw(Throw),
+    Try(Try),
+    Var(Var),
+    While(While),
+}
+
+impl Stmt {
+    pub fn accept<R>(&self, visitor: &mut dyn Visitor<R>) -> R {
+        match self {
+            Stmt::Block(stmt) => visitor.visit_block_stmt(stmt),
+            Stmt::Break(stmt) => visitor.visit_break_stmt(stmt),
+            Stmt::Class(stmt) => visitor.visit_class_stmt(stmt),
+            Stmt::Continue(stmt) => visitor.visit_continue_stmt(stmt),
+            Stmt::Expression(stmt) => visitor.visit_expr_stmt(stmt),
+            Stmt::Function(stmt) => visitor.visit_func_stmt(stmt),
+            Stmt::If(stmt) => visitor.visit_if_stmt(stmt),
+            Stmt::Import(stmt) => visitor.visit_import_stmt(stmt),
+            Stmt::Print(stmt) => visitor.visit_print_stmt(stmt),
+            Stmt::Return(stmt) => visitor.visit_return_stmt(stmt),
+            Stmt::Throw(stmt) => visitor.visit_throw_stmt(stmt),
+            Stmt::Try(stmt) => visitor.visit_try_stmt(stmt),
+            Stmt::Var(stmt) => visitor.visit_var_stmt(stmt),
+            Stmt::While(stmt) => visitor.visit_while_stmt(stmt),
+        }
+    }
+
+    /// The statement's node id, unique among the statements of every parsed program.
+    pub fn id(&self) -> usize {
+        match self {
+            Stmt::Block(stmt) => stmt.id,
+            Stmt::Break(stmt) => stmt.id,
+            Stmt::Class(stmt) => stmt.id,
+            Stmt::Continue(stmt) => stmt.id,
+            Stmt::Expression(stmt) => stmt.id,
+            Stmt::Function(stmt) => stmt.id,
+            Stmt::If(stmt) => stmt.id,
+            Stmt::Import(stmt) => stmt.id,
+            Stmt::Print(stmt) => stmt.id,
+            Stmt::Return(stmt) => stmt.id,
+            Stmt::Throw(stmt) => stmt.id,
+            Stmt::Try(stmt) => stmt.id,
+            Stmt::Var(stmt) => stmt.id,
+            Stmt::While(stmt) => stmt.id,
+        }
+    }
+
+    /// The line the statement starts on. Blocks and classes have none, and neither do
+    /// expression statements made only of literals.
+    pub fn line(&self) -> Option<i64> {
+        match self {
+            Stmt::Block(_) | Stmt::Class(_) => None,
+            Stmt::Break(stmt) => Some(stmt.keyword.line),
+            Stmt::Continue(stmt) => Some(stmt.keyword.line),
+            Stmt::Expression(stmt) => stmt.expression.line(),
+            Stmt::Function(stmt) => Some(stmt.name.line),
+            Stmt::If(stmt) => Some(stmt.keyword.line),
+            Stmt::Import(stmt) => Some(stmt.path.line),
+            Stmt::Print(stmt) => Some(stmt.keyword.line),
+            Stmt::Return(stmt) => Some(stmt.keyword.line),
+            Stmt::Throw(stmt) => Some(stmt.keyword.line),
+            Stmt::Try(stmt) => Some(stmt.keyword.line),
+            Stmt::Var(stmt) => Some(stmt.name.line),
+            Stmt::While(stmt) => Some(stmt.keyword.line),
+        }
+    }
 }
 
-pub trait Visitor {
-    fn visit_block_stmt(&mut self, stmt: &mut Block) -> Result<(), Box<dyn Error>>;
-    fn visit_class_stmt(&self, stmt: &Class) -> Result<(), Box<dyn Error>>;
-    fn visit_expr_stmt(&mut self, stmt: &mut Expression) -> Result<(), Box<dyn Error>>;
-    fn visit_func_stmt(&mut self, stmt: &Function) -> Result<(), Box<dyn Error>>;
-    fn visit_if_stmt(&mut self, stmt: &mut If) -> Result<(), Box<dyn Error>>;
-    fn visit_print_stmt(&mut self, stmt: &mut Print) -> Result<(), Box<dyn Error>>;
-    fn visit_return_stmt(&mut self, stmt: &Return) -> Result<(), Box<dyn Error>>;
-    fn visit_var_stmt(&mut self, stmt: &mut Var) -> Result<(), Box<dyn Error>>;
-    fn visit_while_stmt(&mut self, stmt: &While) -> Result<(), Box<dyn Error>>;
+pub trait Visitor<R> {
+    fn visit_block_stmt(&mut self, stmt: &Block) -> R;
+    fn visit_break_stmt(&mut self, stmt: &Break) -> R;
+    fn visit_class_stmt(&mut self, stmt: &Class) -> R;
+    fn visit_continue_stmt(&mut self, stmt: &Continue) -> R;
+    fn visit_expr_stmt(&mut self, stmt: &Expression) -> R;
+    fn visit_func_stmt(&mut self, stmt: &Rc<Function>) -> R;
+    fn visit_if_stmt(&mut self, stmt: &If) -> R;
+    fn visit_import_stmt(&mut self, stmt: &Import) -> R;
+    fn visit_print_stmt(&mut self, stmt: &Print) -> R;
+    fn visit_return_stmt(&mut self, stmt: &Return) -> R;
+    fn visit_throw_stmt(&mut self, stmt: &Throw) -> R;
+    fn visit_try_stmt(&mut self, stmt: &Try) -> R;
+    fn visit_var_stmt(&mut self, stmt: &Var) -> R;
+    fn visit_while_stmt(&mut self, stmt: &While) -> R;
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Block {
-    pub statements: Vec<Rc<RefCell<Box<dyn Stmt>>>>,
+    pub id: usize,
+    pub statements: Vec<Stmt>,
 }
 
 impl Block {
-    pub fn new(statements: Vec<Rc<RefCell<Box<dyn Stmt>>>>) -> Self {
+    pub fn new(statements: Vec<Stmt>) -> Self {
         Self {
-            statements: statements,
+            id: next_id(),
+            statements,
         }
     }
 }
 
-impl Stmt for Block {
-    fn accept(&mut self, visitor: &mut dyn Visitor) -> Result<(), Box<dyn Error>> {
-        return visitor.visit_block_stmt(self);
+#[derive(Debug, Clone)]
+pub struct Break {
+    pub id: usize,
+    pub keyword: Token,
+}
+
+impl Break {
+    pub fn new(keyword: Token) -> Self {
+        Self {
+            id: next_id(),
+            keyword,
+        }
     }
 }
 
-#[derive(Debug)]
+#[allow(dead_code)]
+#[derive(Debug, Clone)]
 pub struct Class {
+    pub id: usize,
     name: Token,
     superclass: expr::Variable,
-    methods: Vec<Function>,
+    methods: Vec<Rc<Function>>,
+    doc: Option<String>,
 }
 
+#[allow(dead_code)]
 impl Class {
-    fn new(name: Token, superclass: expr::Variable, methods: Vec<Function>) -> Self {
+    fn new(
+        name: Token,
+        superclass: expr::Variable,
+        methods: Vec<Rc<Function>>,
+        doc: Option<String>,
+    ) -> Self {
         Self {
+            id: next_id(),
             name,
             superclass,
             methods,
+            doc,
         }
     }
 }
 
-impl Stmt for Class {
-    fn accept(&mut self, visitor: &mut dyn Visitor) -> Result<(), Box<dyn Error>> {
-        return visitor.visit_class_stmt(self);
+#[derive(Debug, Clone)]
+pub struct Continue {
+    pub id: usize,
+    pub keyword: Token,
+}
+
+impl Continue {
+    pub fn new(keyword: Token) -> Self {
+        Self {
+            id: next_id(),
+            keyword,
+        }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Expression {
-    pub expression: Rc<RefCell<Box<dyn expr::Expr>>>,
+    pub id: usize,
+    pub expression: Expr,
 }
 
 impl Expression {
-    pub fn new(expression: Box<dyn Expr>) -> Self {
+    pub fn new(expression: Expr) -> Self {
         Self {
-            expression: Rc::new(RefCell::new(expression)),
+            id: next_id(),
+            expression,
         }
     }
 }
 
-impl Stmt for Expression {
-    fn accept(&mut self, visitor: &mut dyn Visitor) -> Result<(), Box<dyn Error>> {
-        return visitor.visit_expr_stmt(self);
-    }
-}
-
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub struct Function {
+    pub id: usize,
     pub name: Token,
     pub params: Vec<Token>,
-    pub body: Vec<Rc<RefCell<Box<dyn Stmt>>>>,
+    pub body: Rc<[Stmt]>,
+    pub doc: Option<String>,
 }
 
 impl Function {
-    pub fn new(name: Token, params: Vec<Token>, body: Vec<Rc<RefCell<Box<dyn Stmt>>>>) -> Self {
-        Self { name, params, body }
-    }
-}
-
-impl Stmt for Function {
-    fn accept(&mut self, visitor: &mut dyn Visitor) -> Result<(), Box<dyn Error>> {
-        return visitor.visit_func_stmt(self);
+    pub fn new(name: Token, params: Vec<Token>, body: Vec<Stmt>, doc: Option<String>) -> Self {
+        Self {
+            id: next_id(),
+            name,
+            params,
+            body: body.into(),
+            doc,
+        }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct If {
-    pub condition: Rc<RefCell<Box<dyn expr::Expr>>>,
-    pub then_branch: Rc<RefCell<Box<dyn Stmt>>>,
-    pub else_branch: Option<Rc<RefCell<Box<dyn Stmt>>>>,
+    pub id: usize,
+    pub keyword: Token,
+    pub condition: Expr,
+    pub then_branch: Box<Stmt>,
+    pub else_branch: Option<Box<Stmt>>,
 }
 
 impl If {
     pub fn new(
-        condition: Box<dyn expr::Expr>,
-        then_branch: Box<dyn Stmt>,
-        else_branch: Option<Box<dyn Stmt>>,
+        keyword: Token,
+        condition: Expr,
+        then_branch: Stmt,
+        else_branch: Option<Stmt>,
     ) -> Self {
         Self {
-            condition: Rc::new(RefCell::new(condition)),
-            then_branch: Rc::new(RefCell::new(then_branch)),
-            else_branch: else_branch.map(|stmt| Rc::new(RefCell::new(stmt))),
+            id: next_id(),
+            keyword,
+            condition,
+            then_branch: Box::new(then_branch),
+            else_branch: else_branch.map(Box::new),
         }
     }
 }
 
-impl Stmt for If {
-    fn accept(&mut self, visitor: &mut dyn Visitor) -> Result<(), Box<dyn Error>> {
-        return visitor.visit_if_stmt(self);
+/// `import "path" as name;` runs another file once and binds its top-level
+/// declarations to `name` as a namespace.
+#[derive(Debug, Clone)]
+pub struct Import {
+    pub id: usize,
+    pub path: Token,
+    pub name: Token,
+}
+
+impl Import {
+    pub fn new(path: Token, name: Token) -> Self {
+        Self {
+            id: next_id(),
+            path,
+            name,
+        }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Print {
-    pub expression: Rc<RefCell<Box<dyn expr::Expr>>>,
+    pub id: usize,
+    pub keyword: Token,
+    pub expression: Expr,
 }
 
 impl Print {
-    pub fn new(expression: Box<dyn expr::Expr>) -> Self {
+    pub fn new(keyword: Token, expression: Expr) -> Self {
         Self {
-            expression: Rc::new(RefCell::new(expression)),
+            id: next_id(),
+            keyword,
+            expression,
         }
     }
 }
 
-impl Stmt for Print {
-    fn accept(&mut self, visitor: &mut dyn Visitor) -> Result<(), Box<dyn Error>> {
-        return visitor.visit_print_stmt(self);
+#[derive(Debug, Clone)]
+pub struct Return {
+    pub id: usize,
+    pub keyword: Token,
+    pub value: Option<Expr>,
+}
+
+impl Return {
+    pub fn new(keyword: Token, value: Option<Expr>) -> Self {
+        Self {
+            id: next_id(),
+            keyword,
+            value,
+        }
     }
 }
 
-#[derive(Debug)]
-pub struct Return {
+#[derive(Debug, Clone)]
+pub struct Throw {
+    pub id: usize,
     pub keyword: Token,
-    pub value: Option<Rc<RefCell<Box<dyn expr::Expr>>>>,
+    pub value: Expr,
 }
 
-impl Return {
-    pub fn new(keyword: Token, value: Option<Box<dyn Expr>>) -> Self {
+impl Throw {
+    pub fn new(keyword: Token, value: Expr) -> Self {
         Self {
+            id: next_id(),
             keyword,
-            value: value.map(|expr| Rc::new(RefCell::new(expr))),
+            value,
         }
     }
 }
 
-impl Stmt for Return {
-    fn accept(&mut self, visitor: &mut dyn Visitor) -> Result<(), Box<dyn Error>> {
-        return visitor.visit_return_stmt(self);
+/// `try { } catch (name) { } finally { }`; at least one of the clauses is present.
+#[derive(Debug, Clone)]
+pub struct Try {
+    pub id: usize,
+    pub keyword: Token,
+    pub body: Vec<Stmt>,
+    pub catch: Option<(Token, Vec<Stmt>)>,
+    pub finally: Option<Vec<Stmt>>,
+}
+
+impl Try {
+    pub fn new(
+        keyword: Token,
+        body: Vec<Stmt>,
+        catch: Option<(Token, Vec<Stmt>)>,
+        finally: Option<Vec<Stmt>>,
+    ) -> Self {
+        Self {
+            id: next_id(),
+            keyword,
+            body,
+            catch,
+            finally,
+        }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Var {
+    pub id: usize,
     pub name: Token,
-    pub initializer: Option<Rc<RefCell<Box<dyn expr::Expr>>>>,
+    pub initializer: Option<Expr>,
+    pub doc: Option<String>,
 }
 
 impl Var {
-    pub fn new(name: Token, initializer: Option<Box<dyn Expr>>) -> Self {
+    pub fn new(name: Token, initializer: Option<Expr>, doc: Option<String>) -> Self {
         Self {
+            id: next_id(),
             name,
-            initializer: initializer.map(|init| Rc::new(RefCell::new(init))),
+            initializer,
+            doc,
         }
     }
 }
 
-impl Stmt for Var {
-    fn accept(&mut self, visitor: &mut dyn Visitor) -> Result<(), Box<dyn Error>> {
-        return visitor.visit_var_stmt(self);
-    }
-}
-
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct While {
-    pub condition: Rc<RefCell<Box<dyn expr::Expr>>>,
-    pub body: Rc<RefCell<Box<dyn Stmt>>>,
+    pub id: usize,
+    // `while`, or `for` for a desugared `for` loop.
+    pub keyword: Token,
+    pub condition: Expr,
+    pub body: Box<Stmt>,
+    // A desugared `for` loop's increment, run after the body even on `continue`.
+    pub increment: Option<Expr>,
 }
 
 impl While {
-    pub fn new(condition: Box<dyn Expr>, body: Box<dyn Stmt>) -> Self {
+    pub fn new(keyword: Token, condition: Expr, body: Stmt, increment: Option<Expr>) -> Self {
         Self {
-            condition: Rc::new(RefCell::new(condition)),
-            body: Rc::new(RefCell::new(body)),
+            id: next_id(),
+            keyword,
+            condition,
+            body: Box::new(body),
+            increment,
         }
     }
 }
-
-impl Stmt for While {
-    fn accept(&mut self, visitor: &mut dyn Visitor) -> Result<(), Box<dyn Error>> {
-        return visitor.visit_while_stmt(self);
-    }
-}
@@ -0,0 +1,93 @@
+use std::{
+    error::Error,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    interpreter::{sandbox::Limits, Interpreter},
+    parser::Parser,
+    resolver::Resolver,
+    scanner::Scanner,
+    stmt::Stmt,
+};
+
+/// The outcome of one `test_*` function: `Err` holds the error it failed with.
+#[derive(Debug)]
+pub struct TestResult {
+    pub name: String,
+    pub outcome: Result<(), String>,
+}
+
+/// Every `.lox` file under `dir`, in sorted order. A file is taken as is.
+pub fn discover(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    if dir.is_file() {
+        return Ok(vec![dir.to_path_buf()]);
+    }
+
+    let mut files = Vec::new();
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<_>>()?;
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            files.extend(discover(&path)?);
+        } else if path.extension().is_some_and(|ext| ext == "lox") {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+/// The top-level `test_*` functions declared in `source`, in order.
+pub fn test_names(source: &str) -> Result<Vec<String>, Box<dyn Error>> {
+    let tokens = Scanner::new(source.to_string()).scan_tokens()?;
+    let statements = Parser::new(tokens).parse()?;
+    Ok(statements
+        .iter()
+        .filter_map(|stmt| match stmt {
+            Stmt::Function(function) if function.name.lexeme.starts_with("test_") => {
+                Some(function.name.lexeme.clone())
+            }
+            _ => None,
+        })
+        .collect())
+}
+
+/// Runs each test in the file at `path`. Every test gets a fresh interpreter with
+/// `limits` that runs the file's top-level code and then calls the test, so tests
+/// can't affect each other. `Err` means the file couldn't be read or parsed.
+pub fn run_file(path: &Path, limits: &Limits) -> Result<Vec<TestResult>, Box<dyn Error>> {
+    let source = fs::read_to_string(path)?;
+    let names = test_names(&source)?;
+
+    Ok(names
+        .into_iter()
+        .map(|name| {
+            let outcome = run_test(path, &source, &name, limits).map_err(|err| err.to_string());
+            TestResult { name, outcome }
+        })
+        .collect())
+}
+
+fn run_test(path: &Path, source: &str, name: &str, limits: &Limits) -> Result<(), Box<dyn Error>> {
+    let tokens = Scanner::new(source.to_string()).scan_tokens()?;
+    let statements = Parser::new(tokens).parse()?;
+
+    let mut interpreter = Interpreter::new().with_limits(limits.clone());
+    interpreter.set_script_path(path)?;
+    Resolver::new(&mut interpreter).resolve(&statements)?;
+    interpreter.interpret(&statements)?;
+
+    let declaration = statements
+        .iter()
+        .find_map(|stmt| match stmt {
+            Stmt::Function(function) if function.name.lexeme == name => Some(function),
+            _ => None,
+        })
+        .unwrap();
+    let test = interpreter.globals.borrow().get(&declaration.name)?;
+    interpreter.call(test, Some(name), Vec::new(), &declaration.name)?;
+    Ok(())
+}
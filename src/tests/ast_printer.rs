@@ -0,0 +1,72 @@
+use crate::{
+    ast_printer::{AstPrinter, AstStyle},
+    parser::Parser,
+    scanner::Scanner,
+};
+
+fn print(source: &str, style: AstStyle) -> String {
+    let tokens = Scanner::new(source.to_string()).scan_tokens().unwrap();
+    let statements = Parser::new(tokens).parse().unwrap();
+    AstPrinter::print(&statements, style)
+}
+
+fn print_expr(source: &str) -> String {
+    let tokens = Scanner::new(source.to_string()).scan_tokens().unwrap();
+    let expression = Parser::new(tokens).expression().unwrap();
+    AstPrinter::print_expr(&expression, AstStyle::SExpr)
+}
+
+#[test]
+fn test_expression_precedence() {
+    assert_eq!(print_expr("-123 * (45.67)"), "(* (- 123) (group 45.67))");
+    assert_eq!(print_expr("1 + 2 * 3 - 4"), "(- (+ 1 (* 2 3)) 4)");
+    assert_eq!(print_expr("-2 ** 2"), "(- (** 2 2))");
+    assert_eq!(print_expr("a or b and c"), "(or a (and b c))");
+    assert_eq!(print_expr("a ? b : c ? d : e"), "(?: a b (?: c d e))");
+    assert_eq!(print_expr(r#"f(1, "two").x"#), r#"(. x (call f 1 "two"))"#);
+    assert_eq!(print_expr("x += 1"), "(= x (+ x 1))");
+}
+
+#[test]
+fn test_for_loop_desugaring() {
+    assert_eq!(
+        print(
+            "for (var i = 0; i < 3; i = i + 1) print i;",
+            AstStyle::SExpr
+        ),
+        "(block (var i 0) (while (< i 3) (print i) (increment (= i (+ i 1)))))\n"
+    );
+    assert_eq!(
+        print("for (;;) break;", AstStyle::SExpr),
+        "(while true (break))\n"
+    );
+}
+
+#[test]
+fn test_tree_style() {
+    let source = r#"
+    fun add(a, b) { return a + b; }
+    if (add(1, 2) > 2) print "big"; else print nil;
+    "#;
+
+    assert_eq!(
+        print(source, AstStyle::Tree),
+        r#"Function add(a, b)
+  Return
+    Binary +
+      Variable a
+      Variable b
+If
+  Binary >
+    Call
+      Variable add
+      Literal 1
+      Literal 2
+    Literal 2
+  Print
+    Literal "big"
+  Print
+    Literal nil
+"#
+    );
+}
@@ -0,0 +1,149 @@
+use std::{fs, rc::Rc};
+
+use crate::{
+    interpreter::{coverage::Coverage, Interpreter},
+    parser::Parser,
+    resolver::Resolver,
+    scanner::Scanner,
+};
+
+fn cover(source: &str) -> Rc<Coverage> {
+    let coverage = Rc::new(Coverage::new());
+    let mut interpreter = Interpreter::new().with_coverage(coverage.clone());
+
+    let tokens = Scanner::new(source.to_string()).scan_tokens().unwrap();
+    let statements = Parser::new(tokens).parse().unwrap();
+    Resolver::new(&mut interpreter)
+        .resolve(&statements)
+        .unwrap();
+    coverage.register(None, &statements);
+    let _ = interpreter.interpret(&statements);
+    coverage
+}
+
+#[test]
+fn test_statement_coverage() {
+    let coverage = cover(
+        "fun unused() {
+    print 1;
+}
+var a = 1;
+if (a > 0) {
+    a = 2;
+} else {
+    a = 3;
+}
+",
+    );
+    let files = coverage.files();
+    assert_eq!(files.len(), 1);
+    let (path, file) = &files[0];
+    assert_eq!(*path, None);
+    let lines: Vec<(i64, u64)> = file.lines.iter().map(|(l, h)| (*l, *h)).collect();
+    assert_eq!(lines, vec![(1, 1), (2, 0), (4, 1), (5, 1), (6, 1), (8, 0)]);
+    assert_eq!(file.lines_hit(), 4);
+}
+
+#[test]
+fn test_branch_coverage() {
+    let coverage = cover(
+        "var i = 0;
+while (i < 2) i = i + 1;
+if (i == 2) print i;
+var a = nil or i;
+var b = false and i;
+var c = i ?? 0;
+",
+    );
+    let (_, file) = &coverage.files()[0];
+    let branches: Vec<(i64, [u64; 2])> = file.branches.iter().map(|((l, _), h)| (*l, *h)).collect();
+    assert_eq!(
+        branches,
+        vec![
+            (2, [2, 1]),
+            (3, [1, 0]),
+            (4, [0, 1]),
+            (5, [1, 0]),
+            (6, [1, 0])
+        ]
+    );
+    assert_eq!((file.branches_hit(), file.branches_found()), (6, 10));
+}
+
+#[test]
+fn test_lcov_and_summary() {
+    let coverage =
+        cover("var a = true;\nif (a) print 1;\nif (!a) print 2;\nfun f() { return a and a; }\n");
+    assert_eq!(
+        coverage.lcov(),
+        "TN:
+SF:<script>
+BRDA:2,0,0,1
+BRDA:2,0,1,0
+BRDA:3,0,0,0
+BRDA:3,0,1,1
+BRDA:4,0,0,-
+BRDA:4,0,1,-
+BRF:6
+BRH:2
+DA:1,1
+DA:2,2
+DA:3,1
+DA:4,1
+LF:4
+LH:4
+end_of_record
+"
+    );
+    let summary = coverage.summary();
+    let row = summary.lines().nth(1).unwrap();
+    assert_eq!(
+        row.split_whitespace().collect::<Vec<_>>(),
+        vec!["<script>", "4/4", "100.0%", "2/6", "33.3%"]
+    );
+}
+
+#[test]
+fn test_imported_functions_count_in_their_file() {
+    let root = std::env::temp_dir().join(format!("lox-coverage-{}", std::process::id()));
+    fs::create_dir_all(&root).unwrap();
+    fs::write(
+        root.join("lib.lox"),
+        "fun half(n) {\n    if (n > 0) return n / 2;\n    return 0;\n}\n",
+    )
+    .unwrap();
+    let main = root.join("main.lox");
+    fs::write(&main, "import \"lib.lox\" as lib;\nprint lib.half(4);\n").unwrap();
+
+    let coverage = Rc::new(Coverage::new());
+    let mut interpreter = Interpreter::new().with_coverage(coverage.clone());
+    interpreter.set_script_path(&main).unwrap();
+    let tokens = Scanner::new(fs::read_to_string(&main).unwrap())
+        .scan_tokens()
+        .unwrap();
+    let statements = Parser::new(tokens).parse().unwrap();
+    Resolver::new(&mut interpreter)
+        .resolve(&statements)
+        .unwrap();
+    coverage.register(interpreter.script_path.as_deref(), &statements);
+    interpreter.interpret(&statements).unwrap();
+    fs::remove_dir_all(&root).unwrap();
+
+    let files = coverage.files();
+    let names: Vec<String> = files
+        .iter()
+        .map(|(path, _)| {
+            path.as_ref()
+                .unwrap()
+                .file_name()
+                .unwrap()
+                .to_string_lossy()
+                .into_owned()
+        })
+        .collect();
+    assert_eq!(names, vec!["lib.lox", "main.lox"]);
+    let lib: Vec<(i64, u64)> = files[0].1.lines.iter().map(|(l, h)| (*l, *h)).collect();
+    assert_eq!(lib, vec![(1, 1), (2, 2), (3, 0)]);
+    let main: Vec<(i64, u64)> = files[1].1.lines.iter().map(|(l, h)| (*l, *h)).collect();
+    assert_eq!(main, vec![(1, 1), (2, 1)]);
+}
@@ -0,0 +1,223 @@
+use std::{
+    cell::RefCell,
+    error::Error,
+    io::{Cursor, Write},
+    rc::Rc,
+};
+
+use crate::{
+    debugger::{dap, Quit, Terminal},
+    interpreter::Interpreter,
+    json::Json,
+    lsp::read_message,
+    parser::Parser,
+    resolver::Resolver,
+    scanner::Scanner,
+};
+
+const SOURCE: &str = "fun make(step) {
+    var count = 0;
+    fun next() {
+        count = count + step;
+        return count;
+    }
+    return next;
+}
+var counter = make(2);
+counter();
+print counter();
+";
+
+/// A writer the test can read back after handing it to the interpreter.
+#[derive(Clone, Default)]
+struct Output(Rc<RefCell<Vec<u8>>>);
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Output {
+    fn text(&self) -> String {
+        String::from_utf8(self.0.borrow().clone()).unwrap()
+    }
+}
+
+fn debug(source: &str, commands: &str) -> (Result<(), Box<dyn Error>>, String) {
+    let output = Output::default();
+    let terminal = Terminal::new(source, Cursor::new(commands.to_string()), output.clone());
+    let mut interpreter = Interpreter::new().with_debugger(Box::new(terminal));
+
+    let tokens = Scanner::new(source.to_string()).scan_tokens().unwrap();
+    let statements = Parser::new(tokens).parse().unwrap();
+    Resolver::new(&mut interpreter)
+        .resolve(&statements)
+        .unwrap();
+    let result = interpreter.interpret(&statements).map(|_| ());
+    (result, output.text())
+}
+
+/// The lines the debugger paused at, in order.
+fn pauses(output: &str) -> Vec<&str> {
+    output
+        .lines()
+        .filter_map(|line| line.split("Paused at ").nth(1))
+        .collect()
+}
+
+#[test]
+fn test_breakpoints_and_stepping() {
+    let (result, output) = debug(SOURCE, "break 4\nc\nc\nc\n");
+    assert!(result.is_ok());
+    assert_eq!(
+        pauses(&output),
+        vec![
+            "line 1 in <script> (entry).",
+            "line 4 in next (breakpoint).",
+            "line 4 in next (breakpoint).",
+        ]
+    );
+    assert!(output.ends_with("(lox) 4\n"));
+
+    // Over the call to `make`, into `counter()`, then back out to the script.
+    let (_, output) = debug(SOURCE, "n\nn\ns\nf\nc\n");
+    assert_eq!(
+        pauses(&output),
+        vec![
+            "line 1 in <script> (entry).",
+            "line 9 in <script> (step).",
+            "line 10 in <script> (step).",
+            "line 4 in next (step).",
+            "line 11 in <script> (step).",
+        ]
+    );
+}
+
+#[test]
+fn test_breakpoints_in_loops_and_on_shared_lines() {
+    let source = "var i = 0;
+while (i < 3) {
+    i = i + 1;
+}
+if (i == 3) print i;
+";
+    let (result, output) = debug(source, "b 3\nb 5\nc\nc\nc\nc\nc\n");
+    assert!(result.is_ok());
+    // The `print` shares line 5 with the `if` paused at, so it doesn't pause again.
+    assert_eq!(
+        pauses(&output),
+        vec![
+            "line 1 in <script> (entry).",
+            "line 3 in <script> (breakpoint).",
+            "line 3 in <script> (breakpoint).",
+            "line 3 in <script> (breakpoint).",
+            "line 5 in <script> (breakpoint).",
+        ]
+    );
+}
+
+#[test]
+fn test_inspecting_scopes_and_stack() {
+    let (_, output) = debug(SOURCE, "b 5\nc\nbt\nvars\np step\np missing\nq\n");
+    assert!(output.contains("#0 next at line 5\n#1 <script> at line 10\n"));
+    assert!(output.contains(
+        "Locals:\nClosure:\n  count = 2\n  next = <fn next>\n  step = 2\n\
+         Globals:\n  counter = <fn next>\n  make = <fn make>\n"
+    ));
+    assert!(output.contains("step = 2\n(lox) Undefined variable 'missing'."));
+}
+
+#[test]
+fn test_quit_and_detach() {
+    let (result, output) = debug(SOURCE, "q\n");
+    assert!(result.unwrap_err().is::<Quit>());
+    assert!(!output.contains("(lox) 4"));
+
+    // Running out of commands lets the script finish.
+    let (result, output) = debug(SOURCE, "b 4\n");
+    assert!(result.is_ok());
+    assert!(output.ends_with("4\n"));
+}
+
+#[test]
+fn test_dap_session() {
+    let path = std::env::temp_dir().join(format!("lox-dap-{}.lox", std::process::id()));
+    std::fs::write(&path, SOURCE).unwrap();
+
+    let mut input = String::new();
+    for (seq, request) in [
+        r#""command":"initialize","arguments":{"adapterID":"lox"}"#.to_string(),
+        format!(
+            r#""command":"launch","arguments":{{"program":{}}}"#,
+            Json::from(path.display().to_string())
+        ),
+        r#""command":"setBreakpoints","arguments":{"source":{},"breakpoints":[{"line":4}]}"#
+            .to_string(),
+        r#""command":"configurationDone""#.to_string(),
+        r#""command":"stackTrace","arguments":{"threadId":1}"#.to_string(),
+        r#""command":"scopes","arguments":{"frameId":0}"#.to_string(),
+        r#""command":"variables","arguments":{"variablesReference":2}"#.to_string(),
+        r#""command":"continue","arguments":{"threadId":1}"#.to_string(),
+        r#""command":"continue","arguments":{"threadId":1}"#.to_string(),
+        r#""command":"disconnect""#.to_string(),
+    ]
+    .iter()
+    .enumerate()
+    {
+        let body = format!(r#"{{"seq":{},"type":"request",{}}}"#, seq + 1, request);
+        input.push_str(&format!("Content-Length: {}\r\n\r\n{}", body.len(), body));
+    }
+
+    let output = Output::default();
+    dap::serve(Cursor::new(input), output.clone()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let mut reader = Cursor::new(output.text());
+    let mut messages = Vec::new();
+    while let Some(body) = read_message(&mut reader).unwrap() {
+        messages.push(Json::parse(&body).unwrap());
+    }
+    let events: Vec<&str> = messages
+        .iter()
+        .filter_map(|m| m.get("event").and_then(Json::as_str))
+        .collect();
+    assert_eq!(
+        events,
+        vec![
+            "initialized",
+            "stopped",
+            "stopped",
+            "output",
+            "exited",
+            "terminated"
+        ]
+    );
+
+    let body = |command: &str| {
+        messages
+            .iter()
+            .find(|m| m.get("command").and_then(Json::as_str) == Some(command))
+            .and_then(|m| m.get("body"))
+            .unwrap()
+    };
+    let frames = body("stackTrace").get("stackFrames").unwrap();
+    let names: Vec<&str> = frames
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|frame| frame.get("name").unwrap().as_str().unwrap())
+        .collect();
+    assert_eq!(names, vec!["next", "<script>"]);
+
+    // `next` has no locals of its own; the second scope is the `make` call it closed over.
+    let variables = body("variables").get("variables").unwrap().to_string();
+    assert_eq!(
+        variables,
+        r#"[{"name":"count","value":"0","variablesReference":0},{"name":"next","value":"<fn next>","variablesReference":0},{"name":"step","value":"2","variablesReference":0}]"#
+    );
+}
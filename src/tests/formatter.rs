@@ -0,0 +1,153 @@
+use crate::formatter::format;
+
+#[test]
+fn test_format_layout() {
+    let source = r#"
+fun add(a,b){return a+b;}
+var x=-1;var y = - -x;
+for(var i=0;i<3;i++){print "v ${i+1}";}
+for(;;){break;}
+if(x>0){print x;}else if (x<0) print -x; else {}
+try { throw "e"; } catch (e) { print e.message ?? e; } finally { print !true; }
+"#;
+
+    assert_eq!(
+        format(source).unwrap(),
+        r#"fun add(a, b) {
+    return a + b;
+}
+var x = -1;
+var y = - -x;
+for (var i = 0; i < 3; i++) {
+    print "v ${i + 1}";
+}
+for (;;) {
+    break;
+}
+if (x > 0) {
+    print x;
+} else if (x < 0)
+    print -x;
+else {}
+try {
+    throw "e";
+} catch (e) {
+    print e.message ?? e;
+} finally {
+    print !true;
+}
+"#
+    );
+}
+
+#[test]
+fn test_format_indents_unbraced_bodies() {
+    let source = r#"
+if (c) print 1; else print 2;
+while (x) x = x - 1;
+for (var i = 0; i < 2; i++) print i;
+fun f() { if (a) if (b) return 1; else return 2; return 3; }
+if (c) // loop
+  while (x) { x = x - 1; }
+else if (d) for (;;) break;
+else { print 3; }
+if (c) { print 4; } else print 5;
+"#;
+
+    let formatted = format(source).unwrap();
+    assert_eq!(
+        formatted,
+        r#"if (c)
+    print 1;
+else
+    print 2;
+while (x)
+    x = x - 1;
+for (var i = 0; i < 2; i++)
+    print i;
+fun f() {
+    if (a)
+        if (b)
+            return 1;
+        else
+            return 2;
+    return 3;
+}
+if (c) // loop
+    while (x) {
+        x = x - 1;
+    }
+else if (d)
+    for (;;)
+        break;
+else {
+    print 3;
+}
+if (c) {
+    print 4;
+} else
+    print 5;
+"#
+    );
+    assert_eq!(format(&formatted).unwrap(), formatted);
+}
+
+#[test]
+fn test_format_keeps_comments_and_one_blank_line() {
+    let source = r#"
+
+// Leading comment.
+
+
+
+/// Adds one.
+fun inc(n) { // trailing
+    /* block */ return n + 1;
+
+}
+"#;
+
+    assert_eq!(
+        format(source).unwrap(),
+        r#"// Leading comment.
+
+/// Adds one.
+fun inc(n) { // trailing
+    /* block */ return n + 1;
+}
+"#
+    );
+}
+
+#[test]
+fn test_format_is_idempotent() {
+    let source = r#"
+var a = 1; // one
+fun f(x) {
+  while (x > 0) { x -= 1; if (x == 2) continue; }
+  return x ** 2 div 3;
+}
+print f(a) + f(-a) * (a - -1);
+{
+    print a /* inline */ + 1;
+    var u = 1 // c
+    ;
+    var v = a + // c
+    2;
+}
+"#;
+
+    let once = format(source).unwrap();
+    assert_eq!(format(&once).unwrap(), once);
+    assert!(once.contains("\n    print a /* inline */ + 1;\n"));
+    assert!(once.contains("\n    var u = 1 // c\n        ;\n"));
+    assert!(once.contains("\n    var v = a + // c\n        2;\n"));
+}
+
+#[test]
+fn test_format_rejects_invalid_source() {
+    assert_eq!(
+        format("var = 1;").unwrap_err().to_string(),
+        "ParseError [line 1]  at '=': Expect variable name."
+    );
+}
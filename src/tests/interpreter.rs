@@ -0,0 +1,865 @@
+use crate::{
+    callable::Capabilities,
+    interpreter,
+    object::Object,
+    parser,
+    resolver::Resolver,
+    scanner,
+    token::{token_type::TokenType, Token},
+};
+use std::error::Error;
+
+fn evaluate(source: &str) -> Result<Object, Box<dyn Error>> {
+    let mut scanner = scanner::Scanner::new(source.to_string());
+    let tokens = scanner.scan_tokens()?;
+
+    let mut parser = parser::Parser::new(tokens);
+    let expression = parser.expression()?;
+
+    let mut interpreter = interpreter::Interpreter::new();
+    interpreter.evaluate(&expression)
+}
+
+fn run(source: &str) -> Result<interpreter::Interpreter, Box<dyn Error>> {
+    let mut scanner = scanner::Scanner::new(source.to_string());
+    let tokens = scanner.scan_tokens()?;
+
+    let mut parser = parser::Parser::new(tokens);
+    let statements = parser.parse()?;
+
+    let mut interpreter = interpreter::Interpreter::new();
+    Resolver::new(&mut interpreter).resolve(&statements)?;
+    interpreter.interpret(&statements)?;
+    Ok(interpreter)
+}
+
+fn global(interpreter: &interpreter::Interpreter, name: &str) -> Object {
+    let token = Token::new(TokenType::IDENTIFIER, name.to_string(), None, 0);
+    interpreter.globals.borrow().get(&token).unwrap()
+}
+
+#[test]
+fn test_closures_assign_to_captured_locals() {
+    let interpreter = run(r#"
+    fun make() {
+        var count = 0;
+        fun increment() {
+            count = count + 1;
+            return count;
+        }
+        return increment;
+    }
+    var counter = make();
+    counter();
+    var count = counter();
+    "#)
+    .unwrap();
+
+    assert_eq!(global(&interpreter, "count"), Object::Int(2));
+}
+
+#[test]
+fn test_function_bodies_resolve_their_own_scopes() {
+    let interpreter = run(r#"
+    var a = "global";
+    fun outer() {
+        var a = "local";
+        fun inner() {
+            return a;
+        }
+        return inner();
+    }
+    var result = outer();
+    "#)
+    .unwrap();
+
+    assert_eq!(
+        global(&interpreter, "result"),
+        Object::String("local".to_string())
+    );
+}
+
+#[test]
+fn test_blocks_restore_the_enclosing_scope() {
+    // Both `a`s are on one line, so only their ids tell the references apart.
+    let interpreter = run(r#"
+    var a = 1; var inner; { var a = 2; { a = 3; } inner = a; } var outer = a;
+    "#)
+    .unwrap();
+
+    assert_eq!(global(&interpreter, "inner"), Object::Int(3));
+    assert_eq!(global(&interpreter, "outer"), Object::Int(1));
+}
+
+#[test]
+fn test_unresolved_names_use_the_global_scope() {
+    // `g` and `set` resolve `x` and `y` to globals, so locals declared later in the
+    // block they're called from must not shadow them.
+    let interpreter = run(r#"
+    var x = "global";
+    var y = 0;
+    var seen = "";
+    {
+        fun g() { seen = seen + x + " "; }
+        fun set() { y = 2; }
+        g();
+        var x = "local";
+        var y = 1;
+        g();
+        set();
+    }
+    "#)
+    .unwrap();
+
+    assert_eq!(
+        global(&interpreter, "seen"),
+        Object::String("global global ".to_string())
+    );
+    assert_eq!(global(&interpreter, "y"), Object::Int(2));
+}
+
+#[test]
+fn test_integer_arithmetic() {
+    assert!(matches!(evaluate("1 + 2 * 3").unwrap(), Object::Int(7)));
+    assert!(matches!(evaluate("7 div 2").unwrap(), Object::Int(3)));
+    assert!(matches!(evaluate("-7 % 3").unwrap(), Object::Int(-1)));
+    assert!(matches!(
+        evaluate("9007199254740993 + 1").unwrap(),
+        Object::Int(9007199254740994)
+    ));
+}
+
+#[test]
+fn test_numeric_promotion() {
+    assert!(matches!(evaluate("1 + 0.5").unwrap(), Object::Number(n) if n == 1.5));
+    assert!(matches!(evaluate("7 / 2").unwrap(), Object::Number(n) if n == 3.5));
+    assert!(matches!(evaluate("7.5 div 2").unwrap(), Object::Number(n) if n == 3.0));
+    assert!(matches!(evaluate("7.5 % 2").unwrap(), Object::Number(n) if n == 1.5));
+    assert_eq!(evaluate("2 == 2.0").unwrap(), Object::Boolean(true));
+    assert_eq!(evaluate("2 < 2.5").unwrap(), Object::Boolean(true));
+}
+
+#[test]
+fn test_int_float_comparison_is_exact() {
+    let cases = [
+        ("9007199254740993 == 9007199254740992.0", false),
+        ("9007199254740992 == 9007199254740992.0", true),
+        ("9007199254740993 > 9007199254740992.0", true),
+        ("9007199254740992.0 < 9007199254740993", true),
+        ("9223372036854775807 < 9223372036854775808", true),
+        ("-3 < -2.5", true),
+        ("-2 > -2.5", true),
+        ("2 == 2.5", false),
+    ];
+
+    for (source, expected) in cases {
+        assert_eq!(
+            evaluate(source).unwrap(),
+            Object::Boolean(expected),
+            "{}",
+            source
+        );
+    }
+}
+
+#[test]
+fn test_integer_errors() {
+    let cases = [
+        (
+            "9223372036854775807 + 1",
+            "RuntimeError [line 1] : Integer overflow.",
+        ),
+        (
+            "-9223372036854775807 - 2",
+            "RuntimeError [line 1] : Integer overflow.",
+        ),
+        ("1 div 0", "RuntimeError [line 1] : Can't divide by zero."),
+        ("1 % 0", "RuntimeError [line 1] : Can't divide by zero."),
+        (
+            "\"a\" % 2",
+            "RuntimeError [line 1] : Operands must be numbers.",
+        ),
+    ];
+
+    for (source, message) in cases {
+        assert_eq!(evaluate(source).unwrap_err().to_string(), message);
+    }
+}
+
+#[test]
+fn test_int_and_float_natives() {
+    assert!(matches!(evaluate("int(3.9)").unwrap(), Object::Int(3)));
+    assert!(matches!(
+        evaluate("int(\" 42 \")").unwrap(),
+        Object::Int(42)
+    ));
+    assert!(matches!(evaluate("float(3)").unwrap(), Object::Number(n) if n == 3.0));
+    assert!(matches!(evaluate("clock()").unwrap(), Object::Int(_)));
+    assert!(evaluate("int(1e300)").is_err());
+    assert!(evaluate("int(\"4.5\")").is_err());
+}
+
+#[test]
+fn test_whole_floats_print_with_a_fraction() {
+    let cases = [
+        ("float(2)", "2.0"),
+        ("2", "2"),
+        ("-4 / 2", "-2.0"),
+        ("2.5", "2.5"),
+        ("\"n=\" + 1e3", "n=1000.0"),
+    ];
+
+    for (source, printed) in cases {
+        assert_eq!(evaluate(source).unwrap().to_string(), printed, "{}", source);
+    }
+}
+
+#[test]
+fn test_power_and_bitwise_operators() {
+    assert!(matches!(evaluate("2 ** 10").unwrap(), Object::Int(1024)));
+    assert!(matches!(evaluate("2 ** 3 ** 2").unwrap(), Object::Int(512)));
+    assert!(matches!(evaluate("-2 ** 2").unwrap(), Object::Int(-4)));
+    assert!(matches!(evaluate("2 ** -1").unwrap(), Object::Number(n) if n == 0.5));
+    assert!(matches!(
+        evaluate("6 & 3 | 8 ^ 1").unwrap(),
+        Object::Int(11)
+    ));
+    assert!(matches!(evaluate("1 << 4 >> 2").unwrap(), Object::Int(4)));
+    assert!(matches!(evaluate("1 + 1 << 2").unwrap(), Object::Int(8)));
+    assert_eq!(evaluate("1 | 2 == 3").unwrap(), Object::Boolean(true));
+
+    let cases = [
+        (
+            "1.5 & 1",
+            "RuntimeError [line 1] : Operands must be integers.",
+        ),
+        (
+            "1 << 64",
+            "RuntimeError [line 1] : Shift amount out of range.",
+        ),
+        ("2 ** 64", "RuntimeError [line 1] : Integer overflow."),
+    ];
+    for (source, message) in cases {
+        assert_eq!(evaluate(source).unwrap_err().to_string(), message);
+    }
+}
+
+#[test]
+fn test_compound_assignment() {
+    let interpreter = run(r#"
+    var total = 0;
+    for (var i = 0; i < 5; i++) {
+        total += i;
+    }
+
+    fun counter() {
+        var n = 10;
+        n -= 3;
+        n *= 2;
+        n %= 5;
+        n /= 2;
+        return n;
+    }
+    var result = counter();
+
+    var x = 5;
+    var before = x++;
+    var after = --x;
+    var text = "a";
+    text += "b";
+    "#)
+    .unwrap();
+
+    assert_eq!(global(&interpreter, "total"), Object::Int(10));
+    assert_eq!(global(&interpreter, "result"), Object::Number(2.0));
+    assert_eq!(global(&interpreter, "before"), Object::Int(5));
+    assert_eq!(global(&interpreter, "after"), Object::Int(5));
+    assert_eq!(global(&interpreter, "x"), Object::Int(5));
+    assert_eq!(
+        global(&interpreter, "text"),
+        Object::String("ab".to_string())
+    );
+}
+
+#[test]
+fn test_doubled_signs_without_an_increment_target() {
+    let interpreter = run(r#"
+    var a = 5;
+    var b = 2;
+    var numbers = 1--1;
+    var names = a--b;
+    var spaced = a -- b + 1;
+    var postfix = a-- - b;
+    "#)
+    .unwrap();
+
+    assert_eq!(global(&interpreter, "numbers"), Object::Int(2));
+    assert_eq!(global(&interpreter, "names"), Object::Int(7));
+    assert_eq!(global(&interpreter, "spaced"), Object::Int(8));
+    assert_eq!(global(&interpreter, "postfix"), Object::Int(3));
+    assert_eq!(global(&interpreter, "a"), Object::Int(4));
+}
+
+#[test]
+fn test_invalid_compound_assignment_target() {
+    let err = run("var a = 1; (a) += 1;").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "ParseError [line 1]  at '+=': Invalid assignment target."
+    );
+
+    let err = run("var a = 1; a.field += 1;").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "RuntimeError [line 1] : Only instances have fields."
+    );
+}
+
+#[test]
+fn test_conditional_expression() {
+    assert!(matches!(evaluate("true ? 1 : 2").unwrap(), Object::Int(1)));
+    assert!(matches!(evaluate("nil ? 1 : 2").unwrap(), Object::Int(2)));
+    assert!(matches!(
+        evaluate("false ? 1 : true ? 2 : 3").unwrap(),
+        Object::Int(2)
+    ));
+    assert!(matches!(evaluate("!0 ? 1 : 2").unwrap(), Object::Int(2)));
+    // The branch that isn't taken is never evaluated.
+    assert!(matches!(
+        evaluate("true ? 1 : missing").unwrap(),
+        Object::Int(1)
+    ));
+    assert!(evaluate("true ? 1 2").is_err());
+}
+
+#[test]
+fn test_null_coalescing() {
+    assert!(matches!(evaluate("nil ?? 2").unwrap(), Object::Int(2)));
+    assert_eq!(evaluate("false ?? 2").unwrap(), Object::Boolean(false));
+    assert!(matches!(
+        evaluate("nil ?? nil ?? 3").unwrap(),
+        Object::Int(3)
+    ));
+    assert!(matches!(evaluate("1 ?? missing").unwrap(), Object::Int(1)));
+    assert!(matches!(
+        evaluate("nil ?? false ? 1 : 2").unwrap(),
+        Object::Int(2)
+    ));
+}
+
+#[test]
+fn test_conditional_in_local_scope() {
+    let interpreter = run(r#"
+    fun describe(n, fallback) {
+        var label = n > 0 ? "positive" : n < 0 ? "negative" : nil;
+        return label ?? fallback;
+    }
+    var a = describe(5, "zero");
+    var b = describe(0, "zero");
+    "#)
+    .unwrap();
+
+    assert_eq!(
+        global(&interpreter, "a"),
+        Object::String("positive".to_string())
+    );
+    assert_eq!(
+        global(&interpreter, "b"),
+        Object::String("zero".to_string())
+    );
+}
+
+#[test]
+fn test_string_functions() {
+    let string = |source: &str| match evaluate(source).unwrap() {
+        Object::String(s) => s,
+        other => panic!("expected a string, got {}", other),
+    };
+
+    assert!(matches!(
+        evaluate("len(\"héllo\")").unwrap(),
+        Object::Int(5)
+    ));
+    assert_eq!(string("substr(\"héllo\", 1, 3)"), "éll");
+    assert_eq!(string("substr(\"héllo\", 2)"), "llo");
+    assert!(matches!(
+        evaluate("index_of(\"héllo\", \"l\")").unwrap(),
+        Object::Int(2)
+    ));
+    assert!(matches!(
+        evaluate("index_of(\"hello\", \"z\")").unwrap(),
+        Object::Int(-1)
+    ));
+    assert!(matches!(
+        evaluate("len(split(\"a,b,,c\", \",\"))").unwrap(),
+        Object::Int(4)
+    ));
+    assert_eq!(string("join(split(\"a,b,c\", \",\"), \"-\")"), "a-b-c");
+    assert_eq!(string("join(chars(\"abc\"), \" \")"), "a b c");
+    assert_eq!(string("upper(trim(\"  MiXed \"))"), "MIXED");
+    assert_eq!(string("lower(\"MiXed\")"), "mixed");
+    assert_eq!(string("replace(\"a-b-c\", \"-\", \"+\")"), "a+b+c");
+    assert_eq!(
+        evaluate("starts_with(\"lox\", \"lo\") and ends_with(\"lox\", \"ox\")").unwrap(),
+        Object::Boolean(true)
+    );
+    assert!(matches!(evaluate("ord(\"A\")").unwrap(), Object::Int(65)));
+    assert_eq!(string("chr(955)"), "λ");
+    assert_eq!(string("str(1.5) + str(nil)"), "1.5nil");
+    assert!(matches!(
+        evaluate("num(\" 42 \")").unwrap(),
+        Object::Int(42)
+    ));
+    assert!(matches!(evaluate("num(\"2.5\")").unwrap(), Object::Number(n) if n == 2.5));
+}
+
+#[test]
+fn test_string_function_errors() {
+    let message = |source: &str| run(source).unwrap_err().to_string();
+
+    assert_eq!(
+        message("var x = 1;\nlen(\nx);"),
+        "RuntimeError [line 3] : Argument 1 must be a string or a list, not int."
+    );
+    assert_eq!(
+        message("upper(\"a\", \"b\");"),
+        "RuntimeError [line 1] : Expected 1 arguments but got 2."
+    );
+    assert_eq!(
+        message("substr(\"abc\", 2, 5);"),
+        "RuntimeError [line 1] : Substring out of range."
+    );
+    for extremes in [
+        "substr(\"abc\", 1, 9223372036854775807);",
+        "substr(\"abc\", -9223372036854775807 - 1);",
+        "substr(\"abc\", 9223372036854775807, 1);",
+    ] {
+        assert_eq!(
+            message(extremes),
+            "RuntimeError [line 1] : Substring out of range."
+        );
+    }
+    assert_eq!(
+        message("ord(\"ab\");"),
+        "RuntimeError [line 1] : Argument 1 must be a single character."
+    );
+    assert_eq!(
+        message("num(\"x1\");"),
+        "RuntimeError [line 1] : Can't convert 'x1' to a number."
+    );
+    assert_eq!(
+        message("join(\"abc\", \",\");"),
+        "RuntimeError [line 1] : Argument 1 must be a list, not string."
+    );
+}
+
+#[test]
+fn test_math_functions() {
+    let float = |source: &str| match evaluate(source).unwrap() {
+        Object::Number(n) => n,
+        other => panic!("expected a float, got {}", other),
+    };
+
+    assert_eq!(float("sqrt(16)"), 4.0);
+    assert!(matches!(evaluate("pow(2, 10)").unwrap(), Object::Int(1024)));
+    assert_eq!(float("pow(4, 0.5)"), 2.0);
+    assert!(matches!(evaluate("abs(-3)").unwrap(), Object::Int(3)));
+    assert_eq!(float("abs(-2.5)"), 2.5);
+    assert_eq!(float("floor(-2.5)"), -3.0);
+    assert_eq!(float("ceil(2.1)"), 3.0);
+    assert_eq!(float("round(2.5)"), 3.0);
+    assert!(matches!(evaluate("round(7)").unwrap(), Object::Int(7)));
+    assert!(matches!(
+        evaluate("min(3, 1, 2.5)").unwrap(),
+        Object::Int(1)
+    ));
+    assert_eq!(float("max(3, 1, 4.5)"), 4.5);
+    assert_eq!(float("sin(0) + cos(0) + tan(0)"), 1.0);
+    assert_eq!(float("log(E)"), 1.0);
+    assert_eq!(float("exp(0)"), 1.0);
+    assert_eq!(float("PI"), std::f64::consts::PI);
+
+    assert!(evaluate("sqrt(\"4\")").is_err());
+    assert!(evaluate("abs(-9223372036854775807 - 1)").is_err());
+    assert!(evaluate("min()").is_err());
+}
+
+#[test]
+fn test_seeded_random() {
+    let interpreter = run(r#"
+    fun roll() {
+        var s = "";
+        for (var i = 0; i < 20; i = i + 1) s = s + str(random_int(1, 6)) + ",";
+        return s + str(random());
+    }
+    seed(42);
+    var first = roll();
+    seed(42);
+    var second = roll();
+    var third = roll();
+    var in_range = true;
+    for (var i = 0; i < 1000; i = i + 1) {
+        var n = random_int(-2, 2);
+        var f = random();
+        if (n < -2 or n > 2 or f < 0 or f >= 1) in_range = false;
+    }
+    "#)
+    .unwrap();
+
+    assert_eq!(
+        global(&interpreter, "first"),
+        global(&interpreter, "second")
+    );
+    assert_ne!(global(&interpreter, "first"), global(&interpreter, "third"));
+    assert_eq!(global(&interpreter, "in_range"), Object::Boolean(true));
+    assert!(run("random_int(3, 1);").is_err());
+}
+
+#[test]
+fn test_random_state_belongs_to_the_interpreter() {
+    let mut seeded = run("seed(7);").unwrap();
+    let other = run("seed(7); var a = random(); var b = random();").unwrap();
+
+    // Another interpreter drawing numbers doesn't advance this one's generator.
+    let tokens = scanner::Scanner::new("var a = random();".to_string())
+        .scan_tokens()
+        .unwrap();
+    let statements = parser::Parser::new(tokens).parse().unwrap();
+    Resolver::new(&mut seeded).resolve(&statements).unwrap();
+    seeded.interpret(&statements).unwrap();
+
+    assert_eq!(global(&seeded, "a"), global(&other, "a"));
+}
+
+#[test]
+fn test_file_natives() {
+    let dir = std::env::temp_dir().join(format!("lox-io-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let _ = std::fs::remove_file(dir.join("notes.txt"));
+    let path = dir.join("notes.txt").to_string_lossy().replace('\\', "/");
+
+    let interpreter = run(&format!(
+        r#"
+    var path = "{}";
+    var before = exists(path);
+    write_file(path, "one\n");
+    append_file(path, "two\nthree\n");
+    var contents = read_file(path);
+    var count = len(lines(path));
+    var last = lines(path);
+    "#,
+        path
+    ))
+    .unwrap();
+
+    assert_eq!(global(&interpreter, "before"), Object::Boolean(false));
+    assert_eq!(
+        global(&interpreter, "contents"),
+        Object::String("one\ntwo\nthree\n".to_string())
+    );
+    assert!(matches!(global(&interpreter, "count"), Object::Int(3)));
+    assert_eq!(
+        global(&interpreter, "last").to_string(),
+        "[one, two, three]"
+    );
+
+    let missing = dir.join("missing.txt").to_string_lossy().replace('\\', "/");
+    let err = run(&format!("read_file(\"{}\");", missing)).unwrap_err();
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert!(err
+        .to_string()
+        .starts_with(&format!("RuntimeError [line 1] : Can't read '{}'", missing)));
+}
+
+#[test]
+fn test_disabled_filesystem_capability() {
+    let tokens = scanner::Scanner::new("read_file(\"x\");".to_string())
+        .scan_tokens()
+        .unwrap();
+    let statements = parser::Parser::new(tokens).parse().unwrap();
+
+    let mut interpreter = interpreter::Interpreter::new().with_capabilities(Capabilities {
+        filesystem: false,
+        ..Capabilities::default()
+    });
+    Resolver::new(&mut interpreter)
+        .resolve(&statements)
+        .unwrap();
+    let err = interpreter.interpret(&statements).unwrap_err();
+    assert!(err.to_string().contains("Undefined variable 'read_file'"));
+}
+
+#[test]
+fn test_disabled_filesystem_capability_blocks_import() {
+    // An absolute path to a file that exists, so only the capability can stop it.
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml").replace('\\', "/");
+    let source = format!("import \"{}\" as manifest;", path);
+    let tokens = scanner::Scanner::new(source).scan_tokens().unwrap();
+    let statements = parser::Parser::new(tokens).parse().unwrap();
+
+    let mut interpreter = interpreter::Interpreter::new().with_capabilities(Capabilities {
+        filesystem: false,
+        ..Capabilities::default()
+    });
+    Resolver::new(&mut interpreter)
+        .resolve(&statements)
+        .unwrap();
+    let err = interpreter.interpret(&statements).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "RuntimeError [line 1] : Can't import '{}': filesystem access is disabled.",
+            path
+        )
+    );
+}
+
+#[test]
+fn test_throw_and_catch() {
+    let interpreter = run(r#"
+    var log = "";
+    try {
+        log = log + "try,";
+        throw 42;
+        log = log + "unreachable,";
+    } catch (e) {
+        log = log + "caught " + str(e) + ",";
+    } finally {
+        log = log + "finally";
+    }
+
+    var message;
+    var line;
+    try {
+        var x = 1;
+        x = x div 0;
+    } catch (e) {
+        message = e.message;
+        line = e.line;
+    }
+
+    fun early() {
+        try {
+            return "returned";
+        } finally {
+            log = log + ",cleanup";
+        }
+    }
+    var returned = early();
+
+    var outer;
+    try {
+        try {
+            throw "inner";
+        } finally {
+            log = log + ",inner finally";
+        }
+    } catch (e) {
+        outer = e;
+    }
+    "#)
+    .unwrap();
+
+    assert_eq!(
+        global(&interpreter, "log"),
+        Object::String("try,caught 42,finally,cleanup,inner finally".to_string())
+    );
+    assert_eq!(
+        global(&interpreter, "message"),
+        Object::String("Can't divide by zero.".to_string())
+    );
+    assert!(matches!(global(&interpreter, "line"), Object::Int(17)));
+    assert_eq!(
+        global(&interpreter, "returned"),
+        Object::String("returned".to_string())
+    );
+    assert_eq!(
+        global(&interpreter, "outer"),
+        Object::String("inner".to_string())
+    );
+}
+
+#[test]
+fn test_uncaught_exceptions() {
+    let message = |source: &str| run(source).unwrap_err().to_string();
+
+    assert_eq!(
+        message("\nthrow \"boom\";"),
+        "RuntimeError [line 2] : Uncaught exception: boom"
+    );
+    assert_eq!(
+        message("try { upper(1); } catch (e) {\nthrow e; }"),
+        "RuntimeError [line 1] : Argument 1 must be a string, not int."
+    );
+    assert_eq!(
+        message("try { throw 1; } catch (e) { e.nope; }"),
+        "RuntimeError [line 1] : Only instances have properties."
+    );
+    assert_eq!(
+        message("try { print 1; }"),
+        "ParseError [line 1]  at end: Expect 'catch' or 'finally' after try block."
+    );
+}
+
+#[test]
+fn test_bare_return_exits_early() {
+    let interpreter = run(r#"
+    var log = "";
+    fun check(n) {
+        if (n < 0) return;
+        log = log + str(n);
+    }
+    check(-1);
+    check(2);
+    var result = check(3);
+
+    fun first_even(a, b, c) {
+        while (true) {
+            if (a % 2 == 0) return a;
+            if (b % 2 == 0) return b;
+            return c;
+        }
+        return nil;
+    }
+    var even = first_even(1, 4, 6);
+    "#)
+    .unwrap();
+
+    assert_eq!(
+        global(&interpreter, "log"),
+        Object::String("23".to_string())
+    );
+    assert_eq!(global(&interpreter, "result"), Object::Nil);
+    assert!(matches!(global(&interpreter, "even"), Object::Int(4)));
+}
+
+#[test]
+fn test_break_and_continue() {
+    let interpreter = run(r#"
+    var odds = "";
+    for (var i = 0; i < 10; i = i + 1) {
+        if (i % 2 == 0) continue;
+        if (i > 7) break;
+        odds = odds + str(i);
+    }
+
+    var pairs = 0;
+    var i = 0;
+    while (i < 3) {
+        i = i + 1;
+        var j = 0;
+        while (true) {
+            j = j + 1;
+            if (j > i) break;
+            pairs = pairs + 1;
+        }
+    }
+
+    var cleaned = false;
+    while (true) {
+        try {
+            break;
+        } finally {
+            cleaned = true;
+        }
+    }
+    "#)
+    .unwrap();
+
+    assert_eq!(
+        global(&interpreter, "odds"),
+        Object::String("1357".to_string())
+    );
+    assert!(matches!(global(&interpreter, "pairs"), Object::Int(6)));
+    assert_eq!(global(&interpreter, "cleaned"), Object::Boolean(true));
+}
+
+#[test]
+fn test_break_outside_loop() {
+    let message = |source: &str| run(source).unwrap_err().to_string();
+
+    assert_eq!(
+        message("break;"),
+        "RuntimeError [line 1] : ResolverError: Can't use 'break' outside of a loop."
+    );
+    assert_eq!(
+        message("while (true) {\nfun f() { continue; }\n}"),
+        "RuntimeError [line 2] : ResolverError: Can't use 'continue' outside of a loop."
+    );
+}
+
+#[test]
+fn test_recursion_and_closures() {
+    let interpreter = run(r#"
+    fun fib(n) {
+        if (n < 2) return n;
+        return fib(n - 1) + fib(n - 2);
+    }
+    var f = fib(15);
+
+    fun counter() {
+        var count = 0;
+        fun increment() {
+            count += 1;
+            return count;
+        }
+        return increment;
+    }
+    var a = counter();
+    var b = counter();
+    a();
+    a();
+    var counts = str(a()) + str(b());
+    "#)
+    .unwrap();
+
+    assert!(matches!(global(&interpreter, "f"), Object::Int(610)));
+    assert_eq!(
+        global(&interpreter, "counts"),
+        Object::String("31".to_string())
+    );
+}
+
+#[test]
+fn test_assertion_functions() {
+    let interpreter = run(r#"
+    fun fails() { throw "nope"; }
+    fun divides() { return 1 / nil; }
+    assert_eq(1 + 1, 2);
+    assert_eq("a", "a", "strings");
+    assert_ne(1, "1");
+    var thrown = assert_throws(fails);
+    var message = assert_throws(divides, "bad operands").message;
+    "#)
+    .unwrap();
+    assert_eq!(
+        global(&interpreter, "thrown"),
+        Object::String("nope".to_string())
+    );
+    assert_eq!(
+        global(&interpreter, "message"),
+        Object::String("Operands must be numbers.".to_string())
+    );
+
+    let message = |source: &str| run(source).unwrap_err().to_string();
+    assert_eq!(
+        message("var x = 1;\nassert_eq(x, \"1\");"),
+        "RuntimeError [line 2] : assert_eq failed: 1 != \"1\"."
+    );
+    assert_eq!(
+        message("assert_ne(nil, nil, \"must differ\");"),
+        "RuntimeError [line 1] : assert_ne failed (must differ): nil == nil."
+    );
+    assert_eq!(
+        message("fun ok() { return 2; }\nassert_throws(ok);"),
+        "RuntimeError [line 2] : assert_throws failed: <fn ok> returned 2 without throwing."
+    );
+    assert_eq!(
+        message("assert_eq(1);"),
+        "RuntimeError [line 1] : Expected 2 or 3 arguments but got 1."
+    );
+    assert_eq!(
+        message("assert_throws(1);"),
+        "RuntimeError [line 1] : Argument 1 must be a function, not int."
+    );
+}
@@ -0,0 +1,58 @@
+use crate::{
+    interpreter::{interrupt::Interrupted, Interpreter},
+    parser::Parser,
+    resolver::Resolver,
+    scanner::Scanner,
+    token::{token_type::TokenType, Token},
+};
+use std::{error::Error, rc::Rc, thread, time::Duration};
+
+fn run(interpreter: &mut Interpreter, source: &str) -> Result<(), Box<dyn Error>> {
+    let tokens = Scanner::new(source.to_string()).scan_tokens()?;
+    let statements = Parser::new(tokens).parse()?;
+    Resolver::new(interpreter).resolve(&statements)?;
+    interpreter.interpret(&statements)?;
+    Ok(())
+}
+
+#[test]
+fn test_interrupt_from_another_thread() {
+    let mut interpreter = Interpreter::new();
+    let handle = interpreter.interrupt_handle();
+    let stopper = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        handle.interrupt();
+    });
+
+    let err = run(
+        &mut interpreter,
+        "fun spin() { var i = 0; while (true) { i = i + 1; } } { var local = 1; spin(); }",
+    )
+    .unwrap_err();
+    stopper.join().unwrap();
+    assert!(err.downcast_ref::<Interrupted>().is_some());
+
+    // The environments entered by the block and the call were unwound.
+    assert!(Rc::ptr_eq(&interpreter.env, &interpreter.globals));
+    assert!(interpreter
+        .env
+        .borrow()
+        .get(&Token::new(
+            TokenType::IDENTIFIER,
+            "local".to_string(),
+            None,
+            0
+        ))
+        .is_err());
+
+    // The interrupt is consumed, so the interpreter can run again.
+    run(&mut interpreter, "var after = 1;").unwrap();
+}
+
+#[test]
+fn test_interrupt_at_call() {
+    let mut interpreter = Interpreter::new();
+    interpreter.interrupt_handle().interrupt();
+    let err = run(&mut interpreter, "fun f() {} f();").unwrap_err();
+    assert_eq!(err.to_string(), "Interrupted.");
+}
@@ -0,0 +1,150 @@
+use crate::{
+    interpreter::Interpreter,
+    linter::{Linter, Rule, Span, Warning},
+    parser::Parser,
+    resolver::Resolver,
+    scanner::Scanner,
+};
+
+fn warnings(source: &str, linter: Linter) -> Vec<Warning> {
+    let tokens = Scanner::new(source.to_string()).scan_tokens().unwrap();
+    let statements = Parser::new(tokens).parse().unwrap();
+    let mut interpreter = Interpreter::new();
+    let mut resolver = Resolver::new(&mut interpreter);
+    let _ = resolver.resolve(&statements);
+    linter.lint(&statements, &resolver.bindings())
+}
+
+fn lint(source: &str, rules: Vec<Rule>) -> Vec<String> {
+    warnings(source, Linter::new(rules))
+        .iter()
+        .map(|warning| warning.to_string())
+        .collect()
+}
+
+#[test]
+fn test_unused_and_shadowed() {
+    let source = r#"
+var total = 0;
+fun add(a, b, unused, _ignored) {
+    var tmp = 1;
+    {
+        var a = 2;
+        print a;
+    }
+    return b;
+}
+print add(1, 2, 3, 4) + total;
+"#;
+
+    assert_eq!(
+        lint(source, Rule::ALL.to_vec()),
+        vec![
+            "Warning [line 3:9] (unused-parameter): Parameter 'a' is never used.",
+            "Warning [line 3:15] (unused-parameter): Parameter 'unused' is never used.",
+            "Warning [line 4:9] (unused-variable): Variable 'tmp' is never used.",
+            "Warning [line 6:13] (shadowed-variable): 'a' shadows a declaration on line 3.",
+        ]
+    );
+}
+
+#[test]
+fn test_unreachable_and_undeclared() {
+    let source = r#"
+fun f() {
+    counter = 1;
+    while (true) {
+        break;
+        print "never";
+    }
+    return later;
+    print "never";
+}
+var later = 2;
+f();
+"#;
+
+    assert_eq!(
+        lint(source, Rule::ALL.to_vec()),
+        vec![
+            "Warning [line 3:5] (undeclared-assignment): Assignment to undeclared variable 'counter'.",
+            "Warning [line 6:9] (unreachable-code): Code after 'break' is unreachable.",
+            "Warning [line 9:5] (unreachable-code): Code after 'return' is unreachable.",
+        ]
+    );
+}
+
+#[test]
+fn test_comparisons_and_arity() {
+    let source = r#"
+fun pair(a, b) { return a + b; }
+print 1 == "1";
+print 1 < 2.5;
+print !nil != (true);
+print pair(1) + pair(1, 2) + pair(1, 2, 3);
+"#;
+
+    assert_eq!(
+        lint(source, Rule::ALL.to_vec()),
+        vec![
+            "Warning [line 3:9] (mismatched-comparison): Comparison '==' between a number and a string.",
+            "Warning [line 6:7] (wrong-arity): Function 'pair' takes 2 arguments but is called with 1.",
+            "Warning [line 6:30] (wrong-arity): Function 'pair' takes 2 arguments but is called with 3.",
+        ]
+    );
+}
+
+#[test]
+fn test_rules_are_selectable() {
+    let source = "fun f(x) { var y; return; print 1 == nil; }";
+
+    assert_eq!(lint(source, Vec::new()), Vec::<String>::new());
+    assert_eq!(
+        lint(source, vec![Rule::UnreachableCode]),
+        vec!["Warning [line 1:27] (unreachable-code): Code after 'return' is unreachable."]
+    );
+    assert_eq!("wrong-arity".parse::<Rule>(), Ok(Rule::WrongArity));
+    assert_eq!(
+        "no-such-rule".parse::<Rule>(),
+        Err(
+            "Unknown rule 'no-such-rule'. Rules: unused-variable, unused-parameter, \
+             shadowed-variable, unreachable-code, undeclared-assignment, \
+             mismatched-comparison, wrong-arity."
+                .to_string()
+        )
+    );
+
+    let warnings = warnings(source, Linter::default());
+    assert_eq!(
+        warnings[0].span,
+        Span {
+            line: 1,
+            column: 7,
+            length: 1
+        }
+    );
+    assert_eq!(warnings.len(), 4);
+}
+
+#[test]
+fn test_names_follow_the_resolver() {
+    let source = r#"
+{ var a = 1; var a = 2; }
+fun f(n) {
+    var total;
+    total = n;
+    return later(n);
+}
+fun later(n) { var later = n; return later; }
+f(1);
+"#;
+
+    assert_eq!(
+        lint(source, Rule::ALL.to_vec()),
+        vec![
+            "Warning [line 2:7] (unused-variable): Variable 'a' is never used.",
+            "Warning [line 4:9] (unused-variable): Variable 'total' is never used.",
+            "Warning [line 8:20] (shadowed-variable): 'later' shadows a declaration on line 8.",
+        ]
+    );
+}
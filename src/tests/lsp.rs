@@ -0,0 +1,252 @@
+use std::io::Cursor;
+
+use crate::{
+    json::Json,
+    lsp::{self, Server},
+};
+
+const SOURCE: &str = "/// Adds two numbers.
+fun add(a, b) {
+    var sum = a + b;
+    return sum;
+}
+var total = add(1, 2);
+print total;
+";
+
+fn message(text: &str) -> Json {
+    Json::parse(text).unwrap()
+}
+
+fn open(server: &mut Server, source: &str) -> Vec<Json> {
+    let source = Json::from(source);
+    server.handle(&message(&format!(
+        r#"{{"jsonrpc":"2.0","method":"textDocument/didOpen","params":{{"textDocument":{{"uri":"file:///main.lox","languageId":"lox","version":1,"text":{}}}}}}}"#,
+        source
+    )))
+}
+
+fn request(server: &mut Server, method: &str, line: i64, character: i64) -> Json {
+    let replies = server.handle(&message(&format!(
+        r#"{{"jsonrpc":"2.0","id":7,"method":"{}","params":{{"textDocument":{{"uri":"file:///main.lox"}},"position":{{"line":{},"character":{}}},"context":{{"includeDeclaration":true}}}}}}"#,
+        method, line, character
+    )));
+    assert_eq!(replies.len(), 1);
+    assert_eq!(replies[0].get("id"), Some(&Json::Int(7)));
+    replies[0].get("result").cloned().unwrap()
+}
+
+fn start(location: &Json) -> (f64, f64) {
+    let start = location.get("range").unwrap().get("start").unwrap();
+    (
+        start.get("line").unwrap().as_f64().unwrap(),
+        start.get("character").unwrap().as_f64().unwrap(),
+    )
+}
+
+fn labels(items: &Json) -> Vec<&str> {
+    items
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item.get("label").unwrap().as_str().unwrap())
+        .collect()
+}
+
+#[test]
+fn test_json_parse() {
+    let text = r#"{"a": [1, -2.5e1, true, null], "b": "q\"\né😀", "c": {}}"#;
+    let json = Json::parse(text).unwrap();
+    assert_eq!(
+        json.get("a"),
+        Some(&Json::Array(vec![
+            Json::Int(1),
+            Json::Number(-25.0),
+            Json::Bool(true),
+            Json::Null
+        ]))
+    );
+    assert_eq!(json.get("b").and_then(Json::as_str), Some("q\"\né😀"));
+    assert_eq!(Json::parse(&json.to_string()), Ok(json));
+
+    assert!(Json::parse("{\"a\": }").is_err());
+    assert!(Json::parse("[1] 2").is_err());
+}
+
+#[test]
+fn test_navigation() {
+    let mut server = Server::new();
+    let replies = server.handle(&message(
+        r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"capabilities":{}}}"#,
+    ));
+    let capabilities = replies[0]
+        .get("result")
+        .unwrap()
+        .get("capabilities")
+        .unwrap();
+    assert_eq!(capabilities.get("hoverProvider"), Some(&Json::Bool(true)));
+
+    let diagnostics = open(&mut server, SOURCE);
+    let params = diagnostics[0].get("params").unwrap();
+    assert_eq!(params.get("diagnostics"), Some(&Json::Array(Vec::new())));
+
+    // `add` in `var total = add(1, 2);` goes to its declaration.
+    let definition = request(&mut server, "textDocument/definition", 5, 13);
+    assert_eq!(start(&definition), (1.0, 4.0));
+
+    let references = request(&mut server, "textDocument/references", 3, 11);
+    let references: Vec<(f64, f64)> = references.as_array().unwrap().iter().map(start).collect();
+    assert_eq!(references, vec![(2.0, 8.0), (3.0, 11.0)]);
+
+    let hover = request(&mut server, "textDocument/hover", 5, 12);
+    let contents = hover.get("contents").unwrap().get("value").unwrap();
+    assert_eq!(
+        contents.as_str(),
+        Some("```lox\nfun add(a, b)\n```\n\nAdds two numbers.")
+    );
+
+    // Natives have nowhere to go.
+    open(&mut server, "print len(\"abc\");");
+    assert_eq!(
+        request(&mut server, "textDocument/definition", 0, 7),
+        Json::Null
+    );
+}
+
+#[test]
+fn test_symbols_and_completion() {
+    let mut server = Server::new();
+    open(&mut server, SOURCE);
+
+    let symbols = request(&mut server, "textDocument/documentSymbol", 0, 0);
+    let symbols: Vec<String> = symbols
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|symbol| {
+            let name = symbol.get("name").unwrap().as_str().unwrap();
+            match symbol.get("containerName").and_then(Json::as_str) {
+                Some(container) => format!("{}.{}", container, name),
+                None => name.to_string(),
+            }
+        })
+        .collect();
+    assert_eq!(symbols, vec!["add", "add.sum", "total"]);
+
+    let inside = request(&mut server, "textDocument/completion", 3, 4);
+    let inside = labels(&inside);
+    for name in ["a", "add", "b", "len", "sum", "total"] {
+        assert!(inside.contains(&name), "missing {}", name);
+    }
+
+    let outside = request(&mut server, "textDocument/completion", 6, 0);
+    let outside = labels(&outside);
+    assert!(outside.contains(&"total") && outside.contains(&"clock"));
+    assert!(!outside.contains(&"a") && !outside.contains(&"sum"));
+
+    let items = request(&mut server, "textDocument/completion", 6, 0);
+    let item = |label: &str| {
+        items
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|item| item.get("label").and_then(Json::as_str) == Some(label))
+            .cloned()
+            .unwrap()
+    };
+    assert_eq!(item("PI").get("kind"), Some(&Json::Int(21)));
+    assert_eq!(
+        item("PI").get("detail").and_then(Json::as_str),
+        Some("native PI = 3.141592653589793")
+    );
+    assert_eq!(item("len").get("kind"), Some(&Json::Int(3)));
+}
+
+#[test]
+fn test_references_follow_the_resolver() {
+    let mut server = Server::new();
+    open(
+        &mut server,
+        "var x = 1;
+fun f() {
+    print x;
+    var y = later;
+    { var x = y; print x; }
+    x = 2;
+}
+var later = 3;
+",
+    );
+
+    // The global `x`, but not the block's `x` that hides it.
+    let references = request(&mut server, "textDocument/references", 0, 4);
+    let references: Vec<(f64, f64)> = references.as_array().unwrap().iter().map(start).collect();
+    assert_eq!(references, vec![(0.0, 4.0), (2.0, 10.0), (5.0, 4.0)]);
+
+    let inner = request(&mut server, "textDocument/references", 4, 23);
+    let inner: Vec<(f64, f64)> = inner.as_array().unwrap().iter().map(start).collect();
+    assert_eq!(inner, vec![(4.0, 10.0), (4.0, 23.0)]);
+
+    // A global declared after the function that reads it.
+    let definition = request(&mut server, "textDocument/definition", 3, 13);
+    assert_eq!(start(&definition), (7.0, 4.0));
+}
+
+#[test]
+fn test_diagnostics() {
+    let mut server = Server::new();
+    let replies = open(&mut server, "var x = 1;\nprint (x;\n");
+    let diagnostics = replies[0]
+        .get("params")
+        .unwrap()
+        .get("diagnostics")
+        .unwrap();
+    let diagnostics = diagnostics.as_array().unwrap();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].get("severity"), Some(&Json::Int(1)));
+    assert_eq!(start(&diagnostics[0]), (1.0, 8.0));
+
+    let replies = open(
+        &mut server,
+        "fun f(unused) {\n    return 1;\n}\nprint f(1);\n",
+    );
+    let diagnostics = replies[0]
+        .get("params")
+        .unwrap()
+        .get("diagnostics")
+        .unwrap();
+    let diagnostic = &diagnostics.as_array().unwrap()[0];
+    assert_eq!(diagnostic.get("severity"), Some(&Json::Int(2)));
+    assert_eq!(
+        diagnostic.get("code"),
+        Some(&Json::from("unused-parameter"))
+    );
+    assert_eq!(start(diagnostic), (0.0, 6.0));
+}
+
+#[test]
+fn test_serve_framing() {
+    let mut input = String::new();
+    for body in [
+        r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#,
+        r#"{"jsonrpc":"2.0","id":2,"method":"no/such/method"}"#,
+        r#"{"jsonrpc":"2.0","id":3,"method":"shutdown"}"#,
+        r#"{"jsonrpc":"2.0","method":"exit"}"#,
+    ] {
+        input.push_str(&format!("Content-Length: {}\r\n\r\n{}", body.len(), body));
+    }
+
+    let mut output = Vec::new();
+    let shut_down = lsp::serve(Cursor::new(input), &mut output).unwrap();
+    assert!(shut_down);
+
+    let mut reader = Cursor::new(output);
+    let mut replies = Vec::new();
+    while let Some(body) = lsp::read_message(&mut reader).unwrap() {
+        replies.push(Json::parse(&body).unwrap());
+    }
+    assert_eq!(replies.len(), 3);
+    let error = replies[1].get("error").unwrap();
+    assert_eq!(error.get("code"), Some(&Json::Int(-32601)));
+    assert_eq!(replies[2].get("result"), Some(&Json::Null));
+}
@@ -1,2 +1,14 @@
+mod ast_printer;
+mod coverage;
+mod debugger;
+mod formatter;
+mod interpreter;
+mod interrupt;
+mod linter;
+mod lsp;
+mod module;
 mod parser;
+mod profile;
+mod sandbox;
 mod scanner;
+mod test_runner;
@@ -0,0 +1,141 @@
+use crate::{
+    interpreter::Interpreter,
+    object::Object,
+    parser::Parser,
+    resolver::Resolver,
+    scanner::Scanner,
+    token::{token_type::TokenType, Token},
+};
+use std::{
+    error::Error,
+    fs,
+    ops::Deref,
+    path::{Path, PathBuf},
+};
+
+/// A directory of source files in the system temp dir, removed when dropped so that
+/// it's cleaned up even when an assertion fails.
+struct Project(PathBuf);
+
+impl Deref for Project {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for Project {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Writes `files` under a fresh directory in the system temp dir.
+fn project(name: &str, files: &[(&str, &str)]) -> Project {
+    let root = std::env::temp_dir().join(format!("lox-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    for (path, source) in files {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, source).unwrap();
+    }
+    Project(root)
+}
+
+fn run_file(path: &Path) -> Result<Interpreter, Box<dyn Error>> {
+    let tokens = Scanner::new(fs::read_to_string(path)?).scan_tokens()?;
+    let statements = Parser::new(tokens).parse()?;
+
+    let mut interpreter = Interpreter::new();
+    interpreter.set_script_path(path)?;
+    Resolver::new(&mut interpreter).resolve(&statements)?;
+    interpreter.interpret(&statements)?;
+    Ok(interpreter)
+}
+
+fn global(interpreter: &Interpreter, name: &str) -> Object {
+    let token = Token::new(TokenType::IDENTIFIER, name.to_string(), None, 0);
+    interpreter.globals.borrow().get(&token).unwrap()
+}
+
+#[test]
+fn test_import_relative_to_importing_file() {
+    let root = project(
+        "import-relative",
+        &[
+            (
+                "main.lox",
+                "import \"lib/util.lox\" as util;\nvar a = util.twice(4);\nvar b = util.name;",
+            ),
+            (
+                "lib/util.lox",
+                "import \"helper.lox\" as helper;\nvar name = helper.name;\nfun twice(x) { return helper.add(x, x); }",
+            ),
+            (
+                "lib/helper.lox",
+                "var name = \"helper\";\nfun add(a, b) { return a + b; }",
+            ),
+        ],
+    );
+
+    let interpreter = run_file(&root.join("main.lox")).unwrap();
+    assert!(matches!(global(&interpreter, "a"), Object::Int(8)));
+    assert_eq!(
+        global(&interpreter, "b"),
+        Object::String("helper".to_string())
+    );
+    assert_eq!(global(&interpreter, "util").to_string(), "<module util>");
+}
+
+#[test]
+fn test_import_runs_each_module_once() {
+    let root = project(
+        "import-cache",
+        &[
+            (
+                "main.lox",
+                "import \"counter.lox\" as a;\nimport \"./counter.lox\" as b;\na.count += 1;\nvar count = b.count;\nvar same = a == b;",
+            ),
+            ("counter.lox", "var count = 0;"),
+        ],
+    );
+
+    let interpreter = run_file(&root.join("main.lox")).unwrap();
+    assert!(matches!(global(&interpreter, "count"), Object::Int(1)));
+    assert_eq!(global(&interpreter, "same"), Object::Boolean(true));
+}
+
+#[test]
+fn test_import_errors() {
+    let root = project(
+        "import-errors",
+        &[
+            ("cycle.lox", "import \"a.lox\" as a;"),
+            ("a.lox", "import \"b.lox\" as b;"),
+            ("b.lox", "import \"a.lox\" as a;"),
+            ("missing.lox", "import \"nope.lox\" as nope;"),
+            ("property.lox", "import \"b2.lox\" as b;\nb.nope;"),
+            ("b2.lox", "var x = 1;"),
+            ("nested.lox", "{ import \"b2.lox\" as b; }"),
+        ],
+    );
+
+    let err = run_file(&root.join("cycle.lox")).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("Import cycle detected: a.lox -> b.lox -> a.lox."));
+
+    let err = run_file(&root.join("missing.lox")).unwrap_err();
+    assert!(err.to_string().contains("Can't import 'nope.lox'"));
+
+    let err = run_file(&root.join("property.lox")).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("Undefined property 'nope' in module 'b2'."));
+
+    let err = run_file(&root.join("nested.lox")).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("Can only import at the top level."));
+}
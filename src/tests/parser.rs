@@ -1,10 +1,18 @@
-use crate::{error, interpreter, parser, scanner};
+use crate::{
+    error,
+    expr::Expr,
+    interpreter,
+    object::Object,
+    parser, scanner,
+    stmt::Stmt,
+    token::{token_type::TokenType, Token},
+};
 
 #[test]
 fn test_print_statement() {
     let source = "print 20;".to_string();
     let mut scanner = scanner::Scanner::new(source);
-    let tokens = scanner.scan_tokens();
+    let tokens = scanner.scan_tokens().unwrap();
     assert_eq!(tokens.len(), 3 + 1); // 'print', '20', ';' + EOF
 
     let mut parser = parser::Parser::new(tokens);
@@ -12,7 +20,7 @@ fn test_print_statement() {
     assert_eq!(statements.len(), 1);
 
     let mut interpreter = interpreter::Interpreter::new();
-    let result = interpreter.interpret(statements);
+    let result = interpreter.interpret(&statements);
 
     assert!(result.is_ok());
 }
@@ -21,7 +29,7 @@ fn test_print_statement() {
 fn test_assignment_expression() {
     let source = r#"a = 20;"#.to_string();
     let mut scanner = scanner::Scanner::new(source);
-    let tokens = scanner.scan_tokens();
+    let tokens = scanner.scan_tokens().unwrap();
     assert_eq!(tokens.len(), 5); // 'a', '=', '20', ';' + EOF
 
     let mut parser = parser::Parser::new(tokens);
@@ -29,7 +37,7 @@ fn test_assignment_expression() {
     assert_eq!(statements.len(), 1);
 
     let mut interpreter = interpreter::Interpreter::new();
-    let result = interpreter.interpret(statements);
+    let result = interpreter.interpret(&statements);
 
     assert!(result.is_err());
     assert_eq!(
@@ -49,7 +57,7 @@ fn test_variable_declaration_and_assignment() {
         .to_string();
 
     let mut scanner = scanner::Scanner::new(source);
-    let tokens = scanner.scan_tokens();
+    let tokens = scanner.scan_tokens().unwrap();
     assert_eq!(tokens.len(), 10); // 'var', 'a', '=', '20', ';', 'a', '=', 'bittu', ';' + EOF
 
     let mut parser = parser::Parser::new(tokens);
@@ -57,7 +65,114 @@ fn test_variable_declaration_and_assignment() {
     assert_eq!(statements.len(), 2);
 
     let mut interpreter = interpreter::Interpreter::new();
-    let result = interpreter.interpret(statements);
+    let result = interpreter.interpret(&statements);
 
     assert!(result.is_ok());
 }
+
+#[test]
+fn test_string_interpolation() {
+    let source = r#""${1 + 2} apples, ${true} and ${"${"nested"}"}.""#.to_string();
+    let mut scanner = scanner::Scanner::new(source);
+    let tokens = scanner.scan_tokens().unwrap();
+
+    let mut parser = parser::Parser::new(tokens);
+    let expression = parser.expression().unwrap();
+
+    let mut interpreter = interpreter::Interpreter::new();
+    let result = interpreter.evaluate(&expression).unwrap();
+
+    assert_eq!(
+        result,
+        Object::String("3 apples, true and nested.".to_string())
+    );
+}
+
+#[test]
+fn test_doc_comments() {
+    let source = r#"
+    /// Adds two numbers.
+    /// Returns their sum.
+    fun add(a, b) { return a + b; }
+
+    /// Not attached to anything.
+    print add(1, 2);
+
+    /// The answer.
+    var answer = 42;
+    "#
+    .to_string();
+    let mut scanner = scanner::Scanner::new(source);
+    let tokens = scanner.scan_tokens().unwrap();
+
+    let mut parser = parser::Parser::new(tokens);
+    let statements = parser.parse().unwrap();
+    assert_eq!(statements.len(), 3);
+
+    let Stmt::Function(function) = &statements[0] else {
+        panic!("expected a function, got {:?}", statements[0]);
+    };
+    assert_eq!(
+        function.doc.as_deref(),
+        Some("Adds two numbers.\nReturns their sum.")
+    );
+    let Stmt::Var(var) = &statements[2] else {
+        panic!("expected a variable, got {:?}", statements[2]);
+    };
+    assert_eq!(var.doc.as_deref(), Some("The answer."));
+}
+
+#[test]
+fn test_compound_assignment_desugars() {
+    let mut scanner = scanner::Scanner::new("x += 2".to_string());
+    let tokens = scanner.scan_tokens().unwrap();
+    let expression = parser::Parser::new(tokens).expression().unwrap();
+
+    let Expr::Assign(assign) = expression else {
+        panic!("expected an assignment, got {:?}", expression);
+    };
+    assert_eq!(assign.name.lexeme, "x");
+    let Expr::Binary(binary) = *assign.value else {
+        panic!("expected a binary value, got {:?}", assign.value);
+    };
+    assert_eq!(binary.operator.type_, TokenType::PLUS);
+    assert!(matches!(*binary.left, Expr::Variable(ref v) if v.name.lexeme == "x"));
+    assert!(matches!(*binary.right, Expr::Literal(ref l) if l.value == Object::Int(2)));
+}
+
+#[test]
+fn test_for_loop_with_empty_clauses() {
+    let source = "var n = 0;
+for (;;) { n = n + 1; if (n == 3) break; }
+for (; n < 5;) n = n + 1;"
+        .to_string();
+    let tokens = scanner::Scanner::new(source).scan_tokens().unwrap();
+    let statements = parser::Parser::new(tokens).parse().unwrap();
+    assert_eq!(statements.len(), 3);
+
+    let mut interpreter = interpreter::Interpreter::new();
+    interpreter.interpret(&statements).unwrap();
+    let n = interpreter
+        .globals
+        .borrow()
+        .get(&Token::new(TokenType::IDENTIFIER, "n".to_string(), None, 0))
+        .unwrap();
+    assert_eq!(n, Object::Int(5));
+}
+
+#[test]
+fn test_doc_comment_in_for_header_is_ignored() {
+    let source = "for (\n/// doc\nvar i = 0; i < 2; i = i + 1) print i;".to_string();
+    let tokens = scanner::Scanner::new(source).scan_tokens().unwrap();
+    let statements = parser::Parser::new(tokens).parse().unwrap();
+    assert_eq!(statements.len(), 1);
+
+    let Stmt::Block(block) = &statements[0] else {
+        panic!("expected a desugared for loop, got {:?}", statements[0]);
+    };
+    let Stmt::Var(var) = &block.statements[0] else {
+        panic!("expected the loop variable, got {:?}", block.statements[0]);
+    };
+    assert_eq!(var.name.lexeme, "i");
+    assert_eq!(var.doc, None);
+}
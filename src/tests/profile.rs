@@ -0,0 +1,108 @@
+use std::{rc::Rc, time::Duration};
+
+use crate::{
+    interpreter::{profile::Profiler, Interpreter},
+    parser::Parser,
+    resolver::Resolver,
+    scanner::Scanner,
+};
+
+fn profile(source: &str) -> Rc<Profiler> {
+    let profiler = Rc::new(Profiler::new());
+    let mut interpreter = Interpreter::new().with_profiler(profiler.clone());
+
+    let tokens = Scanner::new(source.to_string()).scan_tokens().unwrap();
+    let statements = Parser::new(tokens).parse().unwrap();
+    Resolver::new(&mut interpreter)
+        .resolve(&statements)
+        .unwrap();
+    let _ = interpreter.interpret(&statements);
+    profiler.finish();
+    profiler
+}
+
+#[test]
+fn test_call_counts_and_lines() {
+    let profiler = profile(
+        "fun fib(n) {
+    if (n < 2) return n;
+    return fib(n - 1) + fib(n - 2);
+}
+fib(10);
+clock();
+",
+    );
+    let functions = profiler.functions();
+    let fib = functions.iter().find(|f| f.name == "fib").unwrap();
+    assert_eq!((fib.line, fib.calls), (Some(1), 177));
+    let clock = functions.iter().find(|f| f.name == "clock").unwrap();
+    assert_eq!((clock.line, clock.calls), (None, 1));
+
+    let lines: Vec<(i64, u64)> = profiler
+        .lines()
+        .into_iter()
+        .map(|(path, line, hits)| {
+            assert_eq!(path, None);
+            (line, hits)
+        })
+        .collect();
+    // Line 2 counts the `if` on every call and its `return` on the 89 base cases.
+    assert_eq!(lines, vec![(2, 266), (3, 88), (1, 1), (5, 1), (6, 1)]);
+}
+
+#[test]
+fn test_recursion_is_not_counted_twice() {
+    let profiler = profile(
+        "fun down(n) {
+    if (n > 0) down(n - 1);
+}
+down(50);
+",
+    );
+    let functions = profiler.functions();
+    let script = functions.iter().find(|f| f.name == "<script>").unwrap();
+    let down = functions.iter().find(|f| f.name == "down").unwrap();
+    assert_eq!(down.calls, 51);
+    assert!(down.inclusive <= script.inclusive);
+    assert!(down.exclusive <= down.inclusive);
+
+    let total: Duration = functions.iter().map(|f| f.exclusive).sum();
+    assert!(total <= script.inclusive);
+}
+
+#[test]
+fn test_folded_stacks() {
+    let profiler = profile(
+        "fun inner() {}
+fun outer() { inner(); inner(); }
+outer();
+inner();
+",
+    );
+    let folded = profiler.folded();
+    let stacks: Vec<&str> = folded
+        .lines()
+        .map(|line| line.rsplit_once(' ').unwrap().0)
+        .collect();
+    assert_eq!(
+        stacks,
+        vec![
+            "<script>",
+            "<script>;inner",
+            "<script>;outer",
+            "<script>;outer;inner"
+        ]
+    );
+    assert!(profiler.report().starts_with("Function"));
+}
+
+#[test]
+fn test_error_still_profiles() {
+    let profiler = profile("fun fail() { return nil + 1; }\nfail();\n");
+    let fail = profiler
+        .functions()
+        .into_iter()
+        .find(|f| f.name == "fail")
+        .unwrap();
+    assert_eq!(fail.calls, 1);
+}
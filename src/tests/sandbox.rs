@@ -0,0 +1,123 @@
+use crate::{
+    callable::Capabilities,
+    interpreter::{
+        sandbox::{LimitError, LimitKind, Limits},
+        Interpreter,
+    },
+    parser::Parser,
+    resolver::Resolver,
+    scanner::Scanner,
+};
+use std::{error::Error, time::Duration};
+
+fn run(source: &str, limits: Limits) -> Result<Interpreter, Box<dyn Error>> {
+    let tokens = Scanner::new(source.to_string()).scan_tokens()?;
+    let statements = Parser::new(tokens).parse()?;
+
+    let mut interpreter = Interpreter::new().with_limits(limits);
+    Resolver::new(&mut interpreter).resolve(&statements)?;
+    interpreter.interpret(&statements)?;
+    Ok(interpreter)
+}
+
+fn limit_kind(source: &str, limits: Limits) -> LimitKind {
+    let err = run(source, limits).unwrap_err();
+    match err.downcast_ref::<LimitError>() {
+        Some(err) => err.kind,
+        None => panic!("expected a LimitError, got {}", err),
+    }
+}
+
+#[test]
+fn test_fuel_budget() {
+    let limits = Limits {
+        fuel: Some(10_000),
+        ..Limits::default()
+    };
+    assert_eq!(
+        limit_kind("while (true) {}", limits.clone()),
+        LimitKind::Fuel
+    );
+    assert!(run("var x = 1 + 2;", limits).is_ok());
+}
+
+#[test]
+fn test_deadline() {
+    let limits = Limits {
+        deadline: Some(Duration::from_millis(50)),
+        ..Limits::default()
+    };
+    assert_eq!(
+        limit_kind("var i = 0; while (true) i = i + 1;", limits),
+        LimitKind::Deadline
+    );
+}
+
+#[test]
+fn test_size_limits() {
+    let limits = Limits {
+        max_string_len: Some(1000),
+        max_collection_len: Some(3),
+        ..Limits::default()
+    };
+    assert_eq!(
+        limit_kind("var s = \"ab\"; while (true) s = s + s;", limits.clone()),
+        LimitKind::StringSize
+    );
+    assert_eq!(
+        limit_kind(
+            "var s = \"a\"; while (true) s = \"${s}${s}\";",
+            limits.clone()
+        ),
+        LimitKind::StringSize
+    );
+    assert_eq!(
+        limit_kind("split(\"a,b,c,d\", \",\");", limits.clone()),
+        LimitKind::CollectionSize
+    );
+    assert!(run("split(\"a,b,c\", \",\");", limits).is_ok());
+}
+
+#[test]
+fn test_call_depth() {
+    let source = "
+    fun a() { return b(); }
+    fun b() { return c(); }
+    fun c() { return 1; }
+    var x = a();
+    ";
+    let limits = |depth| Limits {
+        max_call_depth: Some(depth),
+        ..Limits::default()
+    };
+    assert_eq!(limit_kind(source, limits(2)), LimitKind::CallDepth);
+    assert!(run(source, limits(3)).is_ok());
+}
+
+#[test]
+fn test_capabilities() {
+    let tokens = Scanner::new("clock();".to_string()).scan_tokens().unwrap();
+    let statements = Parser::new(tokens).parse().unwrap();
+
+    let mut interpreter = Interpreter::new().with_capabilities(Capabilities {
+        clock: false,
+        ..Capabilities::default()
+    });
+    Resolver::new(&mut interpreter)
+        .resolve(&statements)
+        .unwrap();
+    let err = interpreter.interpret(&statements).unwrap_err();
+    assert!(err.to_string().contains("Undefined variable 'clock'"));
+}
+
+#[test]
+fn test_limits_are_not_catchable() {
+    let limits = Limits {
+        fuel: Some(1_000),
+        ..Limits::default()
+    };
+    assert_eq!(
+        limit_kind("try { while (true) {} } catch (e) {}", limits),
+        LimitKind::Fuel
+    );
+}
@@ -1,4 +1,7 @@
+use crate::json::Json;
+use crate::object::Object;
 use crate::scanner;
+use crate::token::token_type::TokenType;
 
 #[test]
 pub fn test_variable_declaration() {
@@ -10,7 +13,7 @@ pub fn test_variable_declaration() {
     .to_string();
 
     let mut scanner = scanner::Scanner::new(source);
-    let tokens = scanner.scan_tokens();
+    let tokens = scanner.scan_tokens().unwrap();
     assert_eq!(tokens.len(), 18);
 }
 
@@ -19,7 +22,7 @@ pub fn test_variable_assignment() {
     let source = r#"a = 456;"#.to_string();
 
     let mut scanner = scanner::Scanner::new(source);
-    let tokens = scanner.scan_tokens();
+    let tokens = scanner.scan_tokens().unwrap();
     assert_eq!(tokens.len(), 5);
 }
 
@@ -27,6 +30,296 @@ pub fn test_variable_assignment() {
 pub fn test_string() {
     let source = "\"this is a string\"".to_string();
     let mut scanner = scanner::Scanner::new(source);
-    let tokens = scanner.scan_tokens();
+    let tokens = scanner.scan_tokens().unwrap();
     assert_eq!(tokens.len(), 2);
 }
+
+#[test]
+pub fn test_string_escapes() {
+    let source = r#""tab\there\n\"quoted\" \\ \u{48}\u{1F600}""#.to_string();
+    let mut scanner = scanner::Scanner::new(source);
+    let tokens = scanner.scan_tokens().unwrap();
+    assert_eq!(
+        tokens[0].literal.as_deref(),
+        Some(&Object::String(
+            "tab\there\n\"quoted\" \\ H\u{1F600}".to_string()
+        ))
+    );
+}
+
+#[test]
+pub fn test_invalid_escape() {
+    let source = r#""bad \q escape""#.to_string();
+    let mut scanner = scanner::Scanner::new(source);
+    let err = scanner.scan_tokens().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "ScanError [line 1]  at '\"bad \\q': Invalid escape sequence '\\q'."
+    );
+}
+
+#[test]
+pub fn test_string_interpolation() {
+    let source = r#""Hello ${name}, ${ { x } } and ${"${x}"}!""#.to_string();
+    let mut scanner = scanner::Scanner::new(source);
+    let tokens = scanner.scan_tokens().unwrap();
+    let types: Vec<TokenType> = tokens.into_iter().map(|token| token.type_).collect();
+
+    use TokenType::*;
+    assert_eq!(
+        types,
+        vec![
+            INTERPOLATION,
+            IDENTIFIER,
+            INTERPOLATION,
+            LEFT_BRACE,
+            IDENTIFIER,
+            RIGHT_BRACE,
+            INTERPOLATION,
+            INTERPOLATION,
+            IDENTIFIER,
+            STRING,
+            STRING,
+            EOF
+        ]
+    );
+}
+
+#[test]
+pub fn test_unterminated_interpolation() {
+    let source = r#""Hello ${name"#.to_string();
+    let mut scanner = scanner::Scanner::new(source);
+    assert!(scanner.scan_tokens().is_err());
+}
+
+#[test]
+pub fn test_unicode_source() {
+    let source = "// ¿qué tal? 😀\nvar café = \"naïve ☕\"; print café;".to_string();
+    let mut scanner = scanner::Scanner::new(source);
+    let tokens = scanner.scan_tokens().unwrap();
+    assert_eq!(tokens.len(), 9);
+    assert_eq!(tokens[1].lexeme, "café");
+    assert_eq!(tokens[3].lexeme, "\"naïve ☕\"");
+    assert_eq!(
+        tokens[3].literal.as_deref(),
+        Some(&Object::String("naïve ☕".to_string()))
+    );
+    assert_eq!(tokens[6].lexeme, "café");
+    assert_eq!(tokens[6].line, 2);
+}
+
+#[test]
+pub fn test_large_input() {
+    let line = "var π_ü = \"ünïcödé ✓\" + 12.5; // ✓ comment ✓\n";
+    let repeat = 4 * 1024 * 1024 / line.len();
+    let source = line.repeat(repeat);
+
+    let mut scanner = scanner::Scanner::new(source);
+    let tokens = scanner.scan_tokens().unwrap();
+    assert_eq!(tokens.len(), 7 * repeat + 1);
+    assert_eq!(tokens[tokens.len() - 1].line, repeat as i64 + 1);
+}
+
+#[test]
+pub fn test_block_comments() {
+    let source = "/* outer /* nested\n */ still\n comment */ var a = 1; /**/ print a;".to_string();
+    let mut scanner = scanner::Scanner::new(source);
+    let tokens = scanner.scan_tokens().unwrap();
+    assert_eq!(tokens.len(), 9);
+    assert_eq!(tokens[0].line, 3);
+}
+
+#[test]
+pub fn test_unterminated_block_comment() {
+    let source = "var a = 1;\n/* open /* nested */\nvar b = 2;".to_string();
+    let mut scanner = scanner::Scanner::new(source);
+    let err = scanner.scan_tokens().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "ScanError [line 2]  at '/*': Unterminated block comment."
+    );
+}
+
+#[test]
+pub fn test_doc_comments() {
+    let source = "/// Adds one.\n//// not a doc comment\n// nor this\nvar x;".to_string();
+    let mut scanner = scanner::Scanner::new(source);
+    let tokens = scanner.scan_tokens().unwrap();
+    assert_eq!(tokens[0].type_, TokenType::DOC_COMMENT);
+    assert_eq!(
+        tokens[0].literal.as_deref(),
+        Some(&Object::String("Adds one.".to_string()))
+    );
+    assert_eq!(tokens.len(), 5);
+}
+
+#[test]
+pub fn test_number_literals() {
+    let source = "0xFF 0b1010 1_000_000 1.5e-3 2E10 0x_1 12.5_0".to_string();
+    let mut scanner = scanner::Scanner::new(source);
+    let result = scanner.scan_tokens();
+    assert!(result.is_err());
+
+    let source = "0xFF 0b1010 1_000_000 1.5e-3 2E10 0xdead_BEEF 12.5".to_string();
+    let mut scanner = scanner::Scanner::new(source);
+    let values: Vec<Object> = scanner
+        .scan_tokens()
+        .unwrap()
+        .into_iter()
+        .filter_map(|token| token.literal.map(|literal| *literal))
+        .collect();
+    assert!(matches!(values[0], Object::Int(255)));
+    assert!(matches!(values[3], Object::Number(_)));
+    assert_eq!(
+        values,
+        vec![
+            Object::Int(255),
+            Object::Int(10),
+            Object::Int(1_000_000),
+            Object::Number(1.5e-3),
+            Object::Number(2e10),
+            Object::Int(3_735_928_559),
+            Object::Number(12.5),
+        ]
+    );
+}
+
+#[test]
+pub fn test_large_integer_literals() {
+    let source =
+        "9223372036854775807 9223372036854775808 0xFFFF_FFFF_FFFF_FFFF 0x8000_0000_0000_0000 0b1"
+            .to_string();
+    let values: Vec<Object> = scanner::Scanner::new(source)
+        .scan_tokens()
+        .unwrap()
+        .into_iter()
+        .filter_map(|token| token.literal.map(|literal| *literal))
+        .collect();
+    assert!(matches!(values[1], Object::Number(_)));
+    assert_eq!(
+        values,
+        vec![
+            Object::Int(i64::MAX),
+            Object::Number(9223372036854775808.0),
+            Object::Int(-1),
+            Object::Int(i64::MIN),
+            Object::Int(1),
+        ]
+    );
+}
+
+#[test]
+pub fn test_malformed_number_literals() {
+    let cases = [
+        (
+            "0x",
+            "ScanError [line 1]  at '0x': Expect digits after number prefix.",
+        ),
+        (
+            "0b102",
+            "ScanError [line 1]  at '0b102': Invalid character in number literal.",
+        ),
+        (
+            "1__000",
+            "ScanError [line 1]  at '1_': Underscores in numbers must separate digits.",
+        ),
+        (
+            "100_",
+            "ScanError [line 1]  at '100_': Underscores in numbers must separate digits.",
+        ),
+        (
+            "1.5e",
+            "ScanError [line 1]  at '1.5e': Expect digits in exponent.",
+        ),
+        (
+            "12abc",
+            "ScanError [line 1]  at '12a': Invalid character in number literal.",
+        ),
+        (
+            "0x1_0000_0000_0000_0000",
+            "ScanError [line 1]  at '0x1_0000_0000_0000_0000': Number literal is too large.",
+        ),
+    ];
+
+    for (source, message) in cases {
+        let mut scanner = scanner::Scanner::new(source.to_string());
+        let err = scanner.scan_tokens().unwrap_err();
+        assert_eq!(err.to_string(), message);
+    }
+}
+
+#[test]
+fn test_token_columns() {
+    let source = "var s = \"hé\";\n  print s;".to_string();
+    let tokens = scanner::Scanner::new(source).scan_tokens().unwrap();
+
+    let positions: Vec<(i64, usize)> = tokens.iter().map(|t| (t.line, t.column)).collect();
+    assert_eq!(
+        positions,
+        vec![
+            (1, 1),
+            (1, 5),
+            (1, 7),
+            (1, 9),
+            (1, 13),
+            (2, 3),
+            (2, 9),
+            (2, 10),
+            (2, 11)
+        ]
+    );
+}
+
+#[test]
+fn test_token_dump() {
+    let tokens = scanner::Scanner::new("x = \"a\\n\" + 2;".to_string())
+        .scan_tokens()
+        .unwrap();
+
+    assert_eq!(tokens[0].to_string(), "1:1     IDENTIFIER      x");
+    assert_eq!(
+        tokens[2].to_string(),
+        "1:5     STRING          \"a\\n\"           \"a\\n\""
+    );
+    assert_eq!(
+        tokens[2].to_json().to_string(),
+        r#"{"type":"STRING","lexeme":"\"a\\n\"","literal":"a\n","line":1,"column":5}"#
+    );
+    assert_eq!(
+        tokens[4].to_json().to_string(),
+        r#"{"type":"NUMBER","lexeme":"2","literal":2,"line":1,"column":13}"#
+    );
+}
+
+#[test]
+fn test_token_json_numbers_are_exact() {
+    let tokens = scanner::Scanner::new("9007199254740993 2.0".to_string())
+        .scan_tokens()
+        .unwrap();
+    let literals: Vec<String> = tokens
+        .iter()
+        .map(|token| token.to_json().get("literal").unwrap().to_string())
+        .collect();
+
+    assert_eq!(literals, vec!["9007199254740993", "2.0", "null"]);
+    assert_eq!(
+        tokens[0].to_string(),
+        "1:1     NUMBER          9007199254740993 9007199254740993"
+    );
+    let json = Json::parse(&tokens[0].to_json().to_string()).unwrap();
+    assert_eq!(json.get("literal"), Some(&Json::Int(9007199254740993)));
+}
+
+#[test]
+fn test_long_single_line_scans_in_linear_time() {
+    // 200k tokens on one line; rescanning the line for each token's column took minutes.
+    let count = 100_000;
+    let source = "é+".repeat(count) + "x";
+    let started = std::time::Instant::now();
+    let tokens = scanner::Scanner::new(source).scan_tokens().unwrap();
+    assert!(started.elapsed() < std::time::Duration::from_secs(5));
+
+    let last = &tokens[tokens.len() - 2];
+    assert_eq!((last.lexeme.as_str(), last.column), ("x", 2 * count + 1));
+    assert_eq!(tokens.last().unwrap().column, 2 * count + 2);
+}
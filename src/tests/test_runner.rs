@@ -0,0 +1,107 @@
+use std::fs;
+
+use crate::{
+    interpreter::sandbox::Limits,
+    test_runner::{discover, run_file, test_names},
+};
+
+#[test]
+fn test_discovers_test_functions() {
+    let names = test_names(
+        "fun test_one() {}\nfun helper() {}\n{ fun test_nested() {} }\nfun test_two() {}\n",
+    )
+    .unwrap();
+    assert_eq!(names, vec!["test_one", "test_two"]);
+}
+
+#[test]
+fn test_runs_each_test_in_a_fresh_interpreter() {
+    let root = std::env::temp_dir().join(format!("lox-test-runner-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(root.join("nested")).unwrap();
+    fs::write(
+        root.join("counter.lox"),
+        "var count = 0;
+fun test_first() { count = count + 1; assert_eq(count, 1); }
+fun test_second() { count = count + 1; assert_eq(count, 1); }
+fun test_fails() {
+    assert_eq(count, 5, \"count\");
+}
+",
+    )
+    .unwrap();
+    fs::write(root.join("nested/more.lox"), "fun test_more() {}\n").unwrap();
+    fs::write(root.join("notes.txt"), "fun test_ignored() {}\n").unwrap();
+
+    let files = discover(&root).unwrap();
+    let names: Vec<String> = files
+        .iter()
+        .map(|file| file.strip_prefix(&root).unwrap().display().to_string())
+        .collect();
+    assert_eq!(names, vec!["counter.lox", "nested/more.lox"]);
+
+    let results = run_file(&files[0], &Limits::default()).unwrap();
+    fs::remove_dir_all(&root).unwrap();
+    let outcomes: Vec<(&str, Result<(), &str>)> = results
+        .iter()
+        .map(|result| {
+            (
+                result.name.as_str(),
+                result.outcome.as_ref().map(|_| ()).map_err(String::as_str),
+            )
+        })
+        .collect();
+    assert_eq!(
+        outcomes,
+        vec![
+            ("test_first", Ok(())),
+            ("test_second", Ok(())),
+            (
+                "test_fails",
+                Err("RuntimeError [line 5] : assert_eq failed (count): 0 != 5.")
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_tests_run_within_the_call_depth() {
+    let path = std::env::temp_dir().join(format!("lox-test-depth-{}.lox", std::process::id()));
+    fs::write(
+        &path,
+        "fun forever() { return forever(); }
+fun fail() { throw \"no\"; }
+fun fail_deeper() { fail(); }
+fun test_recursion() { forever(); }
+fun test_throws() { assert_throws(fail); }
+fun test_throws_deeper() { assert_throws(fail_deeper); }
+",
+    )
+    .unwrap();
+
+    let limits = Limits {
+        max_call_depth: Some(3),
+        ..Limits::default()
+    };
+    let results = run_file(&path, &limits).unwrap();
+    fs::remove_file(&path).unwrap();
+    let outcomes: Vec<(&str, Result<(), &str>)> = results
+        .iter()
+        .map(|result| {
+            (
+                result.name.as_str(),
+                result.outcome.as_ref().map(|_| ()).map_err(String::as_str),
+            )
+        })
+        .collect();
+    // The test, `assert_throws` and the function it calls each take a level.
+    let exceeded = "LimitError (call depth): Call depth of 3 exceeded.";
+    assert_eq!(
+        outcomes,
+        vec![
+            ("test_recursion", Err(exceeded)),
+            ("test_throws", Ok(())),
+            ("test_throws_deeper", Err(exceeded)),
+        ]
+    );
+}
@@ -1,5 +1,8 @@
 pub mod token_type;
 
+use std::fmt;
+
+use crate::json::Json;
 use crate::object::Object;
 use token_type::TokenType;
 
@@ -9,6 +12,8 @@ pub struct Token {
     pub lexeme: String,
     pub literal: Option<Box<Object>>,
     pub line: i64,
+    // 1-based, in characters; 0 for tokens the parser synthesizes while desugaring.
+    pub column: usize,
 }
 
 impl Token {
@@ -16,12 +21,51 @@ impl Token {
         Self {
             type_,
             lexeme,
-            literal: literal.map(|x| Box::new(x)),
+            literal: literal.map(Box::new),
             line,
+            column: 0,
         }
     }
 
-    pub fn to_string(&self) -> String {
-        format!("{:?} {} {:?}", self.type_, self.lexeme, self.literal)
+    pub fn with_column(self, column: usize) -> Self {
+        Self { column, ..self }
+    }
+
+    pub fn to_json(&self) -> Json {
+        let literal = match self.literal.as_deref() {
+            Some(Object::String(s)) => Json::from(s.as_str()),
+            Some(Object::Int(i)) => Json::from(*i),
+            Some(Object::Number(n)) => Json::Number(*n),
+            Some(Object::Boolean(b)) => Json::Bool(*b),
+            Some(other) => Json::from(other.to_string()),
+            None => Json::Null,
+        };
+        Json::object([
+            ("type", Json::from(format!("{:?}", self.type_))),
+            ("lexeme", Json::from(self.lexeme.as_str())),
+            ("literal", literal),
+            ("line", Json::from(self.line)),
+            ("column", Json::from(self.column)),
+        ])
+    }
+}
+
+/// `line:column  TYPE  lexeme  literal`, as printed by `lox --tokens`. Each column is
+/// followed by at least one space, so a wide lexeme doesn't run into its literal.
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let position = format!("{}:{}", self.line, self.column);
+        let type_ = format!("{:?}", self.type_);
+        let line = match self.literal.as_deref() {
+            Some(Object::String(s)) => {
+                format!("{:<7} {:<15} {:<15} {:?}", position, type_, self.lexeme, s)
+            }
+            Some(literal) => format!(
+                "{:<7} {:<15} {:<15} {}",
+                position, type_, self.lexeme, literal
+            ),
+            None => format!("{:<7} {:<15} {}", position, type_, self.lexeme),
+        };
+        write!(f, "{}", line.trim_end())
     }
 }
@@ -1,5 +1,5 @@
 #[derive(Debug, Clone, PartialEq)]
-#[allow(non_camel_case_types)]
+#[allow(non_camel_case_types, clippy::upper_case_acronyms)]
 pub enum TokenType {
     // Single-character tokens.
     LEFT_PAREN,
@@ -13,6 +13,12 @@ pub enum TokenType {
     SEMICOLON,
     SLASH,
     STAR,
+    PERCENT,
+    AMPERSAND,
+    PIPE,
+    CARET,
+    QUESTION,
+    COLON,
 
     // One or two character tokens.
     BANG,
@@ -21,31 +27,59 @@ pub enum TokenType {
     EQUAL_EQUAL,
     GREATER,
     GREATER_EQUAL,
+    GREATER_GREATER,
     LESS,
     LESS_EQUAL,
+    LESS_LESS,
+    MINUS_EQUAL,
+    MINUS_MINUS,
+    PERCENT_EQUAL,
+    PLUS_EQUAL,
+    PLUS_PLUS,
+    QUESTION_QUESTION,
+    SLASH_EQUAL,
+    STAR_EQUAL,
+    STAR_STAR,
 
     // Literals.
     IDENTIFIER,
     STRING,
     NUMBER,
+    // A string segment that is followed by an interpolated `${...}` expression.
+    INTERPOLATION,
+    // A `///` comment documenting the declaration that follows it.
+    DOC_COMMENT,
+    // An ordinary `//` or `/* */` comment, only kept when the scanner retains comments.
+    COMMENT,
 
     // Keywords.
     AND,
+    AS,
+    BREAK,
+    CATCH,
     CLASS,
+    CONTINUE,
+    DIV,
     ELSE,
     FALSE,
+    FINALLY,
     FUN,
     FOR,
     IF,
+    IMPORT,
     NIL,
     OR,
     PRINT,
     RETURN,
     SUPER,
     THIS,
+    THROW,
     TRUE,
+    TRY,
     VAR,
     WHILE,
 
+    // Marks the lexeme a scan error was reported at.
+    ERROR,
     EOF,
 }